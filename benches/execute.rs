@@ -0,0 +1,155 @@
+/*
+    Instruction throughput of `CPU::execute` on a few hand-assembled ROMs.
+    Each ROM loops forever with balanced stacks, so it can run for any
+    number of steps. Run with `cargo bench`.
+
+    The hosted runs add the device and DMA checks a host makes, either after
+    every instruction by scanning every slot, or after every block for only
+    the slots the CPU wrote to.
+*/
+use cohost::core::{DeviceSlot, CPU, DMA};
+use std::time::{Duration, Instant};
+
+const STEPS: usize = 20_000_000;
+const RUNS: usize = 5;
+
+const ARITHMETIC: &[u8] = &[
+    0xB0, 0x07, //  LIT8 7
+    0xB0, 0x05, //  LIT8 5
+    0x60, //        ADD8
+    0xB0, 0x03, //  LIT8 3
+    0x68, //        MUL8
+    0xB0, 0x02, //  LIT8 2
+    0x6C, //        DIV8
+    0xFC, //        DRD
+    0xB0, 0x00, //  LIT8 0
+    0x08, //        JMP8
+];
+
+const WIDE_ARITHMETIC: &[u8] = &[
+    0xB3, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, // LIT64
+    0xB3, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // LIT64
+    0x63, //        ADD64
+    0xB3, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // LIT64
+    0x73, //        GRT64
+    0xFC, //        DRD
+    0xB0, 0x00, //  LIT8 0
+    0x08, //        JMP8
+];
+
+const MEMORY: &[u8] = &[
+    0xB1, 0x00, 0x01, // LIT16 0x0100
+    0xB5, //             ADR16
+    0xB3, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, // LIT64
+    0xBB, //             STR64
+    0xBF, //             LOD64
+    0x7B, //             EQU64
+    0xB0, 0x00, //       LIT8 0
+    0x0C, //             JPC8
+];
+
+const STACKS: &[u8] = &[
+    0xB0, 0x2A, //  LIT8 42
+    0xC4, //        CDS8
+    0xD8, //        CSR8
+    0xE0, //        CRD8
+    0xCC, //        CDH8
+    0xF0, //        CHD8
+    0x60, //        ADD8
+    0x60, //        ADD8
+    0xFC, //        DRD
+    0xFD, //        DRS
+    0xFE, //        DRR
+    0xB0, 0x00, //  LIT8 0
+    0x08, //        JMP8
+];
+
+fn run(rom: &[u8]) -> Duration {
+    let mut cpu = Box::new(CPU::new());
+    cpu.load_rom(rom.to_vec());
+
+    let start = Instant::now();
+    for _ in 0..STEPS {
+        cpu.execute().expect("benchmark rom faulted");
+    }
+    std::hint::black_box(&cpu);
+    start.elapsed()
+}
+
+fn run_hosted(rom: &[u8], blocks: bool) -> Duration {
+    let mut cpu = Box::new(CPU::new());
+    cpu.load_rom(rom.to_vec());
+
+    let start = Instant::now();
+    let mut executed = 0;
+    while executed < STEPS {
+        match blocks {
+            true => {
+                executed += cpu.execute_block().expect("benchmark rom faulted");
+                service_events(&mut cpu);
+            }
+            false => {
+                cpu.execute().expect("benchmark rom faulted");
+                executed += 1;
+                service_all(&mut cpu);
+            }
+        }
+    }
+    std::hint::black_box(&cpu);
+    start.elapsed()
+}
+
+fn service_all(cpu: &mut CPU) {
+    for dma in cpu.dma_controllers {
+        std::hint::black_box(dma.status_reg & DMA::REQ_BIT);
+    }
+    for slot in cpu.devices {
+        std::hint::black_box(slot.status_reg & DeviceSlot::SEND_FLAG);
+    }
+}
+
+fn service_events(cpu: &mut CPU) {
+    let dmas = cpu.take_dma_events();
+    let devices = cpu.take_device_events();
+    for (index, dma) in cpu.dma_controllers.iter().enumerate() {
+        if dmas & (1 << index) != 0 {
+            std::hint::black_box(dma.status_reg & DMA::REQ_BIT);
+        }
+    }
+    for (index, slot) in cpu.devices.iter().enumerate() {
+        if devices & (1 << index) != 0 {
+            std::hint::black_box(slot.status_reg & DeviceSlot::SEND_FLAG);
+        }
+    }
+}
+
+fn report(name: &str, best: Duration) {
+    let rate = STEPS as f64 / best.as_secs_f64() / 1_000_000.0;
+    println!(
+        "{:<28} {:>8.2} ms  {:>8.1} M instructions/s",
+        name,
+        best.as_secs_f64() * 1000.0,
+        rate
+    );
+}
+
+fn main() {
+    let suite = [
+        ("nop", &[0u8; 16][..]),
+        ("arithmetic", ARITHMETIC),
+        ("wide arithmetic", WIDE_ARITHMETIC),
+        ("memory", MEMORY),
+        ("stacks", STACKS),
+    ];
+
+    for (name, rom) in suite {
+        report(name, (0..RUNS).map(|_| run(rom)).min().unwrap());
+    }
+    println!();
+    for (name, rom) in suite {
+        let instructions = (0..RUNS).map(|_| run_hosted(rom, false)).min().unwrap();
+        report(&format!("{}, hosted", name), instructions);
+        let blocks = (0..RUNS).map(|_| run_hosted(rom, true)).min().unwrap();
+        report(&format!("{}, hosted by block", name), blocks);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod diagnostic;
 pub mod parsing;
 pub mod representation;
+pub mod stdlib;
 pub mod tokens;
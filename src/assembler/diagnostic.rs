@@ -0,0 +1,292 @@
+use crate::core::{opcode_to_str, str_to_opcode};
+use std::fmt::Display;
+
+/*
+    A problem found in `.co` source, pointing at the text it's about:
+
+        error: Dangling string literal
+          --> example.co:11:9
+           |
+        10 | : main
+           | - in routine `main`
+        11 |     LIT8 1 ADD9
+           |            ^^^^ not an opcode or label
+           |
+           = note: did you mean `ADD8`?
+
+    The primary label is where the problem is, and is underlined with `^`.
+    Secondary labels point at related text, like the definition an error
+    is inside of, and are underlined with `-`.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Annotation>,
+    pub notes: Vec<String>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    pub span: Span,
+    pub message: String,
+    pub primary: bool,
+}
+
+// a stretch of source text, by byte offset, and where it starts for people
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message.into())
+    }
+    pub fn warning(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message.into())
+    }
+    fn new(severity: Severity, message: String) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn primary(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Annotation {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+    pub fn secondary(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Annotation {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+    pub fn note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    // where the problem is, if it points anywhere
+    pub fn span(&self) -> Option<Span> {
+        let primary = self.labels.iter().find(|label| label.primary);
+        primary.or(self.labels.first()).map(|label| label.span)
+    }
+
+    // the diagnostic with the lines of `source` it points at, for `path`
+    pub fn render(&self, source: &str, path: &str) -> String {
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        let Some(span) = self.span() else {
+            for note in &self.notes {
+                out += &format!("  = note: {}\n", note);
+            }
+            return out;
+        };
+
+        let mut labels: Vec<&Annotation> = self.labels.iter().collect();
+        labels.sort_by_key(|label| (label.span.line, label.span.column));
+        let gutter = labels.iter().map(|l| l.span.line).max().unwrap_or(1);
+        let gutter = gutter.to_string().len();
+        let blank = format!("{} |", " ".repeat(gutter));
+
+        out += &format!(
+            "{}--> {}:{}:{}\n",
+            " ".repeat(gutter),
+            path,
+            span.line,
+            span.column
+        );
+        out += &format!("{}\n", blank);
+        let lines: Vec<&str> = source.lines().collect();
+        let mut shown = 0;
+        for label in labels {
+            let text = lines.get(label.span.line - 1).copied().unwrap_or("");
+            let text = text.trim_end_matches('\r');
+            if label.span.line != shown {
+                out += &format!("{:>gutter$} | {}\n", label.span.line, text);
+                shown = label.span.line;
+            }
+            let width = source
+                .get(label.span.start..label.span.end)
+                .map(|s| s.lines().next().unwrap_or("").chars().count())
+                .unwrap_or(0)
+                .max(1);
+            let mark = match label.primary {
+                true => "^",
+                false => "-",
+            };
+            let indent: String = text
+                .chars()
+                .take(label.span.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            out += &format!("{} {}{}", blank, indent, mark.repeat(width));
+            match label.message.is_empty() {
+                true => out += "\n",
+                false => out += &format!(" {}\n", label.message),
+            }
+        }
+        if !self.notes.is_empty() {
+            out += &format!("{}\n", blank);
+        }
+        for note in &self.notes {
+            out += &format!("{} = note: {}\n", " ".repeat(gutter), note);
+        }
+        out
+    }
+}
+
+// the diagnostics from one run, keeping the first `limit` but counting all
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub list: Vec<Diagnostic>,
+    pub limit: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+impl Diagnostics {
+    pub const DEFAULT_LIMIT: usize = 20;
+
+    pub fn new(limit: usize) -> Diagnostics {
+        Diagnostics {
+            list: Vec::new(),
+            limit,
+            errors: 0,
+            warnings: 0,
+        }
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        match diagnostic.severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+        }
+        if self.list.len() < self.limit {
+            self.list.push(diagnostic);
+        }
+    }
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+    pub fn is_empty(&self) -> bool {
+        self.errors + self.warnings == 0
+    }
+
+    // every diagnostic kept, in source order, then a count of them all
+    pub fn render(&self, source: &str, path: &str) -> String {
+        let mut list: Vec<&Diagnostic> = self.list.iter().collect();
+        list.sort_by_key(|diagnostic| diagnostic.span().map(|span| span.start));
+        let mut out: Vec<String> = list
+            .into_iter()
+            .map(|diagnostic| diagnostic.render(source, path))
+            .collect();
+        let hidden = self.errors + self.warnings - self.list.len();
+        if hidden > 0 {
+            out.push(format!(
+                "{} more not shown (limit {})\n",
+                hidden, self.limit
+            ));
+        }
+        out.push(format!("{}\n", self));
+        out.join("\n")
+    }
+}
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new(Diagnostics::DEFAULT_LIMIT)
+    }
+}
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize, noun: &str| match count {
+            1 => format!("1 {}", noun),
+            count => format!("{} {}s", count, noun),
+        };
+        write!(
+            f,
+            "{}, {}",
+            plural(self.errors, "error"),
+            plural(self.warnings, "warning")
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span() {
+            Some(span) => write!(
+                f,
+                "{}:{}: {}: {}",
+                span.line, span.column, self.severity, self.message
+            ),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+impl From<Diagnostic> for String {
+    fn from(diagnostic: Diagnostic) -> Self {
+        diagnostic.to_string()
+    }
+}
+
+// the opcode mnemonic `word` is most likely a misspelling of, if any
+pub fn suggest_opcode(word: &str) -> Option<&'static str> {
+    let upper = word.to_uppercase();
+    let mnemonics = (0..=u8::MAX)
+        .map(opcode_to_str)
+        .filter(|name| str_to_opcode(name).is_some());
+    let (distance, name) = mnemonics
+        .map(|name| (edit_distance(&upper, name), name))
+        .min()?;
+    match distance <= 1 {
+        true => Some(name),
+        false => None,
+    }
+}
+
+// edits to turn `a` into `b`, where swapping two neighbouring characters
+// counts as one, like other typos
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -1,80 +1,233 @@
-use crate::assembler::tokens::COMMENT_CLOSE;
-use crate::assembler::tokens::{Rune, TextToken};
-use core::str::Chars;
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics, Span};
+use crate::assembler::representation::Module;
+use crate::assembler::tokens::{is_delimiter, validate_name, Spanned, TextToken};
+use crate::assembler::tokens::{COMMENT_CLOSE, COMMENT_OPEN, STRING_QUOTE};
 use std::iter::Peekable;
+use std::str::CharIndices;
 
-pub fn string_until(close_char: char, chars: &mut Peekable<Chars>) -> Result<String, String> {
-    let mut found = false;
-    let mut string = String::new();
-    while let Some(c) = chars.next() {
+// the module in `text`, if it has no errors; every diagnostic goes to `diagnostics`
+pub fn parse_module(text: &str, diagnostics: &mut Diagnostics) -> Option<Module> {
+    let errors = diagnostics.errors;
+    let tokens = parse_text(text, diagnostics);
+    let module = Module::from_text_tokens(tokens, diagnostics);
+    (diagnostics.errors == errors).then_some(module)
+}
+
+/*
+    The lexer reads a character at a time. A token runs until whitespace or a
+    delimiter: a comment's `(` or `)`, or one of the runes `;`, `[` and `]`,
+    which are tokens on their own. Comments nest, so `( a (b) c )` is one
+    comment, and can sit right against a token, like `%name(doc)`. A string in
+    `"` quotes can hold spaces and reserved characters, with `\"` and `\\` for
+    a quote and a backslash.
+
+    Lines end with `\n` or `\r\n`. The indentation starting a line is read as
+    one `Tab` token, counting a tab or `TAB_WIDTH` spaces as a level.
+*/
+
+// spaces that make up one level of indentation
+pub const TAB_WIDTH: usize = 4;
+
+// tokens that can't be read are reported and skipped
+pub fn parse_text(assembly_text: &str, diagnostics: &mut Diagnostics) -> Vec<Spanned> {
+    let mut lexer = Lexer {
+        text: assembly_text,
+        chars: assembly_text.char_indices().peekable(),
+        line: 1,
+        line_start: 0,
+        tokens: Vec::new(),
+    };
+    lexer.indentation();
+    while let Some(&(i, c)) = lexer.chars.peek() {
         match c {
-            c if c == close_char => {
-                found = true;
-                break;
+            '\r' | '\n' => lexer.new_line(),
+            COMMENT_OPEN => lexer.comment(diagnostics),
+            COMMENT_CLOSE => {
+                lexer.chars.next();
+                diagnostics.push(
+                    Diagnostic::error("Unmatched comment close")
+                        .primary(lexer.span(i, i + 1), "there's no comment to close"),
+                );
+            }
+            STRING_QUOTE => lexer.string(diagnostics),
+            c if c.is_whitespace() => {
+                lexer.chars.next();
             }
-            _ => string.push(c),
+            _ => lexer.word(diagnostics),
         }
     }
-    match found {
-        true => Ok(string),
-        false => Err(format!(
-            "string_until: Didn't find close_char `{}`",
-            close_char
-        )),
-    }
+    lexer.tokens
 }
-pub fn parse_text(assembly_text: &str) -> Result<Vec<TextToken>, String> {
-    fn handle_token(buffer: &mut String, text_tokens: &mut Vec<TextToken>) -> Result<(), String> {
-        if !buffer.is_empty() {
-            let string = buffer.clone();
-            let token = string.parse()?;
-            text_tokens.push(token);
-            buffer.clear();
+
+struct Lexer<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    line_start: usize,
+    tokens: Vec<Spanned>,
+}
+impl Lexer<'_> {
+    fn span(&self, start: usize, end: usize) -> Span {
+        let column = self.text[self.line_start..start].chars().count() + 1;
+        Span {
+            start,
+            end,
+            line: self.line,
+            column,
         }
-        Ok(())
+    }
+    fn push(&mut self, token: TextToken, span: Span) {
+        self.tokens.push(Spanned { token, span });
+    }
+    // the offset of the next character
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.text.len(), |&(i, _)| i)
     }
 
-    let mut text_tokens = Vec::new();
-    let mut buffer = String::new();
-    let mut chars = assembly_text.chars().peekable();
-    while let Some(c) = chars.next() {
-        match c {
-            '\n' => {
-                handle_token(&mut buffer, &mut text_tokens)?;
-                text_tokens.push(TextToken::NewLine);
+    // consumes a `\n` or `\r\n`, returning where the next line starts
+    fn line_break(&mut self) -> Option<usize> {
+        let (i, c) = self.chars.next_if(|&(_, c)| c == '\r' || c == '\n')?;
+        let end = match c {
+            '\r' => self
+                .chars
+                .next_if(|&(_, c)| c == '\n')
+                .map_or(i + 1, |(i, _)| i + 1),
+            _ => i + 1,
+        };
+        self.line += 1;
+        self.line_start = end;
+        Some(end)
+    }
+
+    fn new_line(&mut self) {
+        let start = self.offset();
+        let span = self.span(start, start);
+        let Some(end) = self.line_break() else {
+            return;
+        };
+        self.push(TextToken::NewLine, Span { end, ..span });
+        self.indentation();
+    }
+
+    fn indentation(&mut self) {
+        let start = self.offset();
+        let (mut tabs, mut spaces) = (0, 0);
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| c == '\t' || c == ' ') {
+            match c {
+                '\t' => tabs += 1,
+                _ => spaces += 1,
+            }
+        }
+        let level = tabs + spaces / TAB_WIDTH;
+        if level > 0 {
+            let end = self.offset();
+            let span = self.span(start, end);
+            self.push(TextToken::Tab(level.min(u8::MAX as usize) as u8), span);
+        }
+    }
 
-                // look for indentation after a new line
-                let mut num_tabs = 0;
-                while let Some(c) = chars.peek() {
+    fn comment(&mut self, diagnostics: &mut Diagnostics) {
+        let start = self.offset();
+        let open = self.span(start, start + 1);
+        self.chars.next();
+
+        let mut comment = String::new();
+        let mut depth = 1;
+        loop {
+            match self.chars.peek().copied() {
+                Some((i, COMMENT_CLOSE)) if depth == 1 => {
+                    self.chars.next();
+                    let span = Span { end: i + 1, ..open };
+                    self.push(TextToken::Comment(comment.trim().to_string()), span);
+                    return;
+                }
+                Some((_, '\r' | '\n')) => {
+                    self.line_break();
+                    comment.push('\n');
+                }
+                Some((_, c)) => {
                     match c {
-                        '\t' => {
-                            num_tabs += 1;
-                            chars.next();
-                        }
-                        _ => break,
+                        COMMENT_OPEN => depth += 1,
+                        COMMENT_CLOSE => depth -= 1,
+                        _ => {}
                     }
+                    self.chars.next();
+                    comment.push(c);
                 }
-                if num_tabs > 0 {
-                    text_tokens.push(TextToken::Tab(num_tabs));
+                None => {
+                    diagnostics.push(
+                        Diagnostic::error("Comment is never closed")
+                            .primary(open, "this comment")
+                            .note(format!("comments end with `{}`", COMMENT_CLOSE)),
+                    );
+                    return;
                 }
             }
-            c if c.is_whitespace() => {
-                handle_token(&mut buffer, &mut text_tokens)?;
+        }
+    }
 
-                // if we just added a TextToken::Rune(Rune::OpenComment)
-                if matches!(text_tokens.last(), Some(TextToken::Rune(Rune::OpenComment))) {
-                    // pop the TextToken::Rune
-                    text_tokens.pop();
+    fn string(&mut self, diagnostics: &mut Diagnostics) {
+        let start = self.offset();
+        self.chars.next();
 
-                    // replace with parsed TextToken::Comment
-                    let comment = string_until(COMMENT_CLOSE, &mut chars)?;
-                    let comment = comment.trim().to_string();
-                    text_tokens.push(TextToken::Comment(comment));
+        let mut string = String::new();
+        loop {
+            match self.chars.peek().copied() {
+                Some((i, STRING_QUOTE)) => {
+                    self.chars.next();
+                    let span = self.span(start, i + 1);
+                    match validate_name(&string) {
+                        Ok(()) => self.push(TextToken::StringLiteral(string), span),
+                        Err(e) => diagnostics.push(
+                            Diagnostic::error("String can't be used as a name").primary(span, e),
+                        ),
+                    }
+                    return;
+                }
+                Some((_, '\\')) => {
+                    self.chars.next();
+                    match self.chars.next_if(|&(_, c)| c == STRING_QUOTE || c == '\\') {
+                        Some((_, c)) => string.push(c),
+                        None => string.push('\\'),
+                    }
+                }
+                Some((_, '\r' | '\n')) | None => {
+                    let end = self.offset();
+                    let span = self.span(start, end);
+                    diagnostics.push(
+                        Diagnostic::error("String is never closed")
+                            .primary(span, "this string")
+                            .note(format!(
+                                "strings end with `{}` on the same line",
+                                STRING_QUOTE
+                            )),
+                    );
+                    return;
+                }
+                Some((_, c)) => {
+                    self.chars.next();
+                    string.push(c);
                 }
             }
-            c => buffer.push(c),
         }
     }
 
-    Ok(text_tokens)
+    fn word(&mut self, diagnostics: &mut Diagnostics) {
+        let start = self.offset();
+        let mut word = String::new();
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| !c.is_whitespace()) {
+            word.push(c);
+            if is_delimiter(c) || self.chars.peek().is_some_and(|&(_, c)| is_delimiter(c)) {
+                break;
+            }
+        }
+        let end = self.offset();
+        let span = self.span(start, end);
+        match word.parse() {
+            Ok(token) => self.push(token, span),
+            Err(e) => diagnostics.push(
+                Diagnostic::error(format!("Couldn't parse token `{}`", word)).primary(span, e),
+            ),
+        }
+    }
 }
@@ -1,19 +1,23 @@
 mod bytecoil;
 mod context;
 mod import;
+mod layout;
 mod library;
 mod macros;
 mod module;
+mod resolver;
 mod routines;
 
+pub use crate::core::{Hash, Link};
 pub use bytecoil::ByteCoIL;
 pub use context::Context;
 pub use import::Import;
-pub use library::Library;
+pub use layout::{Fixup, Layout, Relocation, Target};
+pub use library::{hex, sha256, Library, LibraryRoutine};
 pub use macros::Macro;
 pub use module::Module;
+pub use resolver::Resolver;
 pub use routines::Routine;
 
-type Hash = [u8; 32];
 type ByteCo = Vec<u8>;
 type NameTable = std::collections::HashMap<String, Hash>;
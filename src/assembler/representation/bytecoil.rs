@@ -1,6 +1,14 @@
 use super::ByteCo;
 use crate::assembler::tokens::Label;
 
+// opcodes the assembler writes for calls, addresses and routine ends
+pub(super) const LITERAL_8: u8 = 0b1011_0000;
+pub(super) const LITERAL_16: u8 = 0b1011_0001;
+pub(super) const CALL_16: u8 = 0b0000_0101;
+pub(super) const RETURN_16: u8 = 0b0000_0111;
+pub(super) const CALL_HASH: u8 = 0b0010_1111;
+pub(super) const ADDRESS_HASH: u8 = 0b0011_0000;
+
 #[derive(Clone)]
 pub enum ByteCoIL {
     Assembled(ByteCo),
@@ -12,14 +20,15 @@ pub enum ByteCoIL {
     RoutineAddressExported(Label),
     RoutineEnd,
     AnchorDef(Label),
-    AnchorRel(Label),
+    AnchorRel(Label, usize), // offset width, set by the jump that takes it
     AnchorAbs(Label),
+    Line(usize), // source line of the code after it
 }
 impl ByteCoIL {
     pub fn len(&self) -> usize {
         match self {
             Self::Assembled(byteco) => byteco.len(),
-            Self::Comment(string) => string.len() + 1,
+            Self::Comment(..) => 0, // kept for listings, never emitted
             Self::RoutineDef(..) => 0,
             Self::RoutineCallLocal(_) => 4,
             Self::RoutineCallExported(_) => 33,
@@ -27,10 +36,15 @@ impl ByteCoIL {
             Self::RoutineAddressExported(_) => 33,
             Self::RoutineEnd => 1,
             Self::AnchorDef(..) => 0,
-            Self::AnchorRel(_) => 2,
+            Self::AnchorRel(_, width) => 1 + width,
             Self::AnchorAbs(_) => 3,
+            Self::Line(..) => 0,
         }
     }
+    // emits no bytes, like a comment or a label
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 impl std::fmt::Display for ByteCoIL {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,8 +58,9 @@ impl std::fmt::Display for ByteCoIL {
             Self::RoutineAddressExported(name) => write!(f, "RoutineAddressExported({:?})", name),
             Self::RoutineEnd => write!(f, "RoutineEnd"),
             Self::AnchorDef(name) => write!(f, "AnchorDef({:?})", name),
-            Self::AnchorRel(name) => write!(f, "AnchorRel({:?})", name),
+            Self::AnchorRel(name, width) => write!(f, "AnchorRel({:?}, {})", name, width),
             Self::AnchorAbs(name) => write!(f, "AnchorAbs({:?})", name),
+            Self::Line(line) => write!(f, "Line({})", line),
         }
     }
 }
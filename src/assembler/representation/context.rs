@@ -1,22 +1,51 @@
-use crate::assembler::representation::{ByteCo, ByteCoIL, Library, Macro, Module, Routine};
-use crate::assembler::tokens::SourceToken;
-use std::collections::HashMap;
+use crate::assembler::representation::bytecoil::{ADDRESS_HASH, CALL_HASH};
+use crate::assembler::representation::layout::size_relative_anchors;
+use crate::assembler::representation::{ByteCo, ByteCoIL, Layout, Library, Macro, Module, Routine};
+use crate::assembler::tokens::{Label, SourceToken, MACRO_USE};
+use std::collections::{HashMap, HashSet};
 
+/*
+    Routines are laid out in the order they were defined, starting at
+    0x0000, so the first routine of a module is its entry point. Each one
+    ends with `RTN16`, and local calls and routine addresses become `LIT16`
+    of the routine's absolute address (followed by `CAL16` for a call).
+    See `Layout` for how addresses are assigned and patched in. Exported
+    calls and addresses are looked up in the library by name, and become
+    `CLH` or `ADH` followed by the routine's hash, for the machine to load
+    it when it's first used.
+
+    Macro arguments are the `'parameter` tokens right after its use, bound
+    in order to the names in its `[ ]` list. An anchor a macro defines with
+    a plain name is private to each use: it and references to it are
+    renamed with a `~` and a use count, which no source label can contain.
+    Anchors named from a parameter are left alone, so macros like `while`
+    and `end-while` can share them.
+*/
 pub struct Context<'a> {
     macros: HashMap<String, Macro>,
-    routines: HashMap<String, Routine>,
-    assembled_tokens: HashMap<String, Vec<ByteCoIL>>,
+    routines: Vec<Routine>,
+    assembled_tokens: HashMap<(String, Vec<String>), Vec<ByteCoIL>>,
     library: &'a Library,
+    expanding: Vec<String>,
+    expansions: usize,
 }
 impl<'a> Context<'a> {
     pub fn new(library: &'a Library, module: Module) -> Result<Context<'a>, String> {
         let mut context = Context {
             macros: HashMap::new(),
-            routines: HashMap::new(),
+            routines: Vec::new(),
             assembled_tokens: HashMap::new(),
             library,
+            expanding: Vec::new(),
+            expansions: 0,
         };
 
+        if let Some(import) = module.imports.first() {
+            return Err(format!(
+                "Context Error: Unresolved import `{}`",
+                import.name
+            ));
+        }
         for m in module.macros {
             context.register_macro(m)?;
         }
@@ -27,102 +56,221 @@ impl<'a> Context<'a> {
         Ok(context)
     }
     pub fn export(self) -> Result<ByteCo, String> {
-        let Context {
-            macros,
-            routines,
-            mut assembled_tokens,
-            library,
-        } = self;
+        let (rom, _) = self.link()?;
+        Ok(rom)
+    }
+    // the rom, and the layout it was fixed up with
+    pub fn link(self) -> Result<(ByteCo, Layout), String> {
+        let bytecoil = self.assemble()?;
+        let layout = Layout::new(&bytecoil)?;
+        let rom = layout.fix_up(bytecoil)?;
+        Ok((rom, layout))
+    }
+    // every routine's intermediate code, in layout order
+    pub fn assemble(mut self) -> Result<Vec<ByteCoIL>, String> {
+        let routines = std::mem::take(&mut self.routines);
+
+        let mut bytecoil: Vec<ByteCoIL> = Vec::new();
+        for routine in routines {
+            bytecoil.append(&mut self.assemble_routine(routine)?);
+        }
+
+        Ok(bytecoil)
+    }
+    // each routine's intermediate code on its own, with whether it's exported
+    pub fn assemble_routines(&mut self) -> Result<Vec<(String, bool, Vec<ByteCoIL>)>, String> {
+        let routines = std::mem::take(&mut self.routines);
+        routines
+            .into_iter()
+            .map(|routine| {
+                let (name, export) = (routine.name.clone(), routine.export);
+                Ok((name, export, self.assemble_routine(routine)?))
+            })
+            .collect()
+    }
+    // the macros that take no parameters and expand to bytes alone
+    pub fn plain_macros(&mut self) -> Vec<(String, ByteCo)> {
+        let mut names: Vec<String> = self
+            .macros
+            .values()
+            .filter(|mac| {
+                !mac.tokens
+                    .iter()
+                    .any(|token| matches!(token, SourceToken::ParameterDef { .. }))
+            })
+            .map(|mac| mac.name.clone())
+            .collect();
+        names.sort();
 
-        for (name, routine) in routines {
-            println!("\n==================");
-            let mut bytecoil: Vec<ByteCoIL> = Vec::new();
-            bytecoil.push(ByteCoIL::RoutineDef(name));
-            for token in routine.tokens {
-                match Context::pre_assemble_token(token, &mut assembled_tokens, &macros, &library) {
-                    Ok(mut il) => bytecoil.append(&mut il),
-                    Err(err) => return Err(err),
+        let mut macros = Vec::new();
+        for name in names {
+            let Ok(il) = self.pre_assemble_macro(name.clone(), Vec::new()) else {
+                continue;
+            };
+            let mut bytes = Vec::new();
+            let plain = il.into_iter().all(|il| match il {
+                ByteCoIL::Assembled(mut byteco) => {
+                    bytes.append(&mut byteco);
+                    true
                 }
+                ByteCoIL::Comment(_) | ByteCoIL::Line(_) => true,
+                _ => false,
+            });
+            if plain {
+                macros.push((name, bytes));
             }
-            bytecoil.push(ByteCoIL::RoutineEnd);
-            for il in bytecoil {
-                println!("{}", il);
-            }
-            println!("==================");
         }
-
-        Ok(vec![])
+        macros
     }
-    fn pre_assemble_token(
-        token: SourceToken,
-        assembled_tokens: &mut HashMap<String, Vec<ByteCoIL>>,
-        macros: &HashMap<String, Macro>,
-        library: &'a Library,
+    fn assemble_routine(&mut self, routine: Routine) -> Result<Vec<ByteCoIL>, String> {
+        let mut il = vec![ByteCoIL::RoutineDef(routine.name)];
+        il.append(&mut self.pre_assemble_tokens(&routine.tokens, &HashMap::new())?);
+        il.push(ByteCoIL::RoutineEnd);
+        size_relative_anchors(&mut il)?;
+        for il in il.iter_mut() {
+            let (opcode, label) = match il {
+                ByteCoIL::RoutineCallExported(label) => (CALL_HASH, label),
+                ByteCoIL::RoutineAddressExported(label) => (ADDRESS_HASH, label),
+                _ => continue,
+            };
+            let name = label.to_string(&HashMap::new())?;
+            let Some(hash) = self.library.resolve(&name) else {
+                return Err(format!(
+                    "Context Error: Exported routine `{}` isn't in the library",
+                    name
+                ));
+            };
+            let mut byteco = vec![opcode];
+            byteco.extend(hash);
+            *il = ByteCoIL::Assembled(byteco);
+        }
+        Ok(il)
+    }
+    fn pre_assemble_tokens(
+        &mut self,
+        tokens: &[SourceToken],
+        parameters: &HashMap<String, String>,
     ) -> Result<Vec<ByteCoIL>, String> {
         let mut vec = Vec::new();
-        // match token {
-        //     SourceToken::Comment { string } => vec.push(ByteCoIL::Comment(string)),
-        //     SourceToken::NumberLiteral { literal } => vec.push(ByteCoIL::Assembled(literal.into())),
-        //     SourceToken::Instruction { opcode } => vec.push(ByteCoIL::Assembled(vec![opcode])),
-        //     SourceToken::ParameterDef { name } =>
-        //     SourceToken::RoutineCallLocal { label } => vec.push(ByteCoIL::RoutineCallLocal(label)),
-        //     SourceToken::RoutineCallExported { label } => {
-        //         vec.push(ByteCoIL::RoutineCallExported(label))
-        //     }
-        //     SourceToken::RoutineAddressLocal { label } => {
-        //         vec.push(ByteCoIL::RoutineAddressLocal(label))
-        //     }
-        //     SourceToken::RoutineAddressExported { label } => {
-        //         vec.push(ByteCoIL::RoutineAddressExported(label))
-        //     }
-        //     SourceToken::AnchorDef { label } => vec.push(ByteCoIL::AnchorDef(label)),
-        //     SourceToken::AnchorAddressRelative { label } => vec.push(ByteCoIL::AnchorRel(label)),
-        //     SourceToken::AnchorAddressAbsolute { label } => vec.push(ByteCoIL::AnchorAbs(label)),
-        //     SourceToken::MacroUse { label } => vec.append(&mut Context::pre_assemble_macro(
-        //         label,
-        //         assembled_tokens,
-        //         macros,
-        //         library,
-        //     )?),
-        // };
+        let mut tokens = tokens.iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                SourceToken::Comment { string } => vec.push(ByteCoIL::Comment(string.clone())),
+                SourceToken::Line { line } => vec.push(ByteCoIL::Line(*line)),
+                SourceToken::NumberLiteral { literal } => {
+                    vec.push(ByteCoIL::Assembled(literal.clone().into()))
+                }
+                SourceToken::Instruction { opcode } => vec.push(ByteCoIL::Assembled(vec![*opcode])),
+                SourceToken::ParameterDef { .. } => {}
+                SourceToken::ParameterUse { label } => {
+                    return Err(format!("Parameter `{:?}` isn't passed to a macro", label))
+                }
+                SourceToken::RoutineCallLocal { label } => {
+                    vec.push(ByteCoIL::RoutineCallLocal(label.bind(parameters)?))
+                }
+                SourceToken::RoutineCallExported { label } => {
+                    vec.push(ByteCoIL::RoutineCallExported(label.bind(parameters)?))
+                }
+                SourceToken::RoutineAddressLocal { label } => {
+                    vec.push(ByteCoIL::RoutineAddressLocal(label.bind(parameters)?))
+                }
+                SourceToken::RoutineAddressExported { label } => {
+                    vec.push(ByteCoIL::RoutineAddressExported(label.bind(parameters)?))
+                }
+                SourceToken::AnchorDef { label } => {
+                    vec.push(ByteCoIL::AnchorDef(label.bind(parameters)?))
+                }
+                SourceToken::AnchorAddressRelative { label } => {
+                    vec.push(ByteCoIL::AnchorRel(label.bind(parameters)?, 1))
+                }
+                SourceToken::AnchorAddressAbsolute { label } => {
+                    vec.push(ByteCoIL::AnchorAbs(label.bind(parameters)?))
+                }
+                SourceToken::MacroUse { label } => {
+                    let name = label.to_string(parameters)?;
+                    // the parameters right after a macro are its arguments, even
+                    // over a line break
+                    let mut arguments = Vec::new();
+                    loop {
+                        let mut ahead = tokens.clone();
+                        while let Some(SourceToken::Line { .. }) = ahead.peek() {
+                            ahead.next();
+                        }
+                        let Some(SourceToken::ParameterUse { label }) = ahead.next() else {
+                            break;
+                        };
+                        arguments.push(label.to_string(parameters)?);
+                        tokens = ahead;
+                    }
+                    vec.append(&mut self.pre_assemble_macro(name, arguments)?);
+                }
+            };
+        }
 
         Ok(vec)
     }
     fn pre_assemble_macro(
-        name: &str,
-        assembled_tokens: &mut HashMap<String, Vec<ByteCoIL>>,
-        macros: &HashMap<String, Macro>,
-        library: &'a Library,
+        &mut self,
+        name: String,
+        arguments: Vec<String>,
     ) -> Result<Vec<ByteCoIL>, String> {
-        match assembled_tokens.get(name) {
-            // if already assembled, use that
-            Some(il) => Ok(il.clone()),
+        let Some(mac) = self.macros.get(&name) else {
+            // then check library
+            return match self.library.macros.get(&name) {
+                Some(_) if !arguments.is_empty() => {
+                    Err(format!("Library macro `{}` doesn't take parameters", name))
+                }
+                Some(mac) => Ok(vec![ByteCoIL::Assembled(mac.clone())]),
+                None => Err(format!("Using undefined macro: {}", name)),
+            };
+        };
+        let private = private_anchors(&mac.tokens);
 
-            // if not assembled
-            None => match macros.get(name) {
-                // first check context defined macros
-                Some(mac) => {
-                    let mut vec = Vec::new();
-                    for token in &mac.tokens {
-                        let mut il = Context::pre_assemble_token(
-                            token.clone(),
-                            assembled_tokens,
-                            macros,
-                            library,
-                        )?;
-                        vec.append(&mut il);
-                    }
-                    // once assembled, cache it
-                    assembled_tokens.insert(name.to_string(), vec.clone());
-                    Ok(vec)
+        let key = (name, arguments);
+        let mut il = match self.assembled_tokens.get(&key) {
+            // if already assembled with these arguments, use that
+            Some(il) => il.clone(),
+
+            // if not assembled, bind the arguments and expand it
+            None => {
+                let (name, arguments) = &key;
+                let names: Vec<&String> = mac
+                    .tokens
+                    .iter()
+                    .filter_map(|token| match token {
+                        SourceToken::ParameterDef { name } => Some(name),
+                        _ => None,
+                    })
+                    .collect();
+                if names.len() != arguments.len() {
+                    return Err(format!(
+                        "Macro `{}` takes {} parameters, but was given {}",
+                        name,
+                        names.len(),
+                        arguments.len()
+                    ));
                 }
-                // then check library
-                None => match library.macros.get(name) {
-                    Some(mac) => Ok(vec![ByteCoIL::Assembled(mac.clone())]),
-                    None => return Err(format!("Using undefined macro: {}", name)),
-                },
-            },
-        }
+                if self.expanding.contains(name) {
+                    return Err(format!("Macro `{}` expands itself", name));
+                }
+                let parameters = names.into_iter().cloned().zip(arguments.clone()).collect();
+                let tokens = mac.tokens.clone();
+
+                self.expanding.push(name.clone());
+                let il = self.pre_assemble_tokens(&tokens, &parameters);
+                self.expanding.pop();
+                let il = il?;
+
+                // once assembled, cache it
+                self.assembled_tokens.insert(key.clone(), il.clone());
+                il
+            }
+        };
+
+        // every use gets its own copy of the macro's anchors
+        self.expansions += 1;
+        rename_private_anchors(&mut il, &private, self.expansions)?;
+        Ok(il)
     }
     fn register_macro(&mut self, mac: Macro) -> Result<(), String> {
         match self.macros.insert(mac.name.clone(), mac) {
@@ -131,9 +279,49 @@ impl<'a> Context<'a> {
         }
     }
     fn register_routine(&mut self, routine: Routine) -> Result<(), String> {
-        match self.routines.insert(routine.name.clone(), routine) {
-            None => Ok(()),
-            Some(prev) => Err(format!("Context Error: Duplicate routine `{}`", prev.name)),
+        match self.routines.iter().any(|r| r.name == routine.name) {
+            false => {
+                self.routines.push(routine);
+                Ok(())
+            }
+            true => Err(format!(
+                "Context Error: Duplicate routine `{}`",
+                routine.name
+            )),
+        }
+    }
+}
+
+// anchors a macro defines by name alone
+fn private_anchors(tokens: &[SourceToken]) -> HashSet<String> {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            SourceToken::AnchorDef { label } if !label.has_parameters() => {
+                label.to_string(&HashMap::new()).ok()
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// gives private anchors, and those already renamed by nested macros, a new suffix
+fn rename_private_anchors(
+    bytecoil: &mut [ByteCoIL],
+    private: &HashSet<String>,
+    expansion: usize,
+) -> Result<(), String> {
+    for il in bytecoil {
+        let label = match il {
+            ByteCoIL::AnchorDef(label)
+            | ByteCoIL::AnchorRel(label, _)
+            | ByteCoIL::AnchorAbs(label) => label,
+            _ => continue,
+        };
+        let name = label.to_string(&HashMap::new())?;
+        if private.contains(&name) || name.contains(MACRO_USE) {
+            *label = Label::literal(format!("{}{}{}", name, MACRO_USE, expansion));
         }
     }
+    Ok(())
 }
@@ -1,4 +1,5 @@
-use crate::assembler::tokens::{self, Path, Rune, TextToken};
+use crate::assembler::diagnostic::{Diagnostic, Span};
+use crate::assembler::tokens::{self, Path, Rune, Spanned, TextToken};
 use std::fmt::Display;
 
 pub enum SymbolType {
@@ -12,14 +13,27 @@ pub struct Import {
 }
 impl Import {
     pub fn from_text_tokens(
-        text_tokens: &mut dyn Iterator<Item = TextToken>,
-    ) -> Result<Vec<Import>, String> {
-        let Some(TextToken::Path(path)) = text_tokens.next() else {
-            return Err("First token of import must be a path".into());
+        open: Span,
+        text_tokens: &mut dyn Iterator<Item = Spanned>,
+    ) -> Result<Vec<Import>, Diagnostic> {
+        let path = match text_tokens.next() {
+            Some(Spanned {
+                token: TextToken::Path(path),
+                ..
+            }) => path,
+            Some(Spanned { span, .. }) => {
+                return Err(Diagnostic::error("First token of import must be a path")
+                    .primary(span, "expected a module path, like `.co.stack`")
+                    .secondary(open, "in this import"))
+            }
+            None => {
+                return Err(Diagnostic::error("First token of import must be a path")
+                    .primary(open, "import has no path"))
+            }
         };
 
         let mut imports = Vec::new();
-        while let Some(token) = text_tokens.next() {
+        for Spanned { token, span } in &mut *text_tokens {
             match token {
                 TextToken::Import(import) => match import {
                     tokens::Import::Macro { identifier, name } => {
@@ -37,10 +51,18 @@ impl Import {
                 },
                 TextToken::Rune(rune) => match rune {
                     Rune::CloseDefinition => break,
-                    _ => return Err("Invalid rune inside import definition".into()),
+                    _ => {
+                        return Err(Diagnostic::error("Invalid rune inside import definition")
+                            .primary(span, "")
+                            .secondary(open, "in this import"))
+                    }
                 },
                 TextToken::NewLine | TextToken::Tab(_) | TextToken::Comment(_) => continue,
-                _ => return Err("Invalid token inside import definition".into()),
+                _ => {
+                    return Err(Diagnostic::error("Invalid token inside import definition")
+                        .primary(span, "expected `%macro` or `>routine`")
+                        .secondary(open, "in this import"))
+                }
             }
         }
 
@@ -49,11 +71,11 @@ impl Import {
 }
 impl Display for Import {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Import: {}\n", self.name)?;
+        writeln!(f, "Import: {}", self.name)?;
         match &self.symbol {
-            SymbolType::Macro => write!(f, " - type: Macro\n")?,
-            SymbolType::Routine => write!(f, " - type: Routine\n")?,
+            SymbolType::Macro => writeln!(f, " - type: Macro")?,
+            SymbolType::Routine => writeln!(f, " - type: Routine")?,
         };
-        write!(f, " - path: {}\n", self.path)
+        writeln!(f, " - path: {}", self.path)
     }
 }
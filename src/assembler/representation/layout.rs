@@ -0,0 +1,239 @@
+use super::bytecoil::{CALL_16, LITERAL_16, LITERAL_8, RETURN_16};
+use super::{ByteCo, ByteCoIL};
+use crate::core::Instruction;
+use std::collections::HashMap;
+
+/*
+    Assembly makes two passes over the ByteCoIL of every routine. Layout
+    gives each routine and anchor its address and records a relocation for
+    every operand that refers to one. Fix-up then emits the bytes with
+    those operands zeroed, and patches each relocation with its target:
+    the absolute address for calls, `$routine` and `*anchor`, or for
+    `&anchor` a signed offset from the relative jump that takes it.
+
+    Anchors belong to the routine that defines them, so two routines can
+    each have a `#loop`. Layout also notes the address each source line
+    starts at, for the source map.
+*/
+pub struct Layout {
+    pub routines: HashMap<String, u16>,
+    pub relocations: Vec<Relocation>,
+    anchors: HashMap<(String, String), u16>,
+    pub lines: Vec<(u16, usize)>,
+    pub size: usize,
+}
+
+// an operand at `address` waiting for the address of `target`
+#[derive(Clone, Debug)]
+pub struct Relocation {
+    pub address: u16,
+    pub fixup: Fixup,
+    pub routine: String,
+    pub target: Target,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fixup {
+    Absolute,
+    Relative { width: usize, jump: u16 },
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Routine(String),
+    Anchor(String),
+}
+
+impl Layout {
+    pub fn new(bytecoil: &[ByteCoIL]) -> Result<Layout, String> {
+        let mut layout = Layout {
+            routines: HashMap::new(),
+            relocations: Vec::new(),
+            anchors: HashMap::new(),
+            lines: Vec::new(),
+            size: 0,
+        };
+
+        let mut routine = String::new();
+        let mut address = 0;
+        for il in bytecoil {
+            if address > 0xFFFF {
+                break; // reported below, with the full size
+            }
+            let operand = (address as u16).wrapping_add(1);
+            let relocation = match il {
+                ByteCoIL::RoutineDef(name) => {
+                    routine = name.clone();
+                    layout.routines.insert(name.clone(), address as u16);
+                    None
+                }
+                ByteCoIL::AnchorDef(label) => {
+                    let name = label.to_string(&HashMap::new())?;
+                    let key = (routine.clone(), name);
+                    if layout.anchors.insert(key, address as u16).is_some() {
+                        return Err(format!(
+                            "Duplicate anchor `{:?}` in routine `{}`",
+                            label, routine
+                        ));
+                    }
+                    None
+                }
+                ByteCoIL::Line(line) => {
+                    // a line with no code of its own gives way to the next
+                    let address = address as u16;
+                    if layout.lines.last().is_some_and(|(a, _)| *a == address) {
+                        layout.lines.pop();
+                    }
+                    layout.lines.push((address, *line));
+                    None
+                }
+                ByteCoIL::RoutineCallLocal(label) | ByteCoIL::RoutineAddressLocal(label) => {
+                    let target = Target::Routine(label.to_string(&HashMap::new())?);
+                    Some((Fixup::Absolute, target))
+                }
+                ByteCoIL::AnchorAbs(label) => {
+                    let target = Target::Anchor(label.to_string(&HashMap::new())?);
+                    Some((Fixup::Absolute, target))
+                }
+                ByteCoIL::AnchorRel(label, width) => {
+                    let target = Target::Anchor(label.to_string(&HashMap::new())?);
+                    let jump = operand.wrapping_add(*width as u16);
+                    let width = *width;
+                    Some((Fixup::Relative { width, jump }, target))
+                }
+                _ => None,
+            };
+            if let Some((fixup, target)) = relocation {
+                layout.relocations.push(Relocation {
+                    address: operand,
+                    fixup,
+                    routine: routine.clone(),
+                    target,
+                });
+            }
+            address += il.len();
+        }
+
+        layout.size = bytecoil.iter().map(ByteCoIL::len).sum();
+        if layout.size > 0x1_0000 {
+            return Err(format!(
+                "Assembled {} bytes, more than fits in memory",
+                layout.size
+            ));
+        }
+        Ok(layout)
+    }
+
+    pub fn fix_up(&self, bytecoil: Vec<ByteCoIL>) -> Result<ByteCo, String> {
+        let mut rom = self.emit(bytecoil)?;
+        for relocation in &self.relocations {
+            let address = self.resolve(relocation)?;
+            patch(&mut rom, relocation, address)?;
+        }
+        Ok(rom)
+    }
+
+    // the bytes, with every relocated operand left as zero
+    pub fn emit(&self, bytecoil: Vec<ByteCoIL>) -> Result<ByteCo, String> {
+        let mut rom = Vec::with_capacity(self.size);
+        for il in bytecoil {
+            match il {
+                ByteCoIL::Assembled(mut byteco) => rom.append(&mut byteco),
+                ByteCoIL::Comment(_)
+                | ByteCoIL::RoutineDef(_)
+                | ByteCoIL::AnchorDef(_)
+                | ByteCoIL::Line(_) => {}
+                ByteCoIL::RoutineCallLocal(_) => rom.extend([LITERAL_16, 0, 0, CALL_16]),
+                ByteCoIL::RoutineAddressLocal(_) | ByteCoIL::AnchorAbs(_) => {
+                    rom.extend([LITERAL_16, 0, 0])
+                }
+                ByteCoIL::AnchorRel(_, 1) => rom.extend([LITERAL_8, 0]),
+                ByteCoIL::AnchorRel(..) => rom.extend([LITERAL_16, 0, 0]),
+                ByteCoIL::RoutineEnd => rom.push(RETURN_16),
+                ByteCoIL::RoutineCallExported(label) | ByteCoIL::RoutineAddressExported(label) => {
+                    return Err(format!(
+                        "Exported routine `{:?}` wasn't looked up in a library",
+                        label
+                    ))
+                }
+            }
+        }
+        Ok(rom)
+    }
+
+    pub fn resolve(&self, relocation: &Relocation) -> Result<u16, String> {
+        let address = match &relocation.target {
+            Target::Routine(name) => self.routines.get(name),
+            Target::Anchor(name) => {
+                let key = (relocation.routine.clone(), name.clone());
+                self.anchors.get(&key)
+            }
+        };
+        match address {
+            Some(address) => Ok(*address),
+            None => Err(format!(
+                "Using undefined {} in routine `{}`",
+                relocation.target, relocation.routine
+            )),
+        }
+    }
+}
+
+// writes `address` into the operand, as an offset from the jump if it's relative
+pub fn patch(rom: &mut [u8], relocation: &Relocation, address: u16) -> Result<(), String> {
+    let start = relocation.address as usize;
+    match relocation.fixup {
+        Fixup::Absolute => rom[start..start + 2].copy_from_slice(&address.to_le_bytes()),
+        Fixup::Relative { width, jump } => {
+            let offset = address as i32 - jump as i32;
+            let fits = match width {
+                1 => i8::try_from(offset).is_ok(),
+                _ => i16::try_from(offset).is_ok(),
+            };
+            if !fits {
+                return Err(format!(
+                    "Relative jump to {} in routine `{}` is {} bytes, too far for {} bits",
+                    relocation.target,
+                    relocation.routine,
+                    offset,
+                    width * 8
+                ));
+            }
+            let bytes = (offset as i16).to_le_bytes();
+            rom[start..start + width].copy_from_slice(&bytes[..width]);
+        }
+    }
+    Ok(())
+}
+
+// sizes each relative anchor by the relative jump that follows it
+pub(super) fn size_relative_anchors(bytecoil: &mut [ByteCoIL]) -> Result<(), String> {
+    for i in 0..bytecoil.len() {
+        let ByteCoIL::AnchorRel(label, _) = &bytecoil[i] else {
+            continue;
+        };
+        let jump = bytecoil[i + 1..].iter().find(|il| !il.is_empty());
+        let width = match jump {
+            Some(ByteCoIL::Assembled(byteco)) => match Instruction::from(byteco[0]) {
+                Instruction::Jump { rel: true, len, .. } => len as usize,
+                _ => 0,
+            },
+            _ => 0,
+        };
+        if width == 0 {
+            return Err(format!(
+                "Relative anchor `{:?}` must be followed by a relative jump",
+                label
+            ));
+        }
+        bytecoil[i] = ByteCoIL::AnchorRel(label.clone(), width);
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Routine(name) => write!(f, "routine `{}`", name),
+            Target::Anchor(name) => write!(f, "anchor `{}`", name),
+        }
+    }
+}
@@ -1,12 +1,44 @@
+mod file;
+mod sha256;
+
+pub use sha256::sha256;
+
+use super::layout::patch;
+use super::{ByteCo, ByteCoIL, Context, Fixup, Hash, Layout, Module, NameTable, Target};
+use crate::core::{Link, Linker};
 use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+/*
+    A library keeps routines by the hash of their contents, so a name can
+    move on to a new version without changing what anything already linked
+    to the old one runs.
 
-use super::{ByteCo, Hash};
+    Each routine is assembled on its own as if it started at 0x0000, and
+    kept with links for the operands a loader fills in once it knows where
+    the routine goes: addresses within the routine itself, and addresses of
+    the routines it calls, which are linked by hash. Exported calls are
+    already by hash, in the bytes. A routine's hash is the SHA-256 of its
+    bytes and links (see `file` for the encoding), so it covers every
+    routine it depends on. Routines that call each other in a cycle can't
+    be hashed this way; a routine calling itself is fine.
 
+    Only exported (`^`) routines are given names. Macros that take no
+    parameters and expand to plain bytes are kept by name too.
+*/
 pub struct Library {
     pub macros: HashMap<String, ByteCo>,
-    pub routine_names: HashMap<String, Hash>,
-    pub routines: HashMap<Hash, ByteCo>,
+    pub routine_names: NameTable,
+    pub routines: HashMap<Hash, LibraryRoutine>,
 }
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryRoutine {
+    pub code: ByteCo,
+    pub links: Vec<Link>,
+}
+
 impl Library {
     pub fn new() -> Library {
         Library {
@@ -15,4 +47,176 @@ impl Library {
             routines: HashMap::new(),
         }
     }
+
+    pub fn load(path: &Path) -> Result<Library, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Couldn't read library {}: {}", path.display(), e))?;
+        Library::from_bytes(&bytes).map_err(|e| format!("In library {}: {}", path.display(), e))
+    }
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes())
+            .map_err(|e| format!("Couldn't write library {}: {}", path.display(), e))
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Library, String> {
+        file::read(bytes)
+    }
+    pub fn to_bytes(&self) -> Vec<u8> {
+        file::write(self)
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<Hash> {
+        self.routine_names.get(name).copied()
+    }
+
+    // a linker with every routine, loading them into `window` as they're called
+    pub fn linker(&self, window: Range<usize>) -> Linker {
+        let mut linker = Linker::new(window);
+        for (hash, routine) in &self.routines {
+            linker.add(*hash, routine.code.clone(), routine.links.clone());
+        }
+        linker
+    }
+
+    // adds a module's routines and plain macros, returning the routines it names
+    pub fn add_module(&mut self, module: Module) -> Result<Vec<(String, Hash)>, String> {
+        let (routines, macros) = {
+            let mut context = Context::new(self, module)?;
+            (context.assemble_routines()?, context.plain_macros())
+        };
+
+        let mut hashes: HashMap<String, Hash> = HashMap::new();
+        let mut named = Vec::new();
+        for index in link_order(&routines)? {
+            let (name, export, bytecoil) = &routines[index];
+            let routine = LibraryRoutine::link(name, bytecoil.clone(), &hashes)?;
+            let hash = routine.hash();
+            self.routines.insert(hash, routine);
+            hashes.insert(name.clone(), hash);
+            if *export {
+                self.routine_names.insert(name.clone(), hash);
+                named.push((name.clone(), hash));
+            }
+        }
+        for (name, bytes) in macros {
+            self.macros.insert(name, bytes);
+        }
+
+        named.sort();
+        Ok(named)
+    }
+}
+
+impl LibraryRoutine {
+    // links another routine by the hash in `hashes`, which must already have it
+    fn link(
+        name: &str,
+        bytecoil: Vec<ByteCoIL>,
+        hashes: &HashMap<String, Hash>,
+    ) -> Result<LibraryRoutine, String> {
+        let layout = Layout::new(&bytecoil)?;
+        let mut code = layout.emit(bytecoil)?;
+        let mut links = Vec::new();
+        for relocation in &layout.relocations {
+            let operand = relocation.address;
+            match &relocation.target {
+                Target::Routine(target) if target != name => {
+                    let hash = hashes[target];
+                    links.push(Link::Routine { operand, hash });
+                }
+                _ => {
+                    let address = layout.resolve(relocation)?;
+                    patch(&mut code, relocation, address)?;
+                    if relocation.fixup == Fixup::Absolute {
+                        links.push(Link::Local { operand });
+                    }
+                }
+            }
+        }
+        Ok(LibraryRoutine { code, links })
+    }
+
+    pub fn hash(&self) -> Hash {
+        let mut encoded = Vec::new();
+        file::write_routine(&mut encoded, self);
+        sha256::sha256(&encoded)
+    }
+}
+
+// routines ordered so each comes after every routine it calls
+fn link_order(routines: &[(String, bool, Vec<ByteCoIL>)]) -> Result<Vec<usize>, String> {
+    let indices: HashMap<&String, usize> = routines
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _, _))| (name, index))
+        .collect();
+    let mut calls = Vec::new();
+    for (name, _, bytecoil) in routines {
+        let mut called = Vec::new();
+        for il in bytecoil {
+            let label = match il {
+                ByteCoIL::RoutineCallLocal(label) | ByteCoIL::RoutineAddressLocal(label) => label,
+                _ => continue,
+            };
+            let target = label.to_string(&HashMap::new())?;
+            match indices.get(&target) {
+                Some(_) if target == *name => {}
+                Some(&index) => called.push(index),
+                None => {
+                    return Err(format!(
+                        "Using undefined routine `{}` in routine `{}`",
+                        target, name
+                    ))
+                }
+            }
+        }
+        calls.push(called);
+    }
+
+    fn visit(
+        index: usize,
+        calls: &[Vec<usize>],
+        names: &[&String],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        if order.contains(&index) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|&i| i == index) {
+            let cycle: Vec<&str> = path[start..]
+                .iter()
+                .chain([&index])
+                .map(|&i| names[i].as_str())
+                .collect();
+            return Err(format!(
+                "Routines call each other in a cycle, which can't be hashed: {}",
+                cycle.join(" -> ")
+            ));
+        }
+        path.push(index);
+        for &called in &calls[index] {
+            visit(called, calls, names, path, order)?;
+        }
+        path.pop();
+        order.push(index);
+        Ok(())
+    }
+
+    let names: Vec<&String> = routines.iter().map(|(name, _, _)| name).collect();
+    let mut order = Vec::new();
+    for index in 0..routines.len() {
+        visit(index, &calls, &names, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+// a hash as 64 lowercase hex digits
+pub fn hex(hash: &Hash) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Self::new()
+    }
 }
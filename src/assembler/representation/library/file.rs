@@ -0,0 +1,228 @@
+use super::{Hash, Library, LibraryRoutine, Link};
+use std::collections::HashMap;
+
+/*
+    A library file is little-endian throughout:
+
+        "COLB" version:u8
+        macros:u32    { name bytes }
+        routines:u32  { hash:[32] routine }
+        names:u32     { name hash:[32] }
+
+    where a name is a u16 length and UTF-8, bytes are a u32 length and the
+    bytes, and a routine is its code as bytes followed by its links:
+
+        code links:u16 { 0:u8 operand:u16 | 1:u8 operand:u16 hash:[32] }
+
+    Entries are sorted, so saving the same library gives the same file. A
+    routine's hash is the SHA-256 of its encoding, and is checked on load.
+*/
+const MAGIC: &[u8; 4] = b"COLB";
+const VERSION: u8 = 1;
+
+const LINK_LOCAL: u8 = 0;
+const LINK_ROUTINE: u8 = 1;
+
+pub fn write(library: &Library) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+
+    let mut macros: Vec<_> = library.macros.iter().collect();
+    macros.sort();
+    out.extend((macros.len() as u32).to_le_bytes());
+    for (name, bytes) in macros {
+        write_name(&mut out, name);
+        write_bytes(&mut out, bytes);
+    }
+
+    let mut routines: Vec<_> = library.routines.iter().collect();
+    routines.sort_by_key(|(hash, _)| **hash);
+    out.extend((routines.len() as u32).to_le_bytes());
+    for (hash, routine) in routines {
+        out.extend(hash);
+        write_routine(&mut out, routine);
+    }
+
+    let mut names: Vec<_> = library.routine_names.iter().collect();
+    names.sort();
+    out.extend((names.len() as u32).to_le_bytes());
+    for (name, hash) in names {
+        write_name(&mut out, name);
+        out.extend(hash);
+    }
+
+    out
+}
+
+pub fn write_routine(out: &mut Vec<u8>, routine: &LibraryRoutine) {
+    write_bytes(out, &routine.code);
+    out.extend((routine.links.len() as u16).to_le_bytes());
+    for link in &routine.links {
+        match link {
+            Link::Local { operand } => {
+                out.push(LINK_LOCAL);
+                out.extend(operand.to_le_bytes());
+            }
+            Link::Routine { operand, hash } => {
+                out.push(LINK_ROUTINE);
+                out.extend(operand.to_le_bytes());
+                out.extend(hash);
+            }
+        }
+    }
+}
+
+pub fn read(bytes: &[u8]) -> Result<Library, String> {
+    let mut reader = Reader { bytes, at: 0 };
+    if reader.take(4)? != MAGIC {
+        return Err("Not a library file".into());
+    }
+    let version = reader.u8()?;
+    if version != VERSION {
+        return Err(format!(
+            "Library file is version {}, but only version {} can be read",
+            version, VERSION
+        ));
+    }
+
+    let mut macros = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let name = reader.name()?;
+        let bytes = reader.bytes()?;
+        macros.insert(name, bytes);
+    }
+
+    let mut routines = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let hash = reader.hash()?;
+        let routine = reader.routine()?;
+        if routine.hash() != hash {
+            return Err(format!(
+                "Routine {} doesn't match its hash",
+                super::hex(&hash)
+            ));
+        }
+        routines.insert(hash, routine);
+    }
+
+    let mut routine_names = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let name = reader.name()?;
+        let hash = reader.hash()?;
+        routine_names.insert(name, hash);
+    }
+
+    for (hash, routine) in &routines {
+        for link in &routine.links {
+            if let Link::Routine { hash: target, .. } = link {
+                if !routines.contains_key(target) {
+                    return Err(format!(
+                        "Routine {} links to {}, which isn't in the library",
+                        super::hex(hash),
+                        super::hex(target)
+                    ));
+                }
+            }
+        }
+    }
+    for (name, hash) in &routine_names {
+        if !routines.contains_key(hash) {
+            return Err(format!(
+                "Name `{}` is for {}, which isn't in the library",
+                name,
+                super::hex(hash)
+            ));
+        }
+    }
+
+    if reader.at != bytes.len() {
+        return Err(format!(
+            "Library file has {} bytes left over",
+            bytes.len() - reader.at
+        ));
+    }
+    Ok(Library {
+        macros,
+        routine_names,
+        routines,
+    })
+}
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    out.extend((name.len() as u16).to_le_bytes());
+    out.extend(name.as_bytes());
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend((bytes.len() as u32).to_le_bytes());
+    out.extend(bytes);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], String> {
+        match self.bytes.get(self.at..self.at + count) {
+            Some(bytes) => {
+                self.at += count;
+                Ok(bytes)
+            }
+            None => Err(format!("Library file ends early, at byte {}", self.at)),
+        }
+    }
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    fn hash(&mut self) -> Result<Hash, String> {
+        let mut hash = [0; 32];
+        hash.copy_from_slice(self.take(32)?);
+        Ok(hash)
+    }
+    fn name(&mut self) -> Result<String, String> {
+        let length = self.u16()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| {
+            format!(
+                "Library file has a name that isn't UTF-8, at byte {}",
+                self.at
+            )
+        })
+    }
+    fn bytes(&mut self) -> Result<Vec<u8>, String> {
+        let length = self.u32()? as usize;
+        Ok(self.take(length)?.to_vec())
+    }
+    fn routine(&mut self) -> Result<LibraryRoutine, String> {
+        let code = self.bytes()?;
+        let mut links = Vec::new();
+        for _ in 0..self.u16()? {
+            let kind = self.u8()?;
+            let operand = self.u16()?;
+            if operand as usize + 2 > code.len() {
+                return Err(format!(
+                    "Library routine links an operand at {:#06x}, past its end",
+                    operand
+                ));
+            }
+            let link = match kind {
+                LINK_LOCAL => Link::Local { operand },
+                LINK_ROUTINE => Link::Routine {
+                    operand,
+                    hash: self.hash()?,
+                },
+                kind => return Err(format!("Unknown link kind {} in library file", kind)),
+            };
+            links.push(link);
+        }
+        Ok(LibraryRoutine { code, links })
+    }
+}
@@ -1,4 +1,8 @@
-use crate::assembler::tokens::{Command, Marker, NumberLiteral, Rune, SourceToken, TextToken};
+use super::routines::{dangling_string, missing_literal};
+use crate::assembler::diagnostic::{Diagnostic, Span};
+use crate::assembler::tokens::{
+    Command, Marker, NumberLiteral, Rune, SourceToken, Spanned, TextToken,
+};
 use std::fmt::Display;
 
 pub struct Macro {
@@ -7,20 +11,42 @@ pub struct Macro {
 }
 impl Macro {
     pub fn from_text_tokens(
-        text_tokens: &mut dyn Iterator<Item = TextToken>,
-    ) -> Result<Macro, String> {
-        let Some(TextToken::StringLiteral(name)) = text_tokens.next() else {
-            return Err("First token of macro must be string literal".into());
+        open: Span,
+        text_tokens: &mut dyn Iterator<Item = Spanned>,
+    ) -> Result<Macro, Diagnostic> {
+        let name = match text_tokens.next() {
+            Some(Spanned {
+                token: TextToken::StringLiteral(name),
+                ..
+            }) => name,
+            Some(Spanned { span, .. }) => {
+                return Err(
+                    Diagnostic::error("First token of macro must be string literal")
+                        .primary(span, "expected the macro's name")
+                        .secondary(open, "in this macro"),
+                )
+            }
+            None => {
+                return Err(
+                    Diagnostic::error("First token of macro must be string literal")
+                        .primary(open, "macro has no name"),
+                )
+            }
         };
 
+        let invalid = |message: &str, span: Span, label: &str| {
+            Diagnostic::error(message)
+                .primary(span, label)
+                .secondary(open, format!("in macro `{}`", name))
+        };
         let mut tokens = Vec::new();
-        while let Some(text_token) = text_tokens.next() {
-            let source_token = match text_token {
+        while let Some(Spanned { token, span }) = text_tokens.next() {
+            let source_token = match token {
                 TextToken::Comment(string) => SourceToken::Comment { string },
                 TextToken::Rune(rune) => match rune {
                     Rune::CloseDefinition => break,
                     Rune::OpenParameters => {
-                        while let Some(token) = text_tokens.next() {
+                        for Spanned { token, span } in &mut *text_tokens {
                             match token {
                                 TextToken::StringLiteral(name) => {
                                     tokens.push(SourceToken::ParameterDef { name })
@@ -28,23 +54,25 @@ impl Macro {
                                 TextToken::Rune(rune) => match rune {
                                     Rune::CloseParameters => break,
                                     _ => {
-                                        return Err(format!(
-                                            "Invalid rune '{}' in parameter list",
-                                            rune
+                                        return Err(invalid(
+                                            &format!("Invalid rune '{}' in parameter list", rune),
+                                            span,
+                                            "",
                                         ))
                                     }
                                 },
                                 _ => {
-                                    return Err(format!(
-                                        "Invalid token '{}' in parameter list",
-                                        token
+                                    return Err(invalid(
+                                        &format!("Invalid token '{}' in parameter list", token),
+                                        span,
+                                        "expected a parameter name",
                                     ))
                                 }
                             }
                         }
                         continue;
                     }
-                    _ => return Err("Invalid rune inside macro definition".into()),
+                    _ => return Err(invalid("Invalid rune inside macro definition", span, "")),
                 },
                 TextToken::Label(Command { marker, label }) => match marker {
                     Marker::CallRoutine => SourceToken::RoutineCallLocal { label },
@@ -58,25 +86,43 @@ impl Macro {
                     Marker::Parameter => SourceToken::ParameterUse { label },
                 },
                 TextToken::Assembly(opcode) => match opcode {
-                    opcode if opcode >= 176 && opcode < 180 => {
-                        let Some(TextToken::NumberLiteral(number)) = text_tokens.next() else {
-                                return Err("Text token after LIT opcode is not Number Literal".into());
-                            };
+                    opcode if (176..180).contains(&opcode) => {
+                        let number = match text_tokens.next() {
+                            Some(Spanned {
+                                token: TextToken::NumberLiteral(number),
+                                ..
+                            }) => number,
+                            next => {
+                                return Err(missing_literal(span, next)
+                                    .secondary(open, format!("in macro `{}`", name)))
+                            }
+                        };
                         let literal = match opcode {
                             176 => NumberLiteral::Byte(number as u8),
                             177 => NumberLiteral::Short(number as u16),
                             178 => NumberLiteral::Int(number as u32),
-                            179 => NumberLiteral::Long(number as u64),
+                            179 => NumberLiteral::Long(number),
                             _ => unreachable!(),
                         };
                         SourceToken::NumberLiteral { literal }
                     }
                     opcode => SourceToken::Instruction { opcode },
                 },
-                TextToken::Import(_) => return Err("Invalid import in macro def".into()),
-                TextToken::Path(_) => return Err("Invalid path in macro def".into()),
-                TextToken::StringLiteral(_) => return Err("Dangling string literal".into()),
-                TextToken::NumberLiteral(_) => return Err("Dangling number literal".into()),
+                TextToken::Import(_) => {
+                    return Err(invalid("Invalid import in macro def", span, ""))
+                }
+                TextToken::Path(_) => return Err(invalid("Invalid path in macro def", span, "")),
+                TextToken::StringLiteral(string) => {
+                    return Err(dangling_string(&string, span)
+                        .secondary(open, format!("in macro `{}`", name)))
+                }
+                TextToken::NumberLiteral(_) => {
+                    return Err(invalid(
+                        "Dangling number literal",
+                        span,
+                        "isn't the operand of a `LIT`",
+                    ))
+                }
                 TextToken::NewLine | TextToken::Tab(_) => continue,
             };
             tokens.push(source_token);
@@ -87,9 +133,9 @@ impl Macro {
 }
 impl Display for Macro {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Macro: {}\n", self.name)?;
+        writeln!(f, "Macro: {}", self.name)?;
         for token in &self.tokens {
-            write!(f, " - {}\n", token)?;
+            writeln!(f, " - {}", token)?;
         }
         Ok(())
     }
@@ -1,5 +1,6 @@
+use crate::assembler::diagnostic::{Diagnostic, Diagnostics};
 use crate::assembler::representation::{Import, Macro, Routine};
-use crate::assembler::tokens::{Rune, TextToken};
+use crate::assembler::tokens::{Rune, Spanned, TextToken};
 use std::fmt::Display;
 
 pub struct Module {
@@ -8,60 +9,100 @@ pub struct Module {
     pub routines: Vec<Routine>,
 }
 impl Module {
-    pub fn from_text_tokens(tokens: Vec<TextToken>) -> Result<Module, String> {
+    // a definition with an error is reported and skipped through its `;`, so
+    // parsing carries on with the next one
+    pub fn from_text_tokens(tokens: Vec<Spanned>, diagnostics: &mut Diagnostics) -> Module {
         let mut imports = Vec::new();
         let mut macros = Vec::new();
         let mut routines = Vec::new();
-        let mut text_tokens = tokens.into_iter();
-        while let Some(token) = text_tokens.next() {
-            match token {
+        let mut text_tokens = Tokens {
+            tokens: tokens.into_iter(),
+            closed: false,
+        };
+        while let Some(Spanned { token, span }) = text_tokens.next() {
+            let parsed = match token {
                 TextToken::Rune(rune) => match rune {
-                    Rune::OpenImport => {
-                        imports.append(&mut Import::from_text_tokens(&mut text_tokens)?);
-                    }
-                    Rune::OpenRoutine => {
-                        routines.push(Routine::from_text_tokens(false, &mut text_tokens)?);
-                    }
+                    Rune::OpenImport => Import::from_text_tokens(span, &mut text_tokens)
+                        .map(|mut import| imports.append(&mut import)),
+                    Rune::OpenRoutine => Routine::from_text_tokens(false, span, &mut text_tokens)
+                        .map(|routine| routines.push(routine)),
                     Rune::OpenExportedRoutine => {
-                        routines.push(Routine::from_text_tokens(true, &mut text_tokens)?)
+                        Routine::from_text_tokens(true, span, &mut text_tokens)
+                            .map(|routine| routines.push(routine))
                     }
                     Rune::OpenMacro => {
-                        macros.push(Macro::from_text_tokens(&mut text_tokens)?);
-                    }
-                    Rune::OpenComment => {
-                        while let Some(token) = text_tokens.next() {
-                            match token {
-                                TextToken::Rune(rune) => match rune {
-                                    Rune::CloseComment => break,
-                                    _ => continue,
-                                },
-                                _ => continue,
-                            }
-                        }
+                        Macro::from_text_tokens(span, &mut text_tokens).map(|m| macros.push(m))
                     }
-                    _ => return Err(format!("Invalid rune '{}' in module", rune)),
+                    _ => Err(
+                        Diagnostic::error(format!("Invalid rune '{}' in module", rune))
+                            .primary(span, "expected a definition")
+                            .note("definitions start with `+`, `:`, `^` or `%`"),
+                    ),
                 },
                 TextToken::Comment(_) | TextToken::NewLine | TextToken::Tab(_) => continue,
-                _ => return Err(format!("Invalid token '{}' in module", token)),
+                _ => Err(
+                    Diagnostic::error(format!("Invalid token '{}' in module", token))
+                        .primary(span, "outside of any definition"),
+                ),
+            };
+            match parsed {
+                Ok(()) if !text_tokens.closed => diagnostics.push(
+                    Diagnostic::warning("Definition is never closed")
+                        .primary(span, "this definition")
+                        .note("definitions end with `;`"),
+                ),
+                Ok(()) => {}
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    if !text_tokens.closed {
+                        text_tokens.skip_definition();
+                    }
+                }
             }
         }
-        Ok(Module {
+        Module {
             imports,
             macros,
             routines,
-        })
+        }
+    }
+}
+
+// the module's tokens, remembering whether the last one closed a definition
+struct Tokens {
+    tokens: std::vec::IntoIter<Spanned>,
+    closed: bool,
+}
+impl Tokens {
+    fn skip_definition(&mut self) {
+        while !self.closed && self.next().is_some() {}
+    }
+}
+impl Iterator for Tokens {
+    type Item = Spanned;
+
+    fn next(&mut self) -> Option<Spanned> {
+        let next = self.tokens.next();
+        self.closed = matches!(
+            next,
+            Some(Spanned {
+                token: TextToken::Rune(Rune::CloseDefinition),
+                ..
+            })
+        );
+        next
     }
 }
 impl Display for Module {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for i in &self.imports {
-            write!(f, "{}\n", i)?;
+            writeln!(f, "{}", i)?;
         }
         for m in &self.macros {
-            write!(f, "{}\n", m)?;
+            writeln!(f, "{}", m)?;
         }
         for r in &self.routines {
-            write!(f, "{}\n", r)?;
+            writeln!(f, "{}", r)?;
         }
         Ok(())
     }
@@ -0,0 +1,279 @@
+use super::import::SymbolType;
+use super::{Import, Macro, Module};
+use crate::assembler::diagnostic::Diagnostics;
+use crate::assembler::parsing::parse_module;
+use crate::assembler::stdlib;
+use crate::assembler::tokens::{Label, SourceToken, IMPORT_PATH_SEPARATOR};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/*
+    An import names a module by its path, and `.co.stack` is looked for as
+    `co/stack.co` under each library root in turn, then among the modules
+    built into the assembler (see `stdlib`). The module is parsed, its own
+    imports resolved, and every macro and routine reachable from the
+    imported symbols is copied into the importing module.
+
+    Copies are renamed `.co.stack.name`, which can't collide with anything
+    in the importing module since `.` is reserved, and references between
+    them are renamed to match. References made through a parameter, like
+    `~{macro}`, are left for the importing module to resolve. An imported
+    macro is also copied under its import name, and an imported routine is
+    renamed to it.
+*/
+pub struct Resolver {
+    roots: Vec<PathBuf>,
+    loading: Vec<String>,
+}
+impl Resolver {
+    pub const LIB_PATH_VAR: &'static str = "COHOST_LIB_PATH";
+
+    pub fn new(roots: Vec<PathBuf>) -> Resolver {
+        Resolver {
+            roots,
+            loading: Vec::new(),
+        }
+    }
+    // the given roots, then any in `COHOST_LIB_PATH`
+    pub fn with_env(mut roots: Vec<PathBuf>) -> Resolver {
+        if let Some(paths) = std::env::var_os(Resolver::LIB_PATH_VAR) {
+            roots.extend(std::env::split_paths(&paths));
+        }
+        Resolver::new(roots)
+    }
+
+    pub fn resolve(&mut self, mut module: Module) -> Result<Module, String> {
+        // group imports by module, in the order modules are first imported
+        let mut modules: Vec<(Vec<String>, Vec<Import>)> = Vec::new();
+        for import in std::mem::take(&mut module.imports) {
+            let path = match import.path.names().split_last() {
+                Some((_, path)) if !path.is_empty() => path.to_vec(),
+                _ => return Err(format!("Import `{}` has no module path", import.name)),
+            };
+            match modules.iter_mut().find(|(p, _)| *p == path) {
+                Some((_, imports)) => imports.push(import),
+                None => modules.push((path, vec![import])),
+            }
+        }
+
+        for (path, imports) in modules {
+            self.import(&path, imports, &mut module)?;
+        }
+        Ok(module)
+    }
+
+    fn import(
+        &mut self,
+        path: &[String],
+        imports: Vec<Import>,
+        into: &mut Module,
+    ) -> Result<(), String> {
+        let name = module_name(path);
+        if self.loading.contains(&name) {
+            return Err(format!(
+                "Import cycle: {} -> {}",
+                self.loading.join(" -> "),
+                name
+            ));
+        }
+        let text = self.find(path)?;
+        let mut diagnostics = Diagnostics::default();
+        let module = parse_module(&text, &mut diagnostics)
+            .ok_or_else(|| diagnostics.render(&text, &name))?;
+        self.loading.push(name.clone());
+        let module = self.resolve(module);
+        self.loading.pop();
+        let module = module?;
+
+        // the symbols asked for, by the name they have in the module
+        let mut wanted_macros = Vec::new();
+        let mut wanted_routines = Vec::new();
+        for import in &imports {
+            let identifier = import.path.names().last().cloned().unwrap_or_default();
+            let found = match import.symbol {
+                SymbolType::Macro => module.macros.iter().any(|m| m.name == identifier),
+                SymbolType::Routine => module.routines.iter().any(|r| r.name == identifier),
+            };
+            let kind = match import.symbol {
+                SymbolType::Macro => "macro",
+                SymbolType::Routine => "routine",
+            };
+            if !found {
+                return Err(format!(
+                    "Module `{}` has no {} `{}`",
+                    name, kind, identifier
+                ));
+            }
+            match import.symbol {
+                SymbolType::Macro => wanted_macros.push((identifier, import.name.clone())),
+                SymbolType::Routine => wanted_routines.push((identifier, import.name.clone())),
+            }
+        }
+
+        // everything reachable, named for the importing module
+        let (macros, routines) = reachable(
+            &module,
+            wanted_macros
+                .iter()
+                .map(|(identifier, _)| identifier.clone()),
+            wanted_routines
+                .iter()
+                .map(|(identifier, _)| identifier.clone()),
+        );
+        let qualify = |n: &String| (n.clone(), format!("{}{}{}", name, IMPORT_PATH_SEPARATOR, n));
+        let macro_names: HashMap<String, String> = macros.iter().map(qualify).collect();
+        let mut routine_names: HashMap<String, String> = routines.iter().map(qualify).collect();
+        let mut aliases: HashMap<&String, &String> = HashMap::new();
+        for (identifier, alias) in &wanted_routines {
+            if let Some(previous) = aliases.insert(identifier, alias) {
+                if previous != alias {
+                    return Err(format!(
+                        "Routine `{}` from `{}` is imported as both `{}` and `{}`",
+                        identifier, name, previous, alias
+                    ));
+                }
+            }
+            routine_names.insert(identifier.clone(), alias.clone());
+        }
+
+        for mut mac in module.macros {
+            if !macros.contains(&mac.name) {
+                continue;
+            }
+            rename(&mut mac.tokens, &macro_names, &routine_names);
+            for (_, alias) in wanted_macros.iter().filter(|(id, _)| *id == mac.name) {
+                into.macros.push(Macro {
+                    name: alias.clone(),
+                    tokens: mac.tokens.clone(),
+                });
+            }
+            mac.name = macro_names[&mac.name].clone();
+            into.macros.push(mac);
+        }
+        for mut routine in module.routines {
+            if !routines.contains(&routine.name) {
+                continue;
+            }
+            rename(&mut routine.tokens, &macro_names, &routine_names);
+            // its lines are in another file, which the source map doesn't list
+            routine
+                .tokens
+                .retain(|token| !matches!(token, SourceToken::Line { .. }));
+            routine.name = routine_names[&routine.name].clone();
+            into.routines.push(routine);
+        }
+        Ok(())
+    }
+
+    // the source of a module, from the first library root that has it or
+    // else from the standard library
+    fn find(&self, path: &[String]) -> Result<String, String> {
+        for root in &self.roots {
+            let mut file = root.clone();
+            file.extend(path);
+            file.set_extension("co");
+            if file.is_file() {
+                return std::fs::read_to_string(&file)
+                    .map_err(|e| format!("Couldn't read {}: {}", file.display(), e));
+            }
+        }
+
+        if let Some(source) = stdlib::module(&module_name(path)) {
+            return Ok(source.to_string());
+        }
+
+        let roots: Vec<String> = self.roots.iter().map(|r| r.display().to_string()).collect();
+        match roots.is_empty() {
+            true => Err(format!(
+                "Couldn't find module `{}`: no library paths (use --lib-path or {})",
+                module_name(path),
+                Resolver::LIB_PATH_VAR
+            )),
+            false => Err(format!(
+                "Couldn't find module `{}` in {}",
+                module_name(path),
+                roots.join(", ")
+            )),
+        }
+    }
+}
+
+fn module_name(path: &[String]) -> String {
+    let separator = IMPORT_PATH_SEPARATOR.to_string();
+    format!("{}{}", separator, path.join(&separator))
+}
+
+// macros and routines used by the given ones, directly or not, through plain names
+fn reachable(
+    module: &Module,
+    macros: impl Iterator<Item = String>,
+    routines: impl Iterator<Item = String>,
+) -> (HashSet<String>, HashSet<String>) {
+    let mut found_macros = HashSet::new();
+    let mut found_routines = HashSet::new();
+    let mut pending: Vec<(bool, String)> = macros.map(|m| (true, m)).collect();
+    pending.extend(routines.map(|r| (false, r)));
+
+    while let Some((is_macro, name)) = pending.pop() {
+        let tokens = match is_macro {
+            true if found_macros.insert(name.clone()) => module
+                .macros
+                .iter()
+                .find(|m| m.name == name)
+                .map(|m| &m.tokens),
+            false if found_routines.insert(name.clone()) => module
+                .routines
+                .iter()
+                .find(|r| r.name == name)
+                .map(|r| &r.tokens),
+            _ => continue,
+        };
+        for token in tokens.into_iter().flatten() {
+            let (is_macro, label) = match token {
+                SourceToken::MacroUse { label } => (true, label),
+                SourceToken::RoutineCallLocal { label }
+                | SourceToken::RoutineAddressLocal { label } => (false, label),
+                _ => continue,
+            };
+            if let Some(name) = plain_name(label) {
+                pending.push((is_macro, name));
+            }
+        }
+    }
+
+    // names used but not defined here are left to the importing module
+    found_macros.retain(|name| module.macros.iter().any(|m| m.name == *name));
+    found_routines.retain(|name| module.routines.iter().any(|r| r.name == *name));
+    (found_macros, found_routines)
+}
+
+fn rename(
+    tokens: &mut [SourceToken],
+    macros: &HashMap<String, String>,
+    routines: &HashMap<String, String>,
+) {
+    for token in tokens {
+        let (names, label) = match token {
+            SourceToken::MacroUse { label } => (macros, label),
+            SourceToken::RoutineCallLocal { label }
+            | SourceToken::RoutineAddressLocal { label } => (routines, label),
+            _ => continue,
+        };
+        if let Some(name) = plain_name(label).and_then(|name| names.get(&name)) {
+            *label = Label::literal(name.clone());
+        }
+    }
+}
+
+fn plain_name(label: &Label) -> Option<String> {
+    match label.has_parameters() {
+        true => None,
+        false => label.to_string(&HashMap::new()).ok(),
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::assembler::tokens::{Command, Marker, NumberLiteral, Rune, SourceToken, TextToken};
+use crate::assembler::diagnostic::{suggest_opcode, Diagnostic, Span};
+use crate::assembler::tokens::{
+    Command, Marker, NumberLiteral, Rune, SourceToken, Spanned, TextToken,
+};
 use std::fmt::Display;
 
 pub struct Routine {
@@ -9,19 +12,46 @@ pub struct Routine {
 impl Routine {
     pub fn from_text_tokens(
         export: bool,
-        text_tokens: &mut dyn Iterator<Item = TextToken>,
-    ) -> Result<Routine, String> {
-        let Some(TextToken::StringLiteral(name)) = text_tokens.next() else {
-            return Err("First token of routine must be string literal".into());
+        open: Span,
+        text_tokens: &mut dyn Iterator<Item = Spanned>,
+    ) -> Result<Routine, Diagnostic> {
+        let name = match text_tokens.next() {
+            Some(Spanned {
+                token: TextToken::StringLiteral(name),
+                ..
+            }) => name,
+            Some(Spanned { span, .. }) => {
+                return Err(
+                    Diagnostic::error("First token of routine must be string literal")
+                        .primary(span, "expected the routine's name")
+                        .secondary(open, "in this routine"),
+                )
+            }
+            None => {
+                return Err(
+                    Diagnostic::error("First token of routine must be string literal")
+                        .primary(open, "routine has no name"),
+                )
+            }
         };
 
-        let mut tokens = Vec::new();
-        while let Some(text_token) = text_tokens.next() {
-            let source_token = match text_token {
+        let invalid = |message: &str, span: Span, label: &str| {
+            Diagnostic::error(message)
+                .primary(span, label)
+                .secondary(open, format!("in routine `{}`", name))
+        };
+        let mut tokens = vec![SourceToken::Line { line: open.line }];
+        let mut line = open.line;
+        while let Some(Spanned { token, span }) = text_tokens.next() {
+            if span.line != line && !matches!(token, TextToken::NewLine | TextToken::Tab(_)) {
+                line = span.line;
+                tokens.push(SourceToken::Line { line });
+            }
+            let source_token = match token {
                 TextToken::Comment(string) => SourceToken::Comment { string },
                 TextToken::Rune(rune) => match rune {
                     Rune::CloseDefinition => break,
-                    _ => return Err("Invalid rune inside routine definition".into()),
+                    _ => return Err(invalid("Invalid rune inside routine definition", span, "")),
                 },
                 TextToken::Label(Command { marker, label }) => match marker {
                     Marker::CallRoutine => SourceToken::RoutineCallLocal { label },
@@ -35,25 +65,43 @@ impl Routine {
                     Marker::Parameter => SourceToken::ParameterUse { label },
                 },
                 TextToken::Assembly(opcode) => match opcode {
-                    opcode if opcode >= 176 && opcode < 180 => {
-                        let Some(TextToken::NumberLiteral(number)) = text_tokens.next() else {
-                            return Err("Text token after LIT opcode is not Number Literal".into());
+                    opcode if (176..180).contains(&opcode) => {
+                        let number = match text_tokens.next() {
+                            Some(Spanned {
+                                token: TextToken::NumberLiteral(number),
+                                ..
+                            }) => number,
+                            next => {
+                                return Err(missing_literal(span, next)
+                                    .secondary(open, format!("in routine `{}`", name)))
+                            }
                         };
                         let literal = match opcode {
                             176 => NumberLiteral::Byte(number as u8),
                             177 => NumberLiteral::Short(number as u16),
                             178 => NumberLiteral::Int(number as u32),
-                            179 => NumberLiteral::Long(number as u64),
+                            179 => NumberLiteral::Long(number),
                             _ => unreachable!(),
                         };
                         SourceToken::NumberLiteral { literal }
                     }
                     opcode => SourceToken::Instruction { opcode },
                 },
-                TextToken::Import(_) => return Err("Invalid import in routine def".into()),
-                TextToken::Path(_) => return Err("Invalid path in routine def".into()),
-                TextToken::StringLiteral(_) => return Err("Dangling string literal".into()),
-                TextToken::NumberLiteral(_) => return Err("Dangling number literal".into()),
+                TextToken::Import(_) => {
+                    return Err(invalid("Invalid import in routine def", span, ""))
+                }
+                TextToken::Path(_) => return Err(invalid("Invalid path in routine def", span, "")),
+                TextToken::StringLiteral(string) => {
+                    return Err(dangling_string(&string, span)
+                        .secondary(open, format!("in routine `{}`", name)))
+                }
+                TextToken::NumberLiteral(_) => {
+                    return Err(invalid(
+                        "Dangling number literal",
+                        span,
+                        "isn't the operand of a `LIT`",
+                    ))
+                }
                 TextToken::NewLine | TextToken::Tab(_) => continue,
             };
             tokens.push(source_token);
@@ -66,11 +114,33 @@ impl Routine {
         })
     }
 }
+
+// a `LIT` at `span` that isn't followed by its number
+pub(super) fn missing_literal(span: Span, next: Option<Spanned>) -> Diagnostic {
+    let diagnostic = Diagnostic::error("Text token after LIT opcode is not Number Literal");
+    match next {
+        Some(next) => diagnostic
+            .primary(next.span, "expected a number")
+            .secondary(span, "this takes a number after it"),
+        None => diagnostic.primary(span, "expected a number after this"),
+    }
+}
+
+// a word that isn't anything, but may be a misspelled opcode
+pub(super) fn dangling_string(string: &str, span: Span) -> Diagnostic {
+    let diagnostic = Diagnostic::error("Dangling string literal");
+    match suggest_opcode(string) {
+        Some(opcode) => diagnostic
+            .primary(span, "not an opcode or label")
+            .note(format!("did you mean `{}`?", opcode)),
+        None => diagnostic.primary(span, "not an opcode or label"),
+    }
+}
 impl Display for Routine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Routine: {}\n", self.name)?;
+        writeln!(f, "Routine: {}", self.name)?;
         for token in &self.tokens {
-            write!(f, " - {}\n", token)?;
+            writeln!(f, " - {}", token)?;
         }
         Ok(())
     }
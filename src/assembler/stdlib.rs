@@ -0,0 +1,23 @@
+/*
+    The standard library, built into the assembler so `+ .co.stack` works
+    without a library path. A module of the same name under a library root
+    is found first, so a project can replace one.
+*/
+const MODULES: [(&str, &str); 4] = [
+    (".co.stack", include_str!("stdlib/co/stack.co")),
+    (".co.math", include_str!("stdlib/co/math.co")),
+    (".co.cond", include_str!("stdlib/co/cond.co")),
+    (".co.loop", include_str!("stdlib/co/loop.co")),
+];
+
+// the source of a standard module, by its import path
+pub fn module(name: &str) -> Option<&'static str> {
+    MODULES
+        .iter()
+        .find(|(module, _)| *module == name)
+        .map(|(_, source)| *source)
+}
+
+pub fn modules() -> impl Iterator<Item = &'static str> {
+    MODULES.iter().map(|(module, _)| *module)
+}
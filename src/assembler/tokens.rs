@@ -15,7 +15,7 @@ pub use number_literal::NumberLiteral;
 pub use path::Path;
 pub use rune::Rune;
 pub use source_token::SourceToken;
-pub use text_tokens::TextToken;
+pub use text_tokens::{Spanned, TextToken};
 
 pub const COMMENT_OPEN: char = '(';
 pub const COMMENT_CLOSE: char = ')';
@@ -40,30 +40,57 @@ pub const ANCHOR_DEF: char = '#';
 pub const ANCHOR_ADDR_ABS: char = '*';
 pub const ANCHOR_ADDR_REL: char = '&';
 
+pub const STRING_QUOTE: char = '"';
+
+// characters that end the token before them; a comment or a one-character rune
+pub fn is_delimiter(c: char) -> bool {
+    matches!(
+        c,
+        COMMENT_OPEN | COMMENT_CLOSE | DEFINITION_CLOSE | MACRO_PARAM_OPEN | MACRO_PARAM_CLOSE
+    )
+}
+
+// a name can't start with a character that marks what a token is
 pub fn validate_string(s: &str) -> Result<(), String> {
-    const RESERVED_CHARS: [char; 22] = [
-        COMMENT_OPEN,
-        COMMENT_CLOSE,
+    const RESERVED_LEADING_CHARS: [char; 12] = [
         IMPORT_DEF,
         ROUTINE_DEF,
         EXPORTED_ROUTINE_DEF,
-        DEFINITION_CLOSE,
-        IMPORT_PATH_SEPARATOR,
-        IMPORT_NAME_ASSIGNMENT,
         ROUTINE_CALL,
         EXPORTED_ROUTINE_CALL,
         ROUTINE_ADDRESS,
         EXPORTED_ROUTINE_ADDRESS,
         MACRO_DEF,
+        MACRO_PARAM,
+        ANCHOR_DEF,
+        ANCHOR_ADDR_ABS,
+        ANCHOR_ADDR_REL,
+    ];
+    if let Some(c) = s
+        .chars()
+        .next()
+        .filter(|c| RESERVED_LEADING_CHARS.contains(c))
+    {
+        return Err(format!("'{}' starts with reserved character '{}'", s, c));
+    }
+    validate_name(s)
+}
+
+// nor contain one that delimits tokens, or splits paths, imports or labels,
+// or `~`, which names the anchors each macro use makes
+pub fn validate_name(s: &str) -> Result<(), String> {
+    const RESERVED_CHARS: [char; 11] = [
+        COMMENT_OPEN,
+        COMMENT_CLOSE,
+        DEFINITION_CLOSE,
+        IMPORT_PATH_SEPARATOR,
+        IMPORT_NAME_ASSIGNMENT,
         MACRO_PARAM_OPEN,
         MACRO_PARAM_CLOSE,
         MACRO_PARAM_USE_OPEN,
         MACRO_PARAM_USE_CLOSE,
-        MACRO_PARAM,
         MACRO_USE,
-        ANCHOR_DEF,
-        ANCHOR_ADDR_ABS,
-        ANCHOR_ADDR_REL,
+        STRING_QUOTE,
     ];
     for c in RESERVED_CHARS {
         if s.contains(c) {
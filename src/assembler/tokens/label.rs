@@ -4,7 +4,7 @@ use std::{
     str::FromStr,
 };
 
-use crate::assembler::tokens::validate_string;
+use crate::assembler::tokens::validate_name;
 
 use super::{
     ANCHOR_ADDR_ABS, ANCHOR_ADDR_REL, ANCHOR_DEF, EXPORTED_ROUTINE_ADDRESS, EXPORTED_ROUTINE_CALL,
@@ -82,7 +82,7 @@ impl Label {
         let mut buffer = String::new();
         for component in &self.components {
             match component {
-                Component::Literal(string) => buffer.push_str(&string),
+                Component::Literal(string) => buffer.push_str(string),
                 Component::Parameter(param) => match parameters.get(param) {
                     Some(value) => buffer.push_str(value),
                     None => return Err(format!("Label contains undefined parameter `{}`", param)),
@@ -91,13 +91,27 @@ impl Label {
         }
         Ok(buffer)
     }
+    // the label with its parameters filled in
+    pub fn bind(&self, parameters: &HashMap<String, String>) -> Result<Label, String> {
+        Ok(Label::literal(self.to_string(parameters)?))
+    }
+    pub fn literal(string: String) -> Label {
+        Label {
+            components: vec![Component::Literal(string)],
+        }
+    }
+    pub fn has_parameters(&self) -> bool {
+        self.components
+            .iter()
+            .any(|component| matches!(component, Component::Parameter(_)))
+    }
 }
 impl FromStr for Label {
     type Err = String;
     fn from_str(s: &str) -> Result<Label, String> {
         fn validate(buffer: &mut String) -> Result<String, String> {
             let string = buffer.clone();
-            if let Err(e) = validate_string(&string) {
+            if let Err(e) = validate_name(&string) {
                 return Err(format!("Invalid Label Component: {}", e));
             }
             buffer.clear();
@@ -124,7 +138,7 @@ impl FromStr for Label {
                 MACRO_PARAM_USE_OPEN => {
                     // start reading in a parameter key
                     let mut param_buffer = String::new();
-                    while let Some(ch) = chars.next() {
+                    for ch in chars.by_ref() {
                         match ch {
                             MACRO_PARAM_USE_CLOSE => {
                                 push_literal(&mut buffer, &mut tokens)?;
@@ -9,6 +9,9 @@ impl Path {
     pub fn push(&mut self, s: String) {
         self.names.push(s)
     }
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
 }
 impl FromStr for Path {
     type Err = String;
@@ -16,6 +16,7 @@ pub enum SourceToken {
     AnchorDef { label: Label },
     AnchorAddressRelative { label: Label },
     AnchorAddressAbsolute { label: Label },
+    Line { line: usize }, // where the next tokens start in the source
 }
 impl Display for SourceToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -43,6 +44,7 @@ impl Display for SourceToken {
             SourceToken::AnchorAddressAbsolute { label } => {
                 format!("Anchor Address Absolute `{:?}`", label)
             }
+            SourceToken::Line { line } => format!("Line( {} )", line),
         };
         write!(f, "{}", s)
     }
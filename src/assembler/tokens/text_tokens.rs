@@ -1,3 +1,4 @@
+use crate::assembler::diagnostic::Span;
 use crate::assembler::tokens::{validate_string, Command, Import, Path, Rune};
 use crate::core::{opcode_to_str, str_to_opcode};
 use std::{fmt::Display, str::FromStr};
@@ -14,6 +15,12 @@ pub enum TextToken {
     NewLine,
     Tab(u8),
 }
+// a token and the text it was read from
+pub struct Spanned {
+    pub token: TextToken,
+    pub span: Span,
+}
+
 impl FromStr for TextToken {
     type Err = String;
 
@@ -49,7 +56,7 @@ impl FromStr for TextToken {
         }
 
         // validate remaining string
-        if let Ok(_) = validate_string(s) {
+        if validate_string(s).is_ok() {
             let s = String::from(s);
             return Ok(Self::StringLiteral(s));
         }
@@ -79,8 +86,5 @@ fn parse_number(string: &str) -> Option<u64> {
         Some(hex) => (hex, 16),
         None => (string, 10),
     };
-    match u64::from_str_radix(token, radix) {
-        Ok(lit) => Some(lit),
-        Err(_) => None,
-    }
+    u64::from_str_radix(token, radix).ok()
 }
@@ -1,9 +1,16 @@
 use cohost::assembler::{
-    parsing::parse_text,
-    representation::{Context, Library, Module},
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{Context, Layout, Library, Resolver},
 };
+use cohost::debugger::{LineEntry, RoutineSymbol, SourceMap};
 
-use std::{collections::HashMap, fs::read_to_string, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
 const HELP: &str = "
 assemble v1 by @jakintosh
@@ -15,6 +22,21 @@ the assembled binary output.
 USAGE:
 `-s` or `--source`              | file with source code
 `-o` or `--output` (required)   | file for compiled output
+`-l` or `--lib-path`            | directories to look for imported modules
+                                | in, separated like PATH; searched before
+                                | those in COHOST_LIB_PATH
+`--library`                     | library file that exported calls
+                                | (`<name`, `@name`) are looked up in
+`--max-errors`                  | most errors and warnings to print
+                                | (default 20); all of them are counted
+`--map`                         | file for the source map, for `cohost`
+                                | and `cohost-dap` (default: the output
+                                | path with a `.map` extension)
+
+routines are laid out in the order they're defined, starting at 0x0000,
+so the first routine in the source is where the rom starts running.
+exported routines aren't copied into the rom; they're called by hash,
+so run it with the same library (see `cohost --library`).
 
 VALID ARGUMENT SYNTAX:
     `-s=file`
@@ -25,6 +47,10 @@ VALID ARGUMENT SYNTAX:
 struct Parameters {
     source: PathBuf,
     output: PathBuf,
+    lib_paths: Vec<PathBuf>,
+    library: Option<PathBuf>,
+    max_errors: usize,
+    map: PathBuf,
 }
 impl TryFrom<std::env::Args> for Parameters {
     type Error = String;
@@ -74,30 +100,123 @@ impl TryFrom<std::env::Args> for Parameters {
 
         let source = map_arg(&map, "s", "source", Err("--source param missing".into()))?.into();
         let output = map_arg(&map, "o", "output", Err("--output param missing".into()))?.into();
-        Ok::<Parameters, String>(Parameters { source, output })
+        let lib_paths = map_arg(&map, "l", "lib-path", Ok(String::new()))?;
+        let lib_paths = std::env::split_paths(&lib_paths)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect();
+        let library = map_arg(&map, "library", "library", Err(String::new()))
+            .ok()
+            .map(PathBuf::from);
+        let max_errors = map_arg(
+            &map,
+            "max-errors",
+            "max-errors",
+            Ok(Diagnostics::DEFAULT_LIMIT.to_string()),
+        )?;
+        let max_errors = max_errors
+            .parse()
+            .map_err(|_| format!("invalid --max-errors '{}'", max_errors))?;
+        let map = match map_arg(&map, "map", "map", Err(String::new())) {
+            Ok(map) => PathBuf::from(map),
+            Err(_) => PathBuf::from(&output).with_extension("map"),
+        };
+        Ok::<Parameters, String>(Parameters {
+            source,
+            output,
+            lib_paths,
+            library,
+            max_errors,
+            map,
+        })
     }
 }
 
-fn main() -> Result<(), String> {
-    let Parameters { source, output } = std::env::args().try_into().map_err(|e| {
-        println!("{}", HELP);
-        format!("{}", e)
-    })?;
+fn main() -> ExitCode {
+    let parameters = match std::env::args().try_into() {
+        Ok(parameters) => parameters,
+        Err(e) => {
+            println!("{}", HELP);
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match assemble(parameters) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let assembly_text = match read_to_string(source) {
+fn assemble(parameters: Parameters) -> Result<(), String> {
+    let Parameters {
+        source,
+        output,
+        lib_paths,
+        library,
+        max_errors,
+        map,
+    } = parameters;
+
+    let assembly_text = match read_to_string(&source) {
         Ok(asm) => asm,
         Err(e) => return Err(format!("Couldn't read source: {}", e)),
     };
-    let text_tokens = parse_text(&assembly_text)?;
-    let module = Module::from_text_tokens(text_tokens)?;
-    println!("Module:\n\n{}", module);
-    // let library = Library::new();
-    // let context = Context::new(&library, module)?;
-    // let byteco = context.assemble()?;
+    let mut diagnostics = Diagnostics::new(max_errors);
+    let module = parse_module(&assembly_text, &mut diagnostics);
+    let rendered = diagnostics.render(&assembly_text, &source.display().to_string());
+    let Some(module) = module else {
+        return Err(rendered);
+    };
+    if !diagnostics.is_empty() {
+        eprint!("{}", rendered);
+    }
+    let module = Resolver::with_env(lib_paths).resolve(module)?;
+    let library = match library {
+        Some(path) => Library::load(&path)?,
+        None => Library::new(),
+    };
+    let context = Context::new(&library, module)?;
+    let (binary, layout) = context.link()?;
 
-    Ok(())
+    std::fs::write(output, binary).map_err(|e| format!("{}", e))?;
+    std::fs::write(&map, source_map(&layout, &source).to_string())
+        .map_err(|e| format!("Couldn't write source map {}: {}", map.display(), e))
+}
 
-    // let binary = binary_from_text(&assembly);
+// the routines and lines of `source`; imported routines have no lines
+fn source_map(layout: &Layout, source: &Path) -> SourceMap {
+    let mut map = SourceMap::new();
+    map.sources
+        .push(std::fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf()));
+
+    let mut starts: Vec<(&String, u16)> = layout
+        .routines
+        .iter()
+        .map(|(name, start)| (name, *start))
+        .collect();
+    starts.sort_by_key(|(_, start)| *start);
+    // a routine ends where the next starts, and the last where the rom does
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|(_, start)| *start as u32)
+        .chain([layout.size as u32]);
+    for ((name, start), end) in starts.iter().zip(ends) {
+        map.routines.push(RoutineSymbol {
+            name: name.to_string(),
+            start: *start,
+            end,
+        });
+    }
 
-    // std::fs::write(output, binary).map_err(|e| format!("{}", e))
+    for (address, line) in &layout.lines {
+        map.lines.push(LineEntry {
+            address: *address,
+            source: 0,
+            line: *line as u32,
+        });
+    }
+    map
 }
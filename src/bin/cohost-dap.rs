@@ -0,0 +1,25 @@
+use cohost::debugger::dap;
+use std::io::BufReader;
+
+const HELP: &str = "
+cohost-dap v1 by @jakintosh
+
+serves the debug adapter protocol over stdin/stdout for the coalescent
+core virtual CPU. meant to be launched by an editor, not by hand.
+
+the `launch` request takes:
+`program`     | rom file
+`sourceMap`   | source map file (defaults to the rom path with a `.map` extension)
+`stopOnEntry` | pause before the first instruction
+`history`     | steps kept for stepping backwards (0 disables)";
+
+fn main() -> Result<(), String> {
+    if std::env::args().len() > 1 {
+        println!("{}", HELP);
+        return Ok(());
+    }
+
+    let messages = dap::spawn_reader(BufReader::new(std::io::stdin()));
+    let mut stdout = std::io::stdout();
+    dap::serve(messages, &mut stdout).map_err(|e| e.to_string())
+}
@@ -1,7 +1,9 @@
+use cohost::assembler::representation::Library;
 use cohost::core::{
     self,
-    device::{Device, Devices},
+    device::{self, DeviceMap},
 };
+use cohost::debugger::{gdb, Profile, Session, SourceMap};
 use std::{
     collections::HashMap,
     io::{Read, Write},
@@ -16,6 +18,32 @@ pass in the location of the binary rom you want to run.
 
 USAGE:
 `-r` or `--rom`  | rom file
+`-m` or `--mode` | `step` through instructions one at a time (default),
+                 | or `run` at full speed
+`-g` or `--gdb`  | serve the gdb remote protocol on a port or unix socket
+`--history`      | steps kept for reverse debugging under gdb (0 disables);
+                 | each keeps what its instruction changed, so memory
+                 | use grows with it
+`--check`        | debug checks to turn on, comma separated:
+                 | `calls` faults when a return doesn't match its call
+                 | `widths` warns when an operand is taken at another
+                 | width than it was pushed, `widths-fault` faults
+                 | `memory` warns when a load or literal reads memory
+                 | nothing wrote, `memory-fault` faults
+`--profile`      | runs until the program stops, then writes the
+                 | instructions spent in each stack of routines to
+                 | this file as folded stacks, and prints a summary;
+                 | it stops on a fault, when the routine it started in
+                 | returns with nothing on the return stack, or after
+                 | `--steps` instructions
+`--steps`        | instructions a profile runs at most (default: no limit)
+`--map`          | source map naming the routines (default: the rom
+                 | path with a `.map` extension)
+`--top`          | routines listed in the profile summary (default 10)
+`--library`      | library that routines called by hash are loaded
+                 | from, the first time they're called
+`--link-at`      | address routines from the library are loaded at,
+                 | up to the end of memory (default 0xC000)
 
 VALID ARGUMENT SYNTAX:
     `-r=file`
@@ -23,8 +51,29 @@ VALID ARGUMENT SYNTAX:
     `--rom=file`
     `--rom file`";
 
+enum Mode {
+    Step,
+    Run,
+}
+
+enum Check {
+    Calls,
+    Widths { fault: bool },
+    Memory { fault: bool },
+}
+
 struct Parameters {
     rom: PathBuf,
+    mode: Mode,
+    gdb: Option<String>,
+    history: usize,
+    checks: Vec<Check>,
+    profile: Option<PathBuf>,
+    map: Option<PathBuf>,
+    top: usize,
+    steps: Option<u64>,
+    library: Option<PathBuf>,
+    link_at: u16,
 }
 impl TryFrom<std::env::Args> for Parameters {
     type Error = String;
@@ -72,30 +121,113 @@ impl TryFrom<std::env::Args> for Parameters {
             }
         }
 
-        let rom = map_arg(&map, "r", "rom", Err("--rom param missing".into()))?.into();
-        Ok::<Parameters, String>(Parameters { rom })
+        let rom: PathBuf = map_arg(&map, "r", "rom", Err("--rom param missing".into()))?.into();
+        let mode = match map_arg(&map, "m", "mode", Ok("step".into()))?.as_str() {
+            "step" => Mode::Step,
+            "run" => Mode::Run,
+            mode => return Err(format!("unknown --mode '{}'", mode)),
+        };
+        let gdb = map_arg(&map, "g", "gdb", Err("--gdb param missing".into())).ok();
+        let history = map_arg(
+            &map,
+            "history",
+            "history",
+            Ok(core::Journal::DEFAULT_CAPACITY.to_string()),
+        )?
+        .parse()
+        .map_err(|_| String::from("--history must be a number"))?;
+        let checks = map_arg(&map, "check", "check", Ok(String::new()))?
+            .split(',')
+            .filter(|check| !check.is_empty())
+            .map(|check| match check {
+                "calls" => Ok(Check::Calls),
+                "widths" => Ok(Check::Widths { fault: false }),
+                "widths-fault" => Ok(Check::Widths { fault: true }),
+                "memory" => Ok(Check::Memory { fault: false }),
+                "memory-fault" => Ok(Check::Memory { fault: true }),
+                check => Err(format!("unknown --check '{}'", check)),
+            })
+            .collect::<Result<_, _>>()?;
+        let profile = map_arg(&map, "profile", "profile", Err(String::new()))
+            .ok()
+            .map(PathBuf::from);
+        let source_map = map_arg(&map, "map", "map", Err(String::new()))
+            .ok()
+            .map(PathBuf::from);
+        let top = map_arg(&map, "top", "top", Ok("10".into()))?
+            .parse()
+            .map_err(|_| String::from("--top must be a number"))?;
+        let steps = map_arg(&map, "steps", "steps", Err(String::new()))
+            .ok()
+            .map(|steps| steps.parse())
+            .transpose()
+            .map_err(|_| String::from("--steps must be a number"))?;
+        let library = map_arg(&map, "library", "library", Err(String::new()))
+            .ok()
+            .map(PathBuf::from);
+        let link_at = map_arg(&map, "link-at", "link-at", Ok("0xC000".into()))?;
+        let link_at = match link_at.strip_prefix("0x") {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => link_at.parse(),
+        }
+        .map_err(|_| String::from("--link-at must be an address"))?;
+        Ok::<Parameters, String>(Parameters {
+            rom,
+            mode,
+            gdb,
+            history,
+            checks,
+            profile,
+            map: source_map,
+            top,
+            steps,
+            library,
+            link_at,
+        })
     }
 }
 
 fn main() -> Result<(), String> {
-    let Parameters { rom } = std::env::args().try_into().map_err(|e| {
+    let Parameters {
+        rom,
+        mode,
+        gdb,
+        history,
+        checks,
+        profile,
+        map,
+        top,
+        steps,
+        library,
+        link_at,
+    } = std::env::args().try_into().inspect_err(|_| {
         println!("{}", HELP);
-        format!("{}", e)
     })?;
 
-    let Ok(rom) = std::fs::read(rom) else {
+    let Ok(rom_bytes) = std::fs::read(&rom) else {
         panic!("couldn't load rom");
     };
 
     // init CPU
     let mut cpu = core::CPU::new();
-    cpu.load_rom(rom);
+    for check in checks {
+        match check {
+            Check::Calls => cpu.shadow = Some(core::ShadowStack::new()),
+            Check::Widths { fault } => cpu.widths = Some(core::WidthCheck::new(fault)),
+            Check::Memory { fault } => cpu.init = Some(core::InitCheck::new(fault)),
+        }
+    }
+    cpu.load_rom(rom_bytes);
+    if let Some(library) = library {
+        let library = Library::load(&library)?;
+        cpu.linker = Some(library.linker(link_at as usize..0x1_0000));
+    }
 
     // // initialize all devices
     // let console = Box::new(device::Console::new());
 
     // // register all devices
-    let mut devices: HashMap<Devices, Box<dyn Device>> = HashMap::new();
+    let mut devices: DeviceMap = HashMap::new();
     // devices.insert(Devices::Console, console);
 
     // // connect devices to CPU
@@ -104,56 +236,87 @@ fn main() -> Result<(), String> {
     //     cpu.connect_device(id);
     // }
 
-    // run CPU
-    loop {
-        draw(&cpu);
-        cpu.execute();
-        check_dmas(&mut cpu);
-        check_devices(&mut cpu, &mut devices);
-    }
-}
-
-fn check_dmas(cpu: &mut core::CPU) {
-    for dma in cpu.dma_controllers {
-        let has_request = core::DMA::REQ_BIT & dma.status_reg != 0;
-        if has_request {
-            // process request
-            // let payload_range = dma.address as usize..(dma.address + dma.buffer_len) as usize;
-            // let payload = &cpu.memory[payload_range];
+    // hand the CPU to a remote debugger
+    if let Some(address) = gdb {
+        println!("waiting for gdb on {}", address);
+        let mut connection = gdb::listen(&address).map_err(|e| e.to_string())?;
+        if history > 0 {
+            cpu.journal = Some(core::Journal::new(history));
         }
+        let service = Box::new(|cpu: &mut core::CPU| device::service(cpu, &mut devices));
+        let mut session = Session::new(&mut cpu, service);
+        return gdb::serve(&mut session, connection.as_mut()).map_err(|e| e.to_string());
     }
-}
 
-fn check_devices(cpu: &mut core::CPU, devices: &mut HashMap<Devices, Box<dyn Device>>) {
-    for mut slot in cpu.devices {
-        // get the device for given slot
-        let device_type = slot.identifier.into();
-        let Some(device) = devices.get_mut(&device_type) else {
-            // eprint!("unregistered device");
-            continue;
+    // run CPU to a stop, counting where it spent its instructions
+    if let Some(path) = profile {
+        let map = match map {
+            Some(map) => SourceMap::load(&map)?,
+            None => {
+                let map = rom.with_extension("map");
+                match map.exists() {
+                    true => SourceMap::load(&map)?,
+                    false => SourceMap::new(), // routines are named by address
+                }
+            }
         };
-
-        // check status registers
-        let cpu_send = core::DeviceSlot::SEND_FLAG & slot.status_reg != 0;
-        let cpu_done = core::DeviceSlot::DONE_FLAG & slot.status_reg != 0;
-        let cpu_block = core::DeviceSlot::BLOCK_FLAG & slot.status_reg != 0;
-
-        // if cpu is sending data, receive it
-        if cpu_send {
-            device.recv(&slot.out_buffer);
-            if cpu_done {
-                slot.status_reg ^= core::DeviceSlot::SEND_FLAG; // turn off
-                slot.status_reg ^= core::DeviceSlot::DONE_FLAG; // turn off
+        let mut profile = Profile::new(map);
+        let fault = loop {
+            if steps.is_some_and(|steps| profile.instructions >= steps) {
+                break None;
             }
-        }
-
-        // don't read from device if cpu is blocking until outgoing is done
-        if !(cpu_send && !cpu_done && cpu_block) {
-            // if device has data waiting, pass it in
-            while let Some(device_buffer) = device.poll() {
-                slot.in_buffer.copy_from_slice(&device_buffer);
-                cpu.interrupt(slot.vector);
+            // returning from the routine profiling started in ends the program
+            let instruction = core::Instruction::from(cpu.memory[cpu.program_counter as usize]);
+            if matches!(instruction, core::Instruction::Return { .. }) && cpu.return_depth() == 0 {
+                break None;
+            }
+            if let Err(fault) = profile.step(&mut cpu) {
+                break Some(fault);
             }
+            if let Err(fault) = device::service(&mut cpu, &mut devices) {
+                break Some(fault);
+            }
+            warn(&mut cpu);
+        };
+        let mut file = std::fs::File::create(&path)
+            .map_err(|e| format!("couldn't create {}: {}", path.display(), e))?;
+        profile
+            .write_folded(&mut file)
+            .map_err(|e| format!("couldn't write {}: {}", path.display(), e))?;
+        eprintln!("{}", profile.summary(top));
+        return match fault {
+            Some(fault) => Err(fault.to_string()),
+            None => Ok(()),
+        };
+    }
+
+    // run CPU
+    match mode {
+        Mode::Step => loop {
+            draw(&cpu);
+            cpu.execute().map_err(|fault| fault.to_string())?;
+            device::service(&mut cpu, &mut devices).map_err(|fault| fault.to_string())?;
+            warn(&mut cpu);
+        },
+        Mode::Run => loop {
+            cpu.execute_block()
+                .map_err(|(_, fault)| fault.to_string())?;
+            device::service(&mut cpu, &mut devices).map_err(|fault| fault.to_string())?;
+            warn(&mut cpu);
+        },
+    }
+}
+
+// prints debug check reports as they come in
+fn warn(cpu: &mut core::CPU) {
+    if let Some(widths) = &mut cpu.widths {
+        for mismatch in widths.reports.drain(..) {
+            eprintln!("width mismatch: {}", mismatch);
+        }
+    }
+    if let Some(init) = &mut cpu.init {
+        for read in init.reports.drain(..) {
+            eprintln!("uninitialized read: {}", read);
         }
     }
 }
@@ -179,7 +342,7 @@ fn draw(cpu: &core::CPU) {
                 "> {:#08X} {:02X}    {}",
                 page_start + i,
                 byte,
-                core::Instruction::from(byte.clone())
+                core::Instruction::from(*byte)
             ),
             false => println!("  {:#08X} {:02X}", page_start + i, byte),
         }
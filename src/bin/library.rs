@@ -0,0 +1,186 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{hex, Library, Resolver},
+};
+
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf, process::ExitCode};
+
+const HELP: &str = "
+library v1 by @jakintosh
+
+keeps assembled routines in a library file, by the hash of their
+contents, so roms can link to them by name.
+
+USAGE:
+`library add`      | assembles a module and adds its routines, naming
+                   | the exported ones; creates the library if needed
+`library list`     | lists the named routines and macros
+`library resolve`  | prints the hash a routine name resolves to
+
+`-f` or `--file` (required) | library file
+`-s` or `--source`          | module to add
+`-l` or `--lib-path`        | directories to look for imported modules
+                            | in, separated like PATH
+`-n` or `--name`            | routine name to resolve
+
+VALID ARGUMENT SYNTAX:
+    `-f=file`
+    `-f file`
+    `--file=file`
+    `--file file`";
+
+enum Command {
+    Add {
+        source: PathBuf,
+        lib_paths: Vec<PathBuf>,
+    },
+    List,
+    Resolve {
+        name: String,
+    },
+}
+
+struct Parameters {
+    file: PathBuf,
+    command: Command,
+}
+impl TryFrom<std::env::Args> for Parameters {
+    type Error = String;
+
+    fn try_from(mut args: std::env::Args) -> Result<Self, Self::Error> {
+        fn parse_arg(args: &mut std::env::Args, token: String) -> Option<(String, String)> {
+            match token.split('=').collect::<Vec<_>>() {
+                subtokens if subtokens.len() == 2 => {
+                    Some((subtokens[0].into(), subtokens[1].into()))
+                }
+                _ => Some((token, args.next()?)),
+            }
+        }
+        fn map_arg(
+            map: &HashMap<String, String>,
+            short: &str,
+            long: &str,
+            default: Result<String, String>,
+        ) -> Result<String, String> {
+            if map.contains_key(short) {
+                Ok(map[short].clone())
+            } else if map.contains_key(long) {
+                Ok(map[long].clone())
+            } else {
+                default
+            }
+        }
+
+        args.next(); // skip first arg, bin location
+        let command = args.next().ok_or("command missing")?;
+
+        let mut map: HashMap<String, String> = HashMap::new();
+        while let Some(arg) = args.next() {
+            let token = {
+                if let Some(t) = arg.strip_prefix("--") {
+                    String::from(t)
+                } else if let Some(t) = arg.strip_prefix("-") {
+                    String::from(t)
+                } else {
+                    arg
+                }
+            };
+
+            if let Some((key, value)) = parse_arg(&mut args, token) {
+                map.insert(key, value);
+            }
+        }
+
+        let file = map_arg(&map, "f", "file", Err("--file param missing".into()))?.into();
+        let command = match command.as_str() {
+            "add" => {
+                let source =
+                    map_arg(&map, "s", "source", Err("--source param missing".into()))?.into();
+                let lib_paths = map_arg(&map, "l", "lib-path", Ok(String::new()))?;
+                let lib_paths = std::env::split_paths(&lib_paths)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .collect();
+                Command::Add { source, lib_paths }
+            }
+            "list" => Command::List,
+            "resolve" => {
+                let name = map_arg(&map, "n", "name", Err("--name param missing".into()))?;
+                Command::Resolve { name }
+            }
+            command => return Err(format!("unknown command '{}'", command)),
+        };
+        Ok::<Parameters, String>(Parameters { file, command })
+    }
+}
+
+fn main() -> ExitCode {
+    let parameters = match std::env::args().try_into() {
+        Ok(parameters) => parameters,
+        Err(e) => {
+            println!("{}", HELP);
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(parameters) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(Parameters { file, command }: Parameters) -> Result<(), String> {
+    match command {
+        Command::Add { source, lib_paths } => {
+            let mut library = match file.exists() {
+                true => Library::load(&file)?,
+                false => Library::new(),
+            };
+            let assembly_text = match read_to_string(&source) {
+                Ok(asm) => asm,
+                Err(e) => return Err(format!("Couldn't read source: {}", e)),
+            };
+            let mut diagnostics = Diagnostics::default();
+            let module = parse_module(&assembly_text, &mut diagnostics);
+            let rendered = diagnostics.render(&assembly_text, &source.display().to_string());
+            let Some(module) = module else {
+                return Err(rendered);
+            };
+            if !diagnostics.is_empty() {
+                eprint!("{}", rendered);
+            }
+            let module = Resolver::with_env(lib_paths).resolve(module)?;
+            for (name, hash) in library.add_module(module)? {
+                println!("{}  {}", hex(&hash), name);
+            }
+            library.save(&file)
+        }
+        Command::List => {
+            let library = Library::load(&file)?;
+            let mut names: Vec<_> = library.routine_names.iter().collect();
+            names.sort();
+            for (name, hash) in names {
+                println!("{}  {}", hex(hash), name);
+            }
+            let mut macros: Vec<_> = library.macros.iter().collect();
+            macros.sort();
+            for (name, bytes) in macros {
+                println!("macro ~{} ({} bytes)", name, bytes.len());
+            }
+            Ok(())
+        }
+        Command::Resolve { name } => {
+            let library = Library::load(&file)?;
+            match library.resolve(&name) {
+                Some(hash) => {
+                    println!("{}", hex(&hash));
+                    Ok(())
+                }
+                None => Err(format!("No routine named `{}` in the library", name)),
+            }
+        }
+    }
+}
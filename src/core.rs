@@ -1,30 +1,83 @@
+mod blocks;
 pub mod device;
+mod fault;
+mod init;
 mod instruction;
+mod journal;
 mod len;
+mod linker;
 mod register;
+mod shadow;
 mod stack;
+mod widths;
 
+pub use fault::{Access, Fault};
+pub use init::{InitCheck, Uninitialized};
 pub use instruction::Ins as Instruction;
 pub use instruction::{opcode_to_str, str_to_opcode};
+pub use journal::Journal;
+pub use linker::{Hash, Link, Linker};
+pub use shadow::ShadowStack;
+pub use widths::{Mismatch, WidthCheck};
 
+use blocks::BlockCache;
 use instruction::Ins;
 use register::Register64;
-use stack::Stack;
+pub use stack::Stack;
+use stack::STACK_SIZE;
 
 // use self::instruction::LenF;
 
 const STACK_FALSE: u8 = 0x00;
 const DMA_COUNT: usize = 4;
 const DEVICE_COUNT: usize = 16;
+const REGION_COUNT: usize = 8;
+const STACK_COUNT: usize = 3;
+
+// memory-backed stacks are kept between these depths, so no one instruction
+// can run off either end of a stack that still has room in memory
+const SPILL_LOW: usize = 32;
+const SPILL_HIGH: usize = 224;
+const SPILL_CHUNK: usize = 128;
+
+/*
+    A context block, as saved and loaded by CXS, CXL and CXW:
+
+      0 -- program counter (16)
+      2 -- hold register (64)
+     10 -- data, swap and return stack lengths (16 each)
+     16 -- data stack (256 bytes)
+    272 -- swap stack (256 bytes)
+    528 -- return stack (256 bytes)
+    784 -- catch depth (32)
+
+    A new context only needs its program counter and zeroed lengths.
+    Stack backings aren't part of a context; a scheduler that gives each
+    thread its own stack memory sets them itself. Since a block only holds
+    the resident part of each stack, saving faults while any stack has
+    bytes spilled to memory, and loading drops whatever was spilled.
+*/
+const CONTEXT_SIZE: usize = 788;
+const CONTEXT_CATCH: usize = 784;
+
+/*
+    CTH pushes a catch frame onto the return stack: the previous catch
+    depth (32), the data and swap stack depths (32 each), and the handler
+    address (16). `catch_depth` then holds the return stack depth just
+    above the frame. THR unwinds all three stacks to the innermost frame,
+    pops it, pushes its error code and jumps to the handler; CTE pops the
+    frame when its protected code finishes without throwing.
+*/
+const CONTEXT_STACKS: usize = 16;
 
 trait Push {
-    fn push(&mut self, bytes: &[u8]);
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Fault>;
 }
 trait Pop {
-    fn pop(&self, len: usize) -> &[u8];
+    fn pop(&self, len: usize) -> Result<&[u8], Fault>;
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct DeviceSlot {
     pub status_reg: u8,
     pub vector: u16,
@@ -47,8 +100,13 @@ impl DeviceSlot {
         }
     }
 }
+impl Default for DeviceSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub struct DMA {
     pub status_reg: u8,
     pub address: u32,
@@ -67,10 +125,94 @@ impl DMA {
         }
     }
 }
+impl Default for DMA {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/*
+    A span of memory, from `start` through `last`, with access flags:
+
+    SUPERVISOR  -- user mode can't read, write or execute it
+    READ_ONLY   -- nothing can write to it, not even devices
+    NO_EXECUTE  -- instructions can't be fetched from it
+    DEVICE_ONLY -- only devices and DMA can write to it
+
+    Flags hold in both modes, except SUPERVISOR. Writes from the host
+    through `CPU::write_memory` are never checked.
+*/
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub flags: u8,
+    pub start: u16,
+    pub last: u16,
+}
+impl Region {
+    pub const SUPERVISOR_FLAG: u8 = 0b1000_0000;
+    pub const READ_ONLY_FLAG: u8 = 0b0100_0000;
+    pub const NO_EXECUTE_FLAG: u8 = 0b0010_0000;
+    pub const DEVICE_ONLY_FLAG: u8 = 0b0001_0000;
+
+    pub fn new() -> Region {
+        Region {
+            flags: 0,
+            start: 0,
+            last: 0,
+        }
+    }
+
+    fn overlaps(&self, range: &std::ops::Range<usize>) -> bool {
+        range.start <= self.last as usize && range.end > self.start as usize
+    }
+    fn forbids(&self, access: Access, supervisor: bool) -> bool {
+        let denied = match access {
+            Access::Read => 0,
+            Access::Write => Region::READ_ONLY_FLAG | Region::DEVICE_ONLY_FLAG,
+            Access::Execute => Region::NO_EXECUTE_FLAG,
+            Access::Device => Region::READ_ONLY_FLAG,
+        };
+        let denied = match supervisor {
+            true => denied,
+            false => denied | Region::SUPERVISOR_FLAG,
+        };
+        self.flags & denied != 0
+    }
+}
+impl Default for Region {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+/*
+    The memory behind a stack: its oldest bytes live in `base..top`, and
+    may grow up to `limit`, while the newest stay on the stack itself.
+*/
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Backing {
+    pub base: u16,
+    pub top: u16,
+    pub limit: u16,
+}
+
+/*
+    The CPU starts in supervisor mode with interrupts enabled. Dropping to
+    user mode (SRT) restricts device, DMA and interrupt control instructions,
+    and any memory inside a region flagged supervisor-only.
+
+    A SYSCALL, or an interrupt taken in user mode, traps: the return address
+    is pushed, the CPU enters supervisor mode with interrupts disabled, and
+    jumps to its vector. SRT pops a return address and resumes user mode with
+    interrupts enabled, which is also how the supervisor starts a program.
+    Interrupts taken in supervisor mode work as before, returning with RTN.
+*/
 pub struct CPU {
     pub program_counter: u16,
     pub memory_address: u64,
+    pub status_reg: u8,
+    pub syscall_vector: u16,
+    pub catch_depth: u32,
 
     pub hold_reg: Register64,
     pub data_st: Stack,
@@ -82,12 +224,36 @@ pub struct CPU {
     pub slot_mask: u16,
     pub devices: [DeviceSlot; DEVICE_COUNT],
     pub dma_controllers: [DMA; DMA_COUNT],
+
+    pub journal: Option<Journal>,
+    pub shadow: Option<ShadowStack>,
+    pub widths: Option<WidthCheck>,
+    pub init: Option<InitCheck>,
+    pub linker: Option<Linker>,
+
+    regions: [Region; REGION_COUNT],
+    region_mask: u8,
+    backings: [Option<Backing>; STACK_COUNT],
+    device_events: u16,
+    dma_events: u8,
+    blocks: BlockCache,
+}
+impl Default for CPU {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl CPU {
+    pub const SUPERVISOR_FLAG: u8 = 0b1000_0000;
+    pub const INTERRUPT_FLAG: u8 = 0b0100_0000;
+
     pub fn new() -> CPU {
         CPU {
             program_counter: 0,
             memory_address: 0,
+            status_reg: CPU::SUPERVISOR_FLAG | CPU::INTERRUPT_FLAG,
+            syscall_vector: 0,
+            catch_depth: 0,
 
             hold_reg: Register64::new(),
             data_st: Stack::new(),
@@ -99,6 +265,20 @@ impl CPU {
 
             slot_mask: 0,
             devices: [DeviceSlot::new(); DEVICE_COUNT],
+
+            journal: None,
+            shadow: None,
+            widths: None,
+            init: None,
+            linker: None,
+
+            regions: [Region::new(); REGION_COUNT],
+            region_mask: 0,
+            backings: [None; STACK_COUNT],
+
+            device_events: 0,
+            dma_events: 0,
+            blocks: BlockCache::new(),
         }
     }
 
@@ -108,56 +288,267 @@ impl CPU {
             panic!("trying to lod rom too big for memory")
         }
         self.memory[0..rom_len].copy_from_slice(&rom[..]);
+        self.blocks.clear();
+        if let Some(init) = &mut self.init {
+            init.mark(0..rom_len);
+        }
+    }
+
+    // writes from the host, which keep the journal and block cache in step
+    pub fn write_memory(&mut self, address: usize, bytes: &[u8]) -> Result<(), Fault> {
+        let end = address.saturating_add(bytes.len());
+        if end > self.memory.len() {
+            return Err(Fault::MemoryOverflow {
+                address: end as u64,
+            });
+        }
+        self.before_write(address..end);
+        self.memory[address..end].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    // a device filling the buffer of a DMA write request, which is only
+    // initialized once the bytes are actually there
+    pub fn fill_dma(&mut self, index: u8, bytes: &[u8]) -> Result<(), Fault> {
+        let dma = self.get_dma(index)?;
+        let (address, length) = (dma.address as usize, dma.buffer_len as usize);
+        if bytes.len() > length {
+            return Err(Fault::MemoryOverflow {
+                address: (address + length) as u64,
+            });
+        }
+        self.write_memory(address, bytes)?;
+        self.mark_initialized(address..address + bytes.len());
+        self.dma_controllers[index as usize].payload_len = bytes.len() as u32;
+        Ok(())
+    }
+
+    // device slots and DMA controllers the CPU has written to since last asked,
+    // one bit per index
+    pub fn take_device_events(&mut self) -> u16 {
+        std::mem::take(&mut self.device_events)
     }
-    pub fn connect_device(&mut self, identifier: [u8; 32]) {
+    pub fn take_dma_events(&mut self) -> u8 {
+        std::mem::take(&mut self.dma_events)
+    }
+    pub fn connect_device(&mut self, identifier: [u8; 32]) -> Option<usize> {
         fn get_free_slot(mask: u16) -> Option<usize> {
-            for i in 0..DEVICE_COUNT {
-                if 1 << i & mask == 0 {
-                    return Some(i);
-                }
-            }
-            None
+            (0..DEVICE_COUNT).find(|i| 1 << i & mask == 0)
         }
 
         let Some(slot) = get_free_slot(self.slot_mask) else {
             eprintln!("device list full");
-            return;
+            return None;
         };
 
         let device = &mut self.devices[slot];
         device.identifier = identifier;
+        self.slot_mask |= 1 << slot;
+        Some(slot)
+    }
+    pub fn disconnect_device(&mut self, slot: usize) {
+        self.devices[slot] = DeviceSlot::new();
+        self.slot_mask &= !(1 << slot);
+    }
+
+    // bytes on the data, swap or return stack, counting any spilled to memory
+    pub fn data_depth(&self) -> usize {
+        self.stack_depth(0)
+    }
+    pub fn swap_depth(&self) -> usize {
+        self.stack_depth(1)
+    }
+    pub fn return_depth(&self) -> usize {
+        self.stack_depth(2)
+    }
+    // moves the top of the data (0), swap (1) or return (2) stack to `depth`,
+    // dropping into spilled bytes or growing the resident part as needed
+    pub fn set_stack_depth(&mut self, stack: u8, depth: usize) -> Result<(), Fault> {
+        let index = stack as usize;
+        if index >= STACK_COUNT {
+            return Err(Fault::InvalidStack { index: stack });
+        }
+        if depth <= self.stack_depth(index) {
+            return self.unwind_stack(index, depth);
+        }
+        let spilled = self.stack_depth(index) - self.stack_len(index);
+        match index {
+            0 => self.data_st.set_len(depth - spilled)?,
+            1 => self.swap_st.set_len(depth - spilled)?,
+            _ => self.return_st.set_len(depth - spilled)?,
+        }
+        self.balance_stack(index);
+        Ok(())
+    }
+    // the memory behind the data (0), swap (1) or return (2) stack
+    pub fn stack_backing(&self, stack: u8) -> Option<Backing> {
+        self.backings.get(stack as usize).copied().flatten()
+    }
+    // backs a stack with `base..limit`, or with nothing if the range is
+    // empty; bytes already spilled are first pulled back onto the stack
+    pub fn back_stack(&mut self, stack: u8, base: u16, limit: u16) -> Result<(), Fault> {
+        let index = stack as usize;
+        if index >= STACK_COUNT {
+            return Err(Fault::InvalidStack { index: stack });
+        }
+        if let Some(backing) = self.backings[index] {
+            let spilled = &self.memory[backing.base as usize..backing.top as usize];
+            match index {
+                0 => self.data_st.insert_bottom(spilled)?,
+                1 => self.swap_st.insert_bottom(spilled)?,
+                _ => self.return_st.insert_bottom(spilled)?,
+            }
+        }
+        self.backings[index] = match base < limit {
+            true => Some(Backing {
+                base,
+                top: base,
+                limit,
+            }),
+            false => None,
+        };
+        Ok(())
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+    pub fn set_region(&mut self, index: u8, region: Region) -> Result<(), Fault> {
+        let slot = self
+            .regions
+            .get_mut(index as usize)
+            .ok_or(Fault::InvalidRegion { index })?;
+        *slot = region;
+        match region.flags {
+            0 => self.region_mask &= !(1 << index),
+            _ => self.region_mask |= 1 << index,
+        }
+        Ok(())
+    }
+
+    pub fn is_supervisor(&self) -> bool {
+        self.status_reg & CPU::SUPERVISOR_FLAG != 0
+    }
+    pub fn interrupts_enabled(&self) -> bool {
+        self.status_reg & CPU::INTERRUPT_FLAG != 0
+    }
+
+    // a handler starts with interrupts off, even in supervisor mode, so the
+    // next device buffer can't land on the one it's reading; it turns them
+    // back on with INE once it's done
+    pub fn interrupt(&mut self, address: u16) -> Result<(), Fault> {
+        self.trap(self.program_counter, address)?;
+        self.status_reg &= !CPU::INTERRUPT_FLAG;
+        Ok(())
+    }
+
+    pub fn execute(&mut self) -> Result<(), Fault> {
+        if let Some(mut journal) = self.journal.take() {
+            journal.begin(self);
+            self.journal = Some(journal);
+        }
+        match self.widths.is_some() {
+            true => self.execute_checked(),
+            false => self.execute_instruction(),
+        }
+    }
+
+    // runs straight-line code up to and including the next branch or I/O
+    // instruction, returning how many instructions ran; on a fault, how many
+    // ran before the one that faulted
+    pub fn execute_block(&mut self) -> Result<usize, (usize, Fault)> {
+        let len = self.blocks.block_len(&self.memory, self.program_counter) as usize;
+        self.blocks.invalidated = false;
+        for executed in 1..=len {
+            self.execute().map_err(|fault| (executed - 1, fault))?;
+            if self.blocks.invalidated {
+                return Ok(executed); // the rest of the block may have changed
+            }
+        }
+        Ok(len)
+    }
+
+    // undoes the last journaled step, returning false once history runs out
+    pub fn step_back(&mut self) -> bool {
+        let Some(mut journal) = self.journal.take() else {
+            return false;
+        };
+        let undone = journal.undo(self);
+        self.journal = Some(journal);
+        if let Some(shadow) = &mut self.shadow {
+            shadow.clear(); // frames no longer line up with the return stack
+        }
+        if let Some(widths) = &mut self.widths {
+            widths.clear();
+        }
+        undone
     }
 
-    pub fn interrupt(&mut self, address: u16) {
-        self.return_st.push(&self.program_counter.to_le_bytes());
-        self.program_counter = le_slice_to_u16(&address.to_le_bytes());
+    // runs one instruction with the widths of its stack operands checked
+    fn execute_checked(&mut self) -> Result<(), Fault> {
+        let Some(mut widths) = self.widths.take() else {
+            return self.execute_instruction();
+        };
+        let address = self.program_counter;
+        let instruction = Ins::from(self.memory[address as usize]);
+        let result = match widths.before(&instruction, address) {
+            Some(mismatch) if widths.fault => Err(Fault::WidthMismatch(mismatch)),
+            _ => self.execute_instruction(),
+        };
+        let depths = [0, 1, 2].map(|index| self.stack_depth(index));
+        match result {
+            Ok(()) => widths.after(&instruction, address, depths),
+            Err(_) => widths.trim(depths[0], depths),
+        }
+        self.widths = Some(widths);
+        result
     }
 
-    pub fn execute(&mut self) {
+    fn execute_instruction(&mut self) -> Result<(), Fault> {
+        if self.backings.iter().any(Option::is_some) {
+            self.balance_stacks();
+        }
         let byte = self.memory[self.program_counter as usize];
         let instruction = Ins::from(byte);
+        if !self.is_supervisor() && is_privileged(&instruction) {
+            return Err(Fault::PrivilegedInstruction { opcode: byte });
+        }
+        let pc = self.program_counter as usize;
+        self.check_access(pc..pc + 1, Access::Execute)?;
         match instruction {
             Ins::NoOperation => {}
 
             // stack movement
-            Ins::DuplicateData { len } => self.data_st.duplicate(len as usize),
-            Ins::CopyDataToSwap { len } => self.swap_st.push(self.data_st.pop(len as usize)),
-            Ins::CopyDataToReturn { len } => self.return_st.push(self.data_st.pop(len as usize)),
-            Ins::CopyDataToHold { len } => self.hold_reg.push(self.data_st.pop(len as usize)),
-            Ins::CopySwapToData { len } => self.data_st.push(self.swap_st.pop(len as usize)),
-            Ins::DuplicateSwap { len } => self.swap_st.duplicate(len as usize),
-            Ins::CopySwapToReturn { len } => self.return_st.push(self.swap_st.pop(len as usize)),
-            Ins::CopySwapToHold { len } => self.hold_reg.push(self.swap_st.pop(len as usize)),
-            Ins::CopyReturnToData { len } => self.data_st.push(self.return_st.pop(len as usize)),
-            Ins::CopyReturnToSwap { len } => self.swap_st.push(self.return_st.pop(len as usize)),
-            Ins::DuplicateReturn { len } => self.return_st.duplicate(len as usize),
-            Ins::CopyReturnToHold { len } => self.hold_reg.push(self.return_st.pop(len as usize)),
-            Ins::CopyHoldToData { len } => self.data_st.push(self.hold_reg.pop(len as usize)),
-            Ins::CopyHoldToSwap { len } => self.swap_st.push(self.hold_reg.pop(len as usize)),
-            Ins::CopyHoldToReturn { len } => self.return_st.push(self.hold_reg.pop(len as usize)),
-            Ins::DropData => self.data_st.drop(1),
-            Ins::DropSwap => self.swap_st.drop(1),
-            Ins::DropReturn => self.return_st.drop(1),
+            Ins::DuplicateData { len } => self.data_st.duplicate(len as usize)?,
+            Ins::CopyDataToSwap { len } => self.swap_st.push(self.data_st.pop(len as usize)?)?,
+            Ins::CopyDataToReturn { len } => {
+                self.return_st.push(self.data_st.pop(len as usize)?)?
+            }
+            Ins::CopyDataToHold { len } => self.hold_reg.push(self.data_st.pop(len as usize)?)?,
+            Ins::CopySwapToData { len } => self.data_st.push(self.swap_st.pop(len as usize)?)?,
+            Ins::DuplicateSwap { len } => self.swap_st.duplicate(len as usize)?,
+            Ins::CopySwapToReturn { len } => {
+                self.return_st.push(self.swap_st.pop(len as usize)?)?
+            }
+            Ins::CopySwapToHold { len } => self.hold_reg.push(self.swap_st.pop(len as usize)?)?,
+            Ins::CopyReturnToData { len } => {
+                self.data_st.push(self.return_st.pop(len as usize)?)?
+            }
+            Ins::CopyReturnToSwap { len } => {
+                self.swap_st.push(self.return_st.pop(len as usize)?)?
+            }
+            Ins::DuplicateReturn { len } => self.return_st.duplicate(len as usize)?,
+            Ins::CopyReturnToHold { len } => {
+                self.hold_reg.push(self.return_st.pop(len as usize)?)?
+            }
+            Ins::CopyHoldToData { len } => self.data_st.push(self.hold_reg.pop(len as usize)?)?,
+            Ins::CopyHoldToSwap { len } => self.swap_st.push(self.hold_reg.pop(len as usize)?)?,
+            Ins::CopyHoldToReturn { len } => {
+                self.return_st.push(self.hold_reg.pop(len as usize)?)?
+            }
+            Ins::DropData => self.data_st.drop(1)?,
+            Ins::DropSwap => self.swap_st.drop(1)?,
+            Ins::DropReturn => self.return_st.drop(1)?,
 
             // branching
             Ins::Jump {
@@ -165,204 +556,361 @@ impl CPU {
                 con: conditional,
                 rel: relative,
             } => {
-                let address = self.pop_operand16(len as usize);
+                let address = self.pop_operand16(len as usize)?;
 
                 if conditional {
-                    let condition = le_slice_to_u8(self.data_st.pop(1));
-                    self.data_st.drop(1);
+                    let condition = self.pop_operand8()?;
                     if condition == STACK_FALSE {
-                        return; // don't execute the jump
+                        self.program_counter = self.program_counter.wrapping_add(1);
+                        return Ok(()); // don't execute the jump
                     };
                 }
 
-                self.program_counter = match relative {
-                    true => self.program_counter + address,
-                    false => address,
+                self.program_counter = match (relative, len as usize) {
+                    // 8 bit offsets are signed, so loops can jump back
+                    (true, 1) => self
+                        .program_counter
+                        .wrapping_add(address as u8 as i8 as u16),
+                    (true, _) => self.program_counter.wrapping_add(address),
+                    (false, _) => address,
                 };
 
-                return; // avoid default PC increment
+                return Ok(()); // avoid default PC increment
             }
             Ins::Call { len } => {
-                self.return_st.push(&self.program_counter.to_le_bytes());
-                self.program_counter = self.pop_operand16(len as usize);
-                return; // avoid default PC increment
+                let address = self.pop_operand16(len as usize)?;
+                let return_address = self.program_counter.wrapping_add(1);
+                self.return_st.push(&return_address.to_le_bytes())?;
+                self.shadow_push(return_address);
+                self.program_counter = address;
+                return Ok(()); // avoid default PC increment
             }
             Ins::Return { len } => {
-                self.program_counter = le_slice_to_u16(self.return_st.pop(len as usize));
-                self.return_st.drop(len as usize);
-                return; // avoid default PC increment
+                let target = le_slice_to_u16(self.return_st.pop(len as usize)?);
+                self.shadow_pop(target)?;
+                self.program_counter = target;
+                self.return_st.drop(len as usize)?;
+                return Ok(()); // avoid default PC increment
+            }
+
+            // system
+            Ins::SystemCall => {
+                let return_address = self.program_counter.wrapping_add(1);
+                return self.trap(return_address, self.syscall_vector);
+            }
+            Ins::SystemReturn => {
+                let target = le_slice_to_u16(self.return_st.pop(2)?);
+                self.shadow_pop(target)?;
+                self.program_counter = target;
+                self.return_st.drop(2)?;
+                self.status_reg = CPU::INTERRUPT_FLAG;
+                return Ok(()); // avoid default PC increment
+            }
+            Ins::InterruptEnable => self.status_reg |= CPU::INTERRUPT_FLAG,
+            Ins::InterruptDisable => self.status_reg &= !CPU::INTERRUPT_FLAG,
+            Ins::SetSystemVector => {
+                // data ( address16 -- )
+                self.syscall_vector = self.pop_operand16(2)?;
+            }
+            Ins::SetDeviceVector => {
+                // data ( index8, address16 -- )
+                let index = self.pop_operand8()?;
+                let address = self.pop_operand16(2)?;
+                self.get_device(index)?;
+                self.devices[index as usize].vector = address;
+            }
+            Ins::SetRegion => {
+                // data ( index8, flags8, start16, last16 -- )
+                let (index, flags) = self.pop_operands8()?;
+                let (start, last) = (self.pop_operand16(2)?, self.pop_operand16(2)?);
+                self.set_region(index, Region { flags, start, last })?;
+            }
+            Ins::SetStack => {
+                // data ( stack8, base16, limit16 -- )
+                let stack = self.pop_operand8()?;
+                let (base, limit) = (self.pop_operand16(2)?, self.pop_operand16(2)?);
+                self.back_stack(stack, base, limit)?;
+            }
+            Ins::ReadStack => {
+                // data ( stack8 -- base16, limit16, top16 )
+                let stack = self.pop_operand8()?;
+                if stack as usize >= STACK_COUNT {
+                    return Err(Fault::InvalidStack { index: stack });
+                }
+                let backing = self.stack_backing(stack).unwrap_or(Backing {
+                    base: 0,
+                    top: 0,
+                    limit: 0,
+                });
+                self.data_st.push_value(2, backing.base as u64)?;
+                self.data_st.push_value(2, backing.limit as u64)?;
+                self.data_st.push_value(2, backing.top as u64)?;
+            }
+            Ins::SaveContext => {
+                // data ( address16 -- ) | memory { [address] => context }
+                let address = self.pop_operand16(2)?;
+                self.save_context(address, self.program_counter.wrapping_add(1))?;
+            }
+            Ins::LoadContext => {
+                // data ( address16 -- ) | context { [address] => cpu }
+                let address = self.pop_operand16(2)?;
+                self.load_context(address)?;
+                return Ok(()); // avoid default PC increment
+            }
+            Ins::SwitchContext => {
+                // data ( save16, load16 -- )
+                let save = self.pop_operand16(2)?;
+                let load = self.pop_operand16(2)?;
+                self.save_context(save, self.program_counter.wrapping_add(1))?;
+                self.load_context(load)?;
+                return Ok(()); // avoid default PC increment
+            }
+
+            Ins::Catch => {
+                // data ( handler16 -- ) | return ( -- frame )
+                let handler = self.pop_operand16(2)?;
+                let (data, swap) = (self.stack_depth(0), self.stack_depth(1));
+                self.return_st.push_value(4, self.catch_depth as u64)?;
+                self.return_st.push_value(4, data as u64)?;
+                self.return_st.push_value(4, swap as u64)?;
+                self.return_st.push_value(2, handler as u64)?;
+                self.catch_depth = self.stack_depth(2) as u32;
+            }
+            Ins::Throw => {
+                // data ( code8 -- code8 ) at the handler of the innermost catch
+                let code = self.pop_operand8()?;
+                if self.catch_depth == 0 {
+                    return Err(Fault::UncaughtThrow { code });
+                }
+                self.unwind_stack(2, self.catch_depth as usize)?;
+                let handler = self.return_st.pop_value(2)? as u16;
+                let swap = self.return_st.pop_value(4)? as usize;
+                let data = self.return_st.pop_value(4)? as usize;
+                self.catch_depth = self.return_st.pop_value(4)? as u32;
+                self.unwind_stack(1, swap)?;
+                self.unwind_stack(0, data)?;
+                self.push_result8(code)?;
+                self.program_counter = handler;
+                return Ok(()); // avoid default PC increment
+            }
+            Ins::EndCatch => {
+                // return ( frame -- )
+                if self.catch_depth == 0 || self.stack_depth(2) != self.catch_depth as usize {
+                    return Err(Fault::CatchMismatch);
+                }
+                self.return_st.pop_value(2)?;
+                self.return_st.pop_value(4)?;
+                self.return_st.pop_value(4)?;
+                self.catch_depth = self.return_st.pop_value(4)? as u32;
+            }
+
+            Ins::CallHash => {
+                // calls the routine whose hash follows, loading it first if needed
+                let hash = self.read_hash()?;
+                let address = self.link(hash)?;
+                let return_address = self.program_counter.wrapping_add(1 + 32);
+                self.return_st.push(&return_address.to_le_bytes())?;
+                self.shadow_push(return_address);
+                self.program_counter = address;
+                return Ok(()); // avoid default PC increment
+            }
+            Ins::AddressHash => {
+                // data ( -- address16 ) of the routine whose hash follows
+                let hash = self.read_hash()?;
+                let address = self.link(hash)?;
+                self.data_st.push_value(2, address as u64)?;
+                self.program_counter = self.program_counter.wrapping_add(32);
             }
 
             // accessing memory
             Ins::Literal { len } => {
-                let lit_range = self.get_lit_range(len as usize);
-                self.data_st.push(&self.memory[lit_range]);
-                self.program_counter += len as u16; // skip consumed literal
+                if self.init.is_some() {
+                    self.check_init(self.get_lit_range(len as usize)?)?;
+                }
+                let literal = self.read_literal(len as usize)?;
+                self.data_st.push_value(len as usize, literal)?;
+                self.program_counter = self.program_counter.wrapping_add(len as u16);
+                // skip consumed literal
             }
             Ins::Address { len } => {
-                self.memory_address = self.pop_operand64(len as usize);
-                if self.memory_address as usize > self.memory.len() {
-                    panic!("Memory Overflow")
+                let address = self.pop_operand64(len as usize)?;
+                if address as usize > self.memory.len() {
+                    return Err(Fault::MemoryOverflow { address });
                 }
+                self.memory_address = address;
             }
             Ins::Store { len } => {
-                let start = self.memory_address as usize;
-                let end = self.memory_address as usize + len as usize;
-                if end > self.memory.len() {
-                    panic!("Memory Overflow")
-                }
-                let data = self.data_st.pop(len as usize);
-                self.memory[start..end].copy_from_slice(data);
+                let range = self.get_memory_range(len as usize)?;
+                self.check_access(range.clone(), Access::Write)?;
+                self.before_write(range.clone());
+                let data = self.data_st.pop(len as usize)?;
+                self.memory[range].copy_from_slice(data);
             }
             Ins::Load { len } => {
-                let range =
-                    self.memory_address as usize..self.memory_address as usize + len as usize;
-                let data = &self.memory[range];
-                self.data_st.push(data);
+                let range = self.get_memory_range(len as usize)?;
+                self.check_access(range.clone(), Access::Read)?;
+                self.check_init(range.clone())?;
+                self.data_st.push(&self.memory[range])?;
             }
 
             // working with DMA
             Ins::DMARead => {
-                let index = self.pop_operand8();
-                let dma = &self.dma_controllers[index as usize];
+                let index = self.pop_operand8()?;
+                let dma = self.get_dma(index)?;
                 let length = dma.buffer_len.to_le_bytes();
                 let address = dma.address.to_le_bytes();
-                self.data_st.push(&length);
-                self.data_st.push(&address);
+                self.data_st.push(&length)?;
+                self.data_st.push(&address)?;
             }
             Ins::DMAWrite { len } => {
-                let (index, flag) = self.pop_operands8();
-                let (address, length) = self.pop_operands32(len as usize);
+                let (index, flag) = self.pop_operands8()?;
+                let (address, length) = self.pop_operands32(len as usize)?;
+                self.get_dma(index)?;
+                let end = (address as usize).saturating_add(length as usize);
+                self.check_access(address as usize..end, Access::Device)?;
                 let dma = &mut self.dma_controllers[index as usize];
                 dma.status_reg |= flag;
+                self.dma_events |= 1 << index;
                 dma.address = address;
                 dma.buffer_len = length;
             }
             Ins::DMAPoll => {
-                let (index, flag) = self.pop_operands8();
-                let dma = &self.dma_controllers[index as usize];
+                let (index, flag) = self.pop_operands8()?;
+                let dma = self.get_dma(index)?;
                 let flag_set = (dma.status_reg & flag) != 0;
-                self.push_result_bool(flag_set);
+                self.push_result_bool(flag_set)?;
             }
 
             // working with devices
             Ins::DeviceRead { len } => {
                 // data ( index8, offset8 -- value )
-                let (index, offset) = self.pop_operands8();
-                let slot = &self.devices[index as usize];
-                let value = &slot.in_buffer[offset as usize..(offset) as usize + len as usize];
-                self.data_st.push(&value);
+                let (index, offset) = self.pop_operands8()?;
+                let range = get_buffer_range(offset, len as usize)?;
+                self.get_device(index)?;
+                let value = &self.devices[index as usize].in_buffer[range];
+                self.data_st.push(value)?;
             }
             Ins::DeviceWrite { len } => {
                 // data ( index8, flag8, offset8, valueLEN -- )
-                let (index, flag) = self.pop_operands8();
-                let offset = self.pop_operand8() as usize;
-                let value = self.data_st.pop(len as usize);
-                let range = offset..offset + len as usize;
+                let (index, flag) = self.pop_operands8()?;
+                let offset = self.pop_operand8()?;
+                let range = get_buffer_range(offset, len as usize)?;
+                self.get_device(index)?;
+                let value = self.data_st.pop(len as usize)?;
                 let slot = &mut self.devices[index as usize];
                 slot.status_reg |= flag;
                 slot.out_buffer[range].copy_from_slice(value);
+                self.device_events |= 1 << index;
             }
             Ins::DevicePoll { len } => {
                 // data ( index8, addressLEN -- ) | memory { [address] => device.identifier }
-                let index = self.pop_operand8();
-                let address = self.pop_operand32(len as usize);
-                let slot = &self.devices[index as usize];
-                let range = address as usize..address as usize + 32;
-                self.memory[range].copy_from_slice(&slot.identifier);
+                let index = self.pop_operand8()?;
+                let address = self.pop_operand32(len as usize)?;
+                let slot = self.get_device(index)?;
+                let identifier = slot.identifier;
+                let end = address as usize + identifier.len();
+                if end > self.memory.len() {
+                    return Err(Fault::MemoryOverflow {
+                        address: end as u64,
+                    });
+                }
+                self.check_access(address as usize..end, Access::Device)?;
+                self.before_write(address as usize..end);
+                self.memory[address as usize..end].copy_from_slice(&identifier);
             }
 
             // arithmetic
             Ins::Add { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs + rhs)
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs.wrapping_add(rhs))?;
             }
             Ins::Subtract { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs - rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs.wrapping_sub(rhs))?;
             }
             Ins::Multiply { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs * rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs.wrapping_mul(rhs))?;
             }
             Ins::Divide { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs / rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                let result = lhs.checked_div(rhs).ok_or(Fault::DivideByZero)?;
+                self.push_result64(len as usize, result)?;
             }
 
             // comparisons
             Ins::Greater { len } => {
                 // data ( lhsLEN, rhsLEN -- result8)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result_bool(lhs > rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result_bool(lhs > rhs)?;
             }
             Ins::Less { len } => {
                 // data ( lhsLEN, rhsLEN -- result8)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result_bool(lhs < rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result_bool(lhs < rhs)?;
             }
             Ins::Equal { len } => {
                 // data ( lhsLEN, rhsLEN -- result8)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result_bool(lhs == rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result_bool(lhs == rhs)?;
             }
             Ins::NotEqual { len } => {
                 // data ( lhsLEN, rhsLEN -- result8)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result_bool(lhs != rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result_bool(lhs != rhs)?;
             }
 
             // float arithmetic
             Ins::AddF { len } => match len {
                 len::LenF::L32 => {
-                    let (lhs, rhs) = self.pop_operands32(4);
+                    let (lhs, rhs) = self.pop_operands32(4)?;
                     let result = f32_from_u32(lhs) + f32_from_u32(rhs);
-                    self.push_result32(len as usize, u32_from_f32(result))
+                    self.push_result32(len as usize, u32_from_f32(result))?;
                 }
                 len::LenF::L64 => {
-                    let (lhs, rhs) = self.pop_operands64(8);
+                    let (lhs, rhs) = self.pop_operands64(8)?;
                     let result = f64_from_u64(lhs) + f64_from_u64(rhs);
-                    self.push_result64(len as usize, u64_from_f64(result))
+                    self.push_result64(len as usize, u64_from_f64(result))?;
                 }
             },
             Ins::SubtractF { len } => match len {
                 len::LenF::L32 => {
-                    let (lhs, rhs) = self.pop_operands32(4);
+                    let (lhs, rhs) = self.pop_operands32(4)?;
                     let result = f32_from_u32(lhs) - f32_from_u32(rhs);
-                    self.push_result32(len as usize, u32_from_f32(result))
+                    self.push_result32(len as usize, u32_from_f32(result))?;
                 }
                 len::LenF::L64 => {
-                    let (lhs, rhs) = self.pop_operands64(8);
+                    let (lhs, rhs) = self.pop_operands64(8)?;
                     let result = f64_from_u64(lhs) - f64_from_u64(rhs);
-                    self.push_result64(len as usize, u64_from_f64(result))
+                    self.push_result64(len as usize, u64_from_f64(result))?;
                 }
             },
             Ins::MultiplyF { len } => match len {
                 len::LenF::L32 => {
-                    let (lhs, rhs) = self.pop_operands32(4);
+                    let (lhs, rhs) = self.pop_operands32(4)?;
                     let result = f32_from_u32(lhs) * f32_from_u32(rhs);
-                    self.push_result32(len as usize, u32_from_f32(result))
+                    self.push_result32(len as usize, u32_from_f32(result))?;
                 }
                 len::LenF::L64 => {
-                    let (lhs, rhs) = self.pop_operands64(8);
+                    let (lhs, rhs) = self.pop_operands64(8)?;
                     let result = f64_from_u64(lhs) * f64_from_u64(rhs);
-                    self.push_result64(len as usize, u64_from_f64(result))
+                    self.push_result64(len as usize, u64_from_f64(result))?;
                 }
             },
             Ins::DivideF { len } => match len {
                 len::LenF::L32 => {
-                    let (lhs, rhs) = self.pop_operands32(4);
+                    let (lhs, rhs) = self.pop_operands32(4)?;
                     let result = f32_from_u32(lhs) / f32_from_u32(rhs);
-                    self.push_result32(len as usize, u32_from_f32(result))
+                    self.push_result32(len as usize, u32_from_f32(result))?;
                 }
                 len::LenF::L64 => {
-                    let (lhs, rhs) = self.pop_operands64(8);
+                    let (lhs, rhs) = self.pop_operands64(8)?;
                     let result = f64_from_u64(lhs) / f64_from_u64(rhs);
-                    self.push_result64(len as usize, u64_from_f64(result))
+                    self.push_result64(len as usize, u64_from_f64(result))?;
                 }
             },
 
@@ -370,163 +918,425 @@ impl CPU {
             Ins::GreaterF { len } => {
                 let result = match len {
                     len::LenF::L32 => {
-                        let (lhs, rhs) = self.pop_operands32(4);
+                        let (lhs, rhs) = self.pop_operands32(4)?;
                         f32_from_u32(lhs) > f32_from_u32(rhs)
                     }
                     len::LenF::L64 => {
-                        let (lhs, rhs) = self.pop_operands64(8);
+                        let (lhs, rhs) = self.pop_operands64(8)?;
                         f64_from_u64(lhs) > f64_from_u64(rhs)
                     }
                 };
-                self.push_result_bool(result)
+                self.push_result_bool(result)?;
             }
             Ins::LessF { len } => {
                 let result = match len {
                     len::LenF::L32 => {
-                        let (lhs, rhs) = self.pop_operands32(4);
+                        let (lhs, rhs) = self.pop_operands32(4)?;
                         f32_from_u32(lhs) < f32_from_u32(rhs)
                     }
                     len::LenF::L64 => {
-                        let (lhs, rhs) = self.pop_operands64(8);
+                        let (lhs, rhs) = self.pop_operands64(8)?;
                         f64_from_u64(lhs) < f64_from_u64(rhs)
                     }
                 };
-                self.push_result_bool(result)
+                self.push_result_bool(result)?;
             }
 
             // bitwise logic
             Ins::And { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs & rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs & rhs)?;
             }
             Ins::Or { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs | rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs | rhs)?;
             }
             Ins::Xor { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (lhs, rhs) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, lhs ^ rhs);
+                let (lhs, rhs) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, lhs ^ rhs)?;
             }
             Ins::Not { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let operand = self.pop_operand64(len as usize);
-                self.push_result64(len as usize, !operand);
+                let operand = self.pop_operand64(len as usize)?;
+                self.push_result64(len as usize, !operand)?;
             }
             Ins::ShiftL { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (shift, operand) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, operand << shift);
+                let (shift, operand) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, shift_left(operand, shift))?;
             }
             Ins::ShiftR { len } => {
                 // data ( lhsLEN, rhsLEN -- resultLEN)
-                let (shift, operand) = self.pop_operands64(len as usize);
-                self.push_result64(len as usize, operand >> shift);
+                let (shift, operand) = self.pop_operands64(len as usize)?;
+                self.push_result64(len as usize, shift_right(operand, shift))?;
             }
         }
 
-        self.program_counter += 1;
+        self.program_counter = self.program_counter.wrapping_add(1);
+        Ok(())
     }
 
-    fn get_lit_range(&self, len: usize) -> std::ops::Range<usize> {
+    fn get_lit_range(&self, len: usize) -> Result<std::ops::Range<usize>, Fault> {
         let start = self.program_counter as usize + 1;
         let end = start + len;
-        start..end
+        if end > self.memory.len() {
+            return Err(Fault::MemoryOverflow {
+                address: end as u64,
+            });
+        }
+        Ok(start..end)
+    }
+    fn read_literal(&self, len: usize) -> Result<u64, Fault> {
+        let range = self.get_lit_range(len)?;
+        let start = range.start;
+        match self.memory.get(start..start + 8) {
+            Some(word) => {
+                let value = u64::from_le_bytes(word.try_into().unwrap());
+                Ok(value & (u64::MAX >> ((8 - len) * 8)))
+            }
+            None => Ok(le_slice_to_u64(&self.memory[range])),
+        }
+    }
+    fn read_hash(&mut self) -> Result<Hash, Fault> {
+        let range = self.get_lit_range(32)?;
+        self.check_init(range.clone())?;
+        let mut hash = [0; 32];
+        hash.copy_from_slice(&self.memory[range]);
+        Ok(hash)
+    }
+    // the address of a routine, loaded into the linker's window on first use
+    fn link(&mut self, hash: Hash) -> Result<u16, Fault> {
+        let Some(mut linker) = self.linker.take() else {
+            return Err(Fault::UnknownRoutine { hash });
+        };
+        let address = linker.load(hash, self);
+        self.linker = Some(linker);
+        address
+    }
+    fn get_memory_range(&self, len: usize) -> Result<std::ops::Range<usize>, Fault> {
+        let start = self.memory_address as usize;
+        let end = start + len;
+        if end > self.memory.len() {
+            return Err(Fault::MemoryOverflow {
+                address: end as u64,
+            });
+        }
+        Ok(start..end)
+    }
+    fn context_range(&self, address: u16) -> Result<std::ops::Range<usize>, Fault> {
+        let (start, end) = (address as usize, address as usize + CONTEXT_SIZE);
+        if end > self.memory.len() {
+            return Err(Fault::MemoryOverflow {
+                address: end as u64,
+            });
+        }
+        Ok(start..end)
+    }
+    fn save_context(&mut self, address: u16, resume: u16) -> Result<(), Fault> {
+        let range = self.context_range(address)?;
+        if let Some(index) = (0..STACK_COUNT).find(|&i| self.stack_depth(i) > self.stack_len(i)) {
+            return Err(Fault::SpilledContext { index: index as u8 });
+        }
+        if let Some(shadow) = &mut self.shadow {
+            shadow.save(address);
+        }
+        self.check_access(range.clone(), Access::Write)?;
+        self.before_write(range.clone());
+
+        let block = &mut self.memory[range];
+        block[0..2].copy_from_slice(&resume.to_le_bytes());
+        block[2..10].copy_from_slice(&self.hold_reg.get().to_le_bytes());
+        block[CONTEXT_CATCH..].copy_from_slice(&self.catch_depth.to_le_bytes());
+        let stacks = [&self.data_st, &self.swap_st, &self.return_st];
+        for (index, stack) in stacks.into_iter().enumerate() {
+            let bytes = stack.as_slice();
+            let len = 10 + index * 2;
+            block[len..len + 2].copy_from_slice(&(bytes.len() as u16).to_le_bytes());
+            let start = CONTEXT_STACKS + index * STACK_SIZE;
+            block[start..start + bytes.len()].copy_from_slice(bytes);
+        }
+        Ok(())
+    }
+    fn load_context(&mut self, address: u16) -> Result<(), Fault> {
+        let range = self.context_range(address)?;
+        if let Some(shadow) = &mut self.shadow {
+            shadow.load(address);
+        }
+        self.check_access(range.clone(), Access::Read)?;
+
+        let block = &self.memory[range];
+        let lens = [0, 1, 2].map(|index| le_slice_to_u16(&block[10 + index * 2..]) as usize);
+        if lens.iter().any(|len| *len > STACK_SIZE) {
+            return Err(Fault::StackOverflow);
+        }
+        let stacks = [&mut self.data_st, &mut self.swap_st, &mut self.return_st];
+        for (index, stack) in stacks.into_iter().enumerate() {
+            let start = CONTEXT_STACKS + index * STACK_SIZE;
+            stack.restore(lens[index], 0, &block[start..start + lens[index]]);
+        }
+        for backing in self.backings.iter_mut().flatten() {
+            backing.top = backing.base;
+        }
+        self.hold_reg.set(le_slice_to_u64(&block[2..10]));
+        self.catch_depth = le_slice_to_u64(&block[CONTEXT_CATCH..]) as u32;
+        self.program_counter = le_slice_to_u16(&block[0..2]);
+        Ok(())
     }
 
-    fn pop_operand8(&mut self) -> u8 {
-        let operand = le_slice_to_u8(self.data_st.pop(1));
-        self.data_st.drop(1);
-        operand
+    // spills the bottom of full memory-backed stacks, and refills nearly empty ones
+    fn balance_stacks(&mut self) {
+        for index in 0..STACK_COUNT {
+            self.balance_stack(index);
+        }
+    }
+    fn balance_stack(&mut self, index: usize) {
+        let Some(mut backing) = self.backings[index] else {
+            return;
+        };
+        let (top, len) = (backing.top as usize, self.stack_len(index));
+        if len > SPILL_HIGH {
+            let count = usize::min(SPILL_CHUNK, (backing.limit - backing.top) as usize);
+            self.before_write(top..top + count);
+            let stack = match index {
+                0 => &mut self.data_st,
+                1 => &mut self.swap_st,
+                _ => &mut self.return_st,
+            };
+            self.memory[top..top + count].copy_from_slice(stack.bottom(count));
+            stack.remove_bottom(count);
+            backing.top += count as u16;
+        } else if len < SPILL_LOW && backing.top > backing.base {
+            let count = usize::min(SPILL_CHUNK, (backing.top - backing.base) as usize);
+            let stack = match index {
+                0 => &mut self.data_st,
+                1 => &mut self.swap_st,
+                _ => &mut self.return_st,
+            };
+            // a stack under SPILL_LOW always has room for a chunk
+            let _ = stack.insert_bottom(&self.memory[top - count..top]);
+            backing.top -= count as u16;
+        }
+        self.backings[index] = Some(backing);
+    }
+    // bytes on a stack, counting any spilled to memory
+    fn stack_depth(&self, index: usize) -> usize {
+        let spilled = match self.backings[index] {
+            Some(backing) => (backing.top - backing.base) as usize,
+            None => 0,
+        };
+        spilled + self.stack_len(index)
+    }
+    // drops everything above `depth` from a stack
+    fn unwind_stack(&mut self, index: usize, depth: usize) -> Result<(), Fault> {
+        if depth > self.stack_depth(index) {
+            return Err(Fault::StackUnderflow); // popped below the catch frame
+        }
+        let spilled = self.stack_depth(index) - self.stack_len(index);
+        let len = match self.backings[index].as_mut() {
+            Some(backing) if depth < spilled => {
+                backing.top = backing.base + depth as u16;
+                0
+            }
+            _ => depth - spilled,
+        };
+        match index {
+            0 => self.data_st.set_len(len)?,
+            1 => self.swap_st.set_len(len)?,
+            _ => self.return_st.set_len(len)?,
+        }
+        self.balance_stack(index);
+        Ok(())
+    }
+    fn stack_len(&self, index: usize) -> usize {
+        match index {
+            0 => self.data_st.len(),
+            1 => self.swap_st.len(),
+            _ => self.return_st.len(),
+        }
+    }
+    fn shadow_push(&mut self, address: u16) {
+        let depth = self.stack_depth(2);
+        if let Some(shadow) = &mut self.shadow {
+            shadow.push(depth, address);
+        }
+    }
+    fn shadow_pop(&mut self, target: u16) -> Result<(), Fault> {
+        let depth = self.stack_depth(2);
+        match &mut self.shadow {
+            Some(shadow) => shadow.pop(depth, target),
+            None => Ok(()),
+        }
+    }
+    // pushes the return address and enters supervisor mode at `vector`
+    fn trap(&mut self, return_address: u16, vector: u16) -> Result<(), Fault> {
+        self.return_st.push(&return_address.to_le_bytes())?;
+        self.shadow_push(return_address);
+        if let Some(widths) = &mut self.widths {
+            widths.trap(self.program_counter);
+        }
+        if !self.is_supervisor() {
+            self.status_reg = CPU::SUPERVISOR_FLAG;
+        }
+        self.program_counter = vector;
+        Ok(())
+    }
+    fn check_access(&self, range: std::ops::Range<usize>, access: Access) -> Result<(), Fault> {
+        if self.region_mask == 0 || range.is_empty() {
+            return Ok(());
+        }
+        let supervisor = self.is_supervisor();
+        for region in &self.regions {
+            if region.forbids(access, supervisor) && region.overlaps(&range) {
+                let address = usize::max(range.start, region.start as usize);
+                return Err(Fault::ProtectedMemory {
+                    address: address as u64,
+                    access,
+                });
+            }
+        }
+        Ok(())
+    }
+    // every write to memory goes through here first
+    fn before_write(&mut self, range: std::ops::Range<usize>) {
+        if let Some(journal) = &mut self.journal {
+            journal.record_write(&self.memory, range.clone());
+        }
+        self.mark_initialized(range.clone());
+        self.blocks.invalidate(range);
+    }
+    // bytes written some other way, like by DMA, journaled for stepping back
+    fn mark_initialized(&mut self, range: std::ops::Range<usize>) {
+        let Some(init) = &mut self.init else {
+            return;
+        };
+        if let Some(journal) = &mut self.journal {
+            journal.record_init(init, range.clone());
+        }
+        init.mark(range);
+    }
+    // reads of memory nothing has written are reported, or fault
+    fn check_init(&mut self, range: std::ops::Range<usize>) -> Result<(), Fault> {
+        let pc = self.program_counter;
+        let Some(init) = &mut self.init else {
+            return Ok(());
+        };
+        match init.check(pc, range) {
+            Some(read) if init.fault => Err(Fault::UninitializedRead {
+                pc: read.pc,
+                address: read.address,
+            }),
+            _ => Ok(()),
+        }
     }
-    fn pop_operands8(&mut self) -> (u8, u8) {
-        (self.pop_operand8(), self.pop_operand8())
+    fn get_device(&self, index: u8) -> Result<&DeviceSlot, Fault> {
+        self.devices
+            .get(index as usize)
+            .ok_or(Fault::InvalidDevice { index })
+    }
+    fn get_dma(&self, index: u8) -> Result<&DMA, Fault> {
+        self.dma_controllers
+            .get(index as usize)
+            .ok_or(Fault::InvalidDMA { index })
     }
 
-    fn pop_operand16(&mut self, len: usize) -> u16 {
-        let operand = le_slice_to_u16(self.data_st.pop(len));
-        self.data_st.drop(len);
-        operand
+    fn pop_operand8(&mut self) -> Result<u8, Fault> {
+        Ok(self.data_st.pop_value(1)? as u8)
+    }
+    fn pop_operands8(&mut self) -> Result<(u8, u8), Fault> {
+        Ok((self.pop_operand8()?, self.pop_operand8()?))
     }
-    fn pop_operands16(&mut self, len: usize) -> (u16, u16) {
-        (self.pop_operand16(len), self.pop_operand16(len))
+
+    fn pop_operand16(&mut self, len: usize) -> Result<u16, Fault> {
+        Ok(self.data_st.pop_value(len)? as u16)
     }
 
-    fn pop_operand32(&mut self, len: usize) -> u32 {
-        let operand = le_slice_to_u32(self.data_st.pop(len));
-        self.data_st.drop(len);
-        operand
+    fn pop_operand32(&mut self, len: usize) -> Result<u32, Fault> {
+        Ok(self.data_st.pop_value(len)? as u32)
     }
-    fn pop_operands32(&mut self, len: usize) -> (u32, u32) {
-        (self.pop_operand32(len), self.pop_operand32(len))
+    fn pop_operands32(&mut self, len: usize) -> Result<(u32, u32), Fault> {
+        Ok((self.pop_operand32(len)?, self.pop_operand32(len)?))
     }
 
-    fn pop_operand64(&mut self, len: usize) -> u64 {
-        let operand = le_slice_to_u64(self.data_st.pop(len));
-        self.data_st.drop(len);
-        operand
+    fn pop_operand64(&mut self, len: usize) -> Result<u64, Fault> {
+        self.data_st.pop_value(len)
     }
-    fn pop_operands64(&mut self, len: usize) -> (u64, u64) {
-        (self.pop_operand64(len), self.pop_operand64(len))
+    fn pop_operands64(&mut self, len: usize) -> Result<(u64, u64), Fault> {
+        Ok((self.pop_operand64(len)?, self.pop_operand64(len)?))
     }
 
-    fn push_result_bool(&mut self, result: bool) {
+    fn push_result_bool(&mut self, result: bool) -> Result<(), Fault> {
         self.push_result8(match result {
             true => 0xff,
             false => STACK_FALSE,
-        });
+        })
     }
-    fn push_result8(&mut self, result: u8) {
-        self.data_st.push(&result.to_le_bytes());
+    fn push_result8(&mut self, result: u8) -> Result<(), Fault> {
+        self.data_st.push_value(1, result as u64)
     }
-    fn push_result32(&mut self, len: usize, result: u32) {
-        self.data_st.push(&result.to_le_bytes()[0..len]);
+    fn push_result32(&mut self, len: usize, result: u32) -> Result<(), Fault> {
+        self.data_st.push_value(len, result as u64)
     }
-    fn push_result64(&mut self, len: usize, result: u64) {
-        self.data_st.push(&result.to_le_bytes()[0..len]);
+    fn push_result64(&mut self, len: usize, result: u64) -> Result<(), Fault> {
+        self.data_st.push_value(len, result)
     }
 }
 
-fn le_slice_to_u8(slice: &[u8]) -> u8 {
-    slice[0].clone()
+// instructions that only run in supervisor mode
+fn is_privileged(instruction: &Ins) -> bool {
+    matches!(
+        instruction,
+        Ins::SystemReturn
+            | Ins::InterruptEnable
+            | Ins::InterruptDisable
+            | Ins::SetSystemVector
+            | Ins::SetDeviceVector
+            | Ins::SetRegion
+            | Ins::SetStack
+            | Ins::DMARead
+            | Ins::DMAWrite { .. }
+            | Ins::DMAPoll
+            | Ins::DeviceRead { .. }
+            | Ins::DeviceWrite { .. }
+            | Ins::DevicePoll { .. }
+    )
 }
-fn le_slice_to_u16(slice: &[u8]) -> u16 {
-    let mut result = 0u16;
 
-    let max = usize::min(2, slice.len());
-    for i in 0..max {
-        let byte = (slice[i]) as u16;
-        let chunk = byte << (i * 8);
-        result = result | chunk;
+fn get_buffer_range(offset: u8, len: usize) -> Result<std::ops::Range<usize>, Fault> {
+    let start = offset as usize;
+    let end = start + len;
+    if end > 64 {
+        return Err(Fault::DeviceBufferOverflow { offset });
     }
-
-    result
+    Ok(start..end)
 }
-fn le_slice_to_u32(slice: &[u8]) -> u32 {
-    let mut result = 0u32;
 
-    let max = usize::min(4, slice.len());
-    for i in 0..max {
-        let byte = (slice[i]) as u32;
-        let chunk = byte << (i * 8);
-        result = result | chunk;
+fn shift_left(operand: u64, shift: u64) -> u64 {
+    match u32::try_from(shift) {
+        Ok(shift) => operand.checked_shl(shift).unwrap_or(0),
+        Err(_) => 0,
     }
-
-    result
 }
-fn le_slice_to_u64(slice: &[u8]) -> u64 {
-    let mut result = 0u64;
-
-    let max = usize::min(8, slice.len());
-    for i in 0..max {
-        let byte = (slice[i]) as u64;
-        let chunk = byte << (i * 8);
-        result = result | chunk;
+fn shift_right(operand: u64, shift: u64) -> u64 {
+    match u32::try_from(shift) {
+        Ok(shift) => operand.checked_shr(shift).unwrap_or(0),
+        Err(_) => 0,
     }
+}
 
-    result
+fn le_slice_to_u16(slice: &[u8]) -> u16 {
+    let mut bytes = [0u8; 2];
+    let len = slice.len().min(2);
+    bytes[..len].copy_from_slice(&slice[..len]);
+    u16::from_le_bytes(bytes)
+}
+fn le_slice_to_u64(slice: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    let len = slice.len().min(8);
+    bytes[..len].copy_from_slice(&slice[..len]);
+    u64::from_le_bytes(bytes)
 }
 
 fn f32_from_u32(bytes: u32) -> f32 {
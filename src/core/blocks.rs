@@ -0,0 +1,107 @@
+use super::instruction::Ins;
+
+const MAX_BLOCK_LEN: u16 = 256;
+const PAGE_SIZE: usize = 256;
+const PAGE_COUNT: usize = 65_536 / PAGE_SIZE;
+
+/*
+    Remembers how many instructions run straight through from a given
+    address before reaching one that branches or talks to the outside world.
+
+    Pages holding cached code are tracked, and any write to one of them
+    throws away every cached block, so self-modifying code stays correct.
+*/
+pub(super) struct BlockCache {
+    lengths: Box<[u16]>,
+    starts: Vec<u16>,
+    code_pages: [bool; PAGE_COUNT],
+    pub(super) invalidated: bool,
+}
+impl BlockCache {
+    pub(super) fn new() -> BlockCache {
+        BlockCache {
+            lengths: vec![0; 65_536].into_boxed_slice(),
+            starts: Vec::new(),
+            code_pages: [false; PAGE_COUNT],
+            invalidated: false,
+        }
+    }
+
+    // the number of instructions in the block at `start`, decoding it if needed
+    pub(super) fn block_len(&mut self, memory: &[u8], start: u16) -> u16 {
+        match self.lengths[start as usize] {
+            0 => self.decode(memory, start),
+            len => len,
+        }
+    }
+
+    pub(super) fn invalidate(&mut self, range: std::ops::Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let pages = range.start / PAGE_SIZE..=(range.end - 1) / PAGE_SIZE;
+        if !pages.into_iter().any(|page| self.code_pages[page]) {
+            return;
+        }
+        self.clear();
+    }
+
+    pub(super) fn clear(&mut self) {
+        for start in self.starts.drain(..) {
+            self.lengths[start as usize] = 0;
+        }
+        self.code_pages = [false; PAGE_COUNT];
+        self.invalidated = true;
+    }
+
+    fn decode(&mut self, memory: &[u8], start: u16) -> u16 {
+        let mut address = start as usize;
+        let mut len = 0;
+        loop {
+            let instruction = Ins::from(memory[address]);
+            len += 1;
+            address += 1;
+            match instruction {
+                Ins::Literal { len } => address += len as usize,
+                Ins::CallHash | Ins::AddressHash => address += 32,
+                _ => {}
+            }
+            if ends_block(&instruction) || len == MAX_BLOCK_LEN || address >= memory.len() {
+                break;
+            }
+        }
+
+        let end = usize::min(address, memory.len());
+        for page in start as usize / PAGE_SIZE..=(end - 1) / PAGE_SIZE {
+            self.code_pages[page] = true;
+        }
+        self.lengths[start as usize] = len;
+        self.starts.push(start);
+        len
+    }
+}
+
+// control flow, I/O and interrupt switches end a block, so the host can
+// react between blocks
+fn ends_block(instruction: &Ins) -> bool {
+    matches!(
+        instruction,
+        Ins::Jump { .. }
+            | Ins::Call { .. }
+            | Ins::CallHash
+            | Ins::Return { .. }
+            | Ins::SystemCall
+            | Ins::SystemReturn
+            | Ins::InterruptEnable
+            | Ins::InterruptDisable
+            | Ins::LoadContext
+            | Ins::SwitchContext
+            | Ins::Throw
+            | Ins::DMARead
+            | Ins::DMAWrite { .. }
+            | Ins::DMAPoll
+            | Ins::DeviceRead { .. }
+            | Ins::DeviceWrite { .. }
+            | Ins::DevicePoll { .. }
+    )
+}
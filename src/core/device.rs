@@ -1,8 +1,15 @@
+use super::{DeviceSlot, Fault, CPU};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::io::Read;
+use std::rc::Rc;
+
+const MAILBOX_TAG: &[u8; 7] = b"mailbox";
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 pub enum Devices {
     Console,
+    Mailbox(u8),
     Other([u8; 32]),
 }
 impl From<Devices> for [u8; 32] {
@@ -10,6 +17,10 @@ impl From<Devices> for [u8; 32] {
         let mut buffer = [0u8; 32];
         match device {
             Devices::Console => buffer[0] = 0,
+            Devices::Mailbox(channel) => {
+                buffer[..MAILBOX_TAG.len()].copy_from_slice(MAILBOX_TAG);
+                buffer[MAILBOX_TAG.len()] = channel;
+            }
             Devices::Other(id) => buffer.copy_from_slice(&id[..]),
         };
         buffer
@@ -17,6 +28,10 @@ impl From<Devices> for [u8; 32] {
 }
 impl From<[u8; 32]> for Devices {
     fn from(array: [u8; 32]) -> Self {
+        let (tag, rest) = array.split_at(MAILBOX_TAG.len());
+        if tag == MAILBOX_TAG && rest[1..].iter().all(|byte| *byte == 0) {
+            return Devices::Mailbox(rest[0]);
+        }
         for i in 1..32 {
             if array[i] != 0 {
                 return Devices::Other(array);
@@ -33,8 +48,79 @@ pub trait Device {
     fn recv(&mut self, buffer: &[u8; 64]);
 }
 
+pub type DeviceMap = HashMap<Devices, Box<dyn Device>>;
+
+// checks the DMA controllers and device slots the CPU has used since the last
+// service, passing data between the CPU and its devices
+pub fn service(cpu: &mut CPU, devices: &mut DeviceMap) -> Result<(), Fault> {
+    check_dmas(cpu);
+    check_devices(cpu, devices)
+}
+
+fn check_dmas(cpu: &mut CPU) {
+    let events = cpu.take_dma_events();
+    for (index, dma) in cpu.dma_controllers.iter().enumerate() {
+        if events & (1 << index) == 0 {
+            continue;
+        }
+
+        let has_request = super::DMA::REQ_BIT & dma.status_reg != 0;
+        if has_request {
+            // process request
+            // let payload_range = dma.address as usize..(dma.address + dma.buffer_len) as usize;
+            // let payload = &cpu.memory[payload_range];
+        }
+    }
+}
+
+fn check_devices(cpu: &mut CPU, devices: &mut DeviceMap) -> Result<(), Fault> {
+    let events = cpu.take_device_events();
+    for index in 0..cpu.devices.len() {
+        if cpu.slot_mask & (1 << index) == 0 {
+            continue; // nothing connected
+        }
+
+        // get the device for given slot
+        let slot = &mut cpu.devices[index];
+        let device_type = slot.identifier.into();
+        let Some(device) = devices.get_mut(&device_type) else {
+            // eprint!("unregistered device");
+            continue;
+        };
+
+        // check status registers
+        let cpu_send = DeviceSlot::SEND_FLAG & slot.status_reg != 0;
+        let cpu_done = DeviceSlot::DONE_FLAG & slot.status_reg != 0;
+        let cpu_block = DeviceSlot::BLOCK_FLAG & slot.status_reg != 0;
+
+        // if cpu has sent data since the last check, receive it
+        if cpu_send && events & (1 << index) != 0 {
+            device.recv(&slot.out_buffer);
+            if cpu_done {
+                slot.status_reg ^= DeviceSlot::SEND_FLAG; // turn off
+                slot.status_reg ^= DeviceSlot::DONE_FLAG; // turn off
+            }
+        }
+
+        // don't read from device if cpu is blocking until outgoing is done,
+        // or has interrupts turned off
+        if !(cpu_send && !cpu_done && cpu_block) && cpu.interrupts_enabled() {
+            // if device has data waiting, pass one buffer in, so the
+            // interrupt handler sees it before the next one arrives
+            if let Some(device_buffer) = device.poll() {
+                let slot = &mut cpu.devices[index];
+                slot.in_buffer.copy_from_slice(&device_buffer);
+                let vector = slot.vector;
+                cpu.interrupt(vector)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // console device
-pub(crate) struct Console {
+pub struct Console {
     read_cursor: Option<usize>,
     read_buffer: Vec<u8>,
 }
@@ -46,9 +132,14 @@ impl Console {
         }
     }
 }
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl Device for Console {
     fn poll(&mut self) -> Option<[u8; 64]> {
-        fn read_buffer(cursor: usize, in_buffer: &Vec<u8>) -> (Option<usize>, [u8; 64]) {
+        fn read_buffer(cursor: usize, in_buffer: &[u8]) -> (Option<usize>, [u8; 64]) {
             // advance cursor
             let end = usize::min(cursor + 64, in_buffer.len());
             let copy_size = end - cursor;
@@ -59,7 +150,7 @@ impl Device for Console {
 
             // copy from stored buffer
             let mut out_buffer = [0; 64];
-            out_buffer[0..copy_size].copy_from_slice(&in_buffer[(cursor..end)]);
+            out_buffer[0..copy_size].copy_from_slice(&in_buffer[cursor..end]);
 
             (next_cursor, out_buffer)
         }
@@ -68,7 +159,8 @@ impl Device for Console {
         let cursor = match self.read_cursor {
             Some(c) => c,
             None => {
-                self.read_buffer = std::io::stdin().bytes().map(|b| b.unwrap()).collect();
+                self.read_buffer.clear();
+                std::io::stdin().read_to_end(&mut self.read_buffer).unwrap();
                 match self.read_buffer.len() {
                     0 => return None,
                     _ => 0,
@@ -84,3 +176,33 @@ impl Device for Console {
         print!("{}", String::from_utf8_lossy(buffer))
     }
 }
+
+// one end of a pair of mailboxes: buffers sent on one end are polled from the other
+pub struct Mailbox {
+    inbox: Rc<RefCell<VecDeque<[u8; 64]>>>,
+    outbox: Rc<RefCell<VecDeque<[u8; 64]>>>,
+}
+impl Mailbox {
+    pub fn pair() -> (Mailbox, Mailbox) {
+        let a = Rc::new(RefCell::new(VecDeque::new()));
+        let b = Rc::new(RefCell::new(VecDeque::new()));
+        (
+            Mailbox {
+                inbox: a.clone(),
+                outbox: b.clone(),
+            },
+            Mailbox {
+                inbox: b,
+                outbox: a,
+            },
+        )
+    }
+}
+impl Device for Mailbox {
+    fn poll(&mut self) -> Option<[u8; 64]> {
+        self.inbox.borrow_mut().pop_front()
+    }
+    fn recv(&mut self, buffer: &[u8; 64]) {
+        self.outbox.borrow_mut().push_back(*buffer);
+    }
+}
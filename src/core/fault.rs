@@ -0,0 +1,93 @@
+use super::widths::Mismatch;
+use std::fmt::Display;
+
+// how a faulting instruction tried to use memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+    Device,
+}
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::Read => write!(f, "Read"),
+            Access::Write => write!(f, "Write"),
+            Access::Execute => write!(f, "Execute"),
+            Access::Device => write!(f, "Device Write"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    StackUnderflow,
+    StackOverflow,
+    RegisterOverflow,
+    MemoryOverflow { address: u64 },
+    DivideByZero,
+    InvalidDevice { index: u8 },
+    InvalidDMA { index: u8 },
+    InvalidRegion { index: u8 },
+    InvalidStack { index: u8 },
+    SpilledContext { index: u8 },
+    UncaughtThrow { code: u8 },
+    CatchMismatch,
+    ReturnMismatch { expected: u16, found: u16 },
+    WidthMismatch(Mismatch),
+    UninitializedRead { pc: u16, address: u16 },
+    DeviceBufferOverflow { offset: u8 },
+    PrivilegedInstruction { opcode: u8 },
+    ProtectedMemory { address: u64, access: Access },
+    UnknownRoutine { hash: [u8; 32] },
+    LinkerFull { hash: [u8; 32] },
+}
+impl Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fault::StackUnderflow => write!(f, "Stack Underflow"),
+            Fault::StackOverflow => write!(f, "Stack Overflow"),
+            Fault::RegisterOverflow => write!(f, "Register Overflow"),
+            Fault::MemoryOverflow { address } => write!(f, "Memory Overflow at {:#06X}", address),
+            Fault::DivideByZero => write!(f, "Divide By Zero"),
+            Fault::InvalidDevice { index } => write!(f, "Invalid Device Slot {}", index),
+            Fault::InvalidDMA { index } => write!(f, "Invalid DMA Controller {}", index),
+            Fault::InvalidRegion { index } => write!(f, "Invalid Memory Region {}", index),
+            Fault::InvalidStack { index } => write!(f, "Invalid Stack {}", index),
+            Fault::SpilledContext { index } => {
+                write!(f, "Context Saved With Stack {} Spilled To Memory", index)
+            }
+            Fault::UncaughtThrow { code } => write!(f, "Uncaught Throw {:#04X}", code),
+            Fault::CatchMismatch => write!(f, "End Catch Without Its Frame On Top"),
+            Fault::ReturnMismatch { expected, found } => write!(
+                f,
+                "Return to {:#06X} where the call expected {:#06X}",
+                found, expected
+            ),
+            Fault::WidthMismatch(mismatch) => write!(f, "Width Mismatch: {}", mismatch),
+            Fault::UninitializedRead { pc, address } => {
+                write!(f, "Uninitialized Read at {:#06X} by {:#06X}", address, pc)
+            }
+            Fault::DeviceBufferOverflow { offset } => {
+                write!(f, "Device Buffer Overflow at offset {}", offset)
+            }
+            Fault::PrivilegedInstruction { opcode } => {
+                write!(f, "Privileged Instruction {:#04X} in User Mode", opcode)
+            }
+            Fault::ProtectedMemory { address, access } => {
+                write!(f, "Protected Memory {} at {:#06X}", access, address)
+            }
+            Fault::UnknownRoutine { hash } => write!(f, "Unknown Routine {}", Hex(hash)),
+            Fault::LinkerFull { hash } => write!(f, "No Room To Link Routine {}", Hex(hash)),
+        }
+    }
+}
+
+// a routine hash, as the `library` tool prints it
+struct Hex<'a>(&'a [u8; 32]);
+impl Display for Hex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
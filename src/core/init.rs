@@ -0,0 +1,75 @@
+use std::ops::Range;
+
+/*
+    One bit per byte of memory, set once the byte has been written: by
+    `load_rom`, by any instruction or host write, or by a device filling a
+    DMA buffer. `Load` and `Literal` reads touching an unset byte are
+    reported with the address of the byte and of the instruction, or raised
+    as a fault. Stepping back through the journal clears the bits the
+    undone step set, and drops the reports it made.
+*/
+pub struct InitCheck {
+    pub fault: bool,
+    pub reports: Vec<Uninitialized>,
+    bits: Box<[u64]>,
+}
+
+// a read at `pc` of a byte at `address` that nothing wrote
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Uninitialized {
+    pub pc: u16,
+    pub address: u16,
+}
+impl std::fmt::Display for Uninitialized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:#06X} read uninitialized memory at {:#06X}",
+            self.pc, self.address
+        )
+    }
+}
+
+impl InitCheck {
+    pub fn new(fault: bool) -> InitCheck {
+        InitCheck {
+            fault,
+            reports: Vec::new(),
+            bits: vec![0; 65_536 / 64].into_boxed_slice(),
+        }
+    }
+
+    pub fn is_initialized(&self, address: usize) -> bool {
+        self.bits[address / 64] & 1 << (address % 64) != 0
+    }
+
+    pub fn mark(&mut self, range: Range<usize>) {
+        for address in range {
+            self.bits[address / 64] |= 1 << (address % 64);
+        }
+    }
+
+    // the words of bits covering `range`, and the index of the first
+    pub(super) fn words(&self, range: Range<usize>) -> (usize, Vec<u64>) {
+        if range.is_empty() {
+            return (0, Vec::new());
+        }
+        let first = range.start / 64;
+        let last = range.end.div_ceil(64).min(self.bits.len());
+        (first, self.bits[first..last].to_vec())
+    }
+    pub(super) fn restore(&mut self, first: usize, words: &[u64]) {
+        self.bits[first..first + words.len()].copy_from_slice(words);
+    }
+
+    // the first byte in `range` nothing wrote, reported against `pc`
+    pub(super) fn check(&mut self, pc: u16, range: Range<usize>) -> Option<Uninitialized> {
+        let address = range.into_iter().find(|&a| !self.is_initialized(a))?;
+        let read = Uninitialized {
+            pc,
+            address: address as u16,
+        };
+        self.reports.push(read);
+        Some(read)
+    }
+}
@@ -8,6 +8,7 @@
 
     0000_1XXX -- Jump ( 8 / 8 )
           CRL -- L = len, C = cond?, R = rel?
+                 relative offsets are from the jump; 8 bit ones are signed
 
     0001_XXXX -- Float Operatins ( 12 / 16 )
          0XXX -- Float Arithmetic (8 / 8 )
@@ -15,7 +16,9 @@
          1XXX -- Float Comparisons ( 4 / 8 )
           DDL -- L = len, D = id
 
-    001X_XXXX -- Unused ( 0 / 32 )
+    001X_XXXX -- System ( 17 / 32 )
+       X_XXXX -- D = id
+                 CLH and ADH are followed by the 32 byte hash of a routine
 
     01XX_XXXX -- Byte Manipulation ( 56 / 64 )
       1X_XXXX -- Integer Operations ( 32 / 32 )
@@ -48,6 +51,7 @@
 */
 use super::len::*;
 
+#[derive(Clone, Copy, Debug)]
 pub enum Ins {
     NoOperation,
 
@@ -92,6 +96,25 @@ pub enum Ins {
     Call { len: Len16 },
     Return { len: Len16 },
 
+    // System
+    SystemCall,
+    SystemReturn,
+    InterruptEnable,
+    InterruptDisable,
+    SetSystemVector,
+    SetDeviceVector,
+    SetRegion,
+    SetStack,
+    ReadStack,
+    SaveContext,
+    LoadContext,
+    SwitchContext,
+    Catch,
+    Throw,
+    EndCatch,
+    CallHash,
+    AddressHash,
+
     // Int Operations
     Add { len: Len64 },
     Subtract { len: Len64 },
@@ -119,339 +142,355 @@ pub enum Ins {
     LessF { len: LenF },
 }
 
+// every opcode, decoded once at compile time
+static DECODE_TABLE: [Ins; 256] = {
+    let mut table = [Ins::NoOperation; 256];
+    let mut byte = 0;
+    while byte < table.len() {
+        table[byte] = decode(byte as u8);
+        byte += 1;
+    }
+    table
+};
+
 impl From<u8> for Ins {
+    #[inline]
     fn from(byte: u8) -> Self {
-        match byte {
-            0b00000000 => Ins::NoOperation,
+        DECODE_TABLE[byte as usize]
+    }
+}
 
-            0b00000001 => Ins::NoOperation,
+const fn decode(byte: u8) -> Ins {
+    match byte {
+        0b00000000 => Ins::NoOperation,
 
-            0b0000001_0 => Ins::NoOperation,
-            0b0000001_1 => Ins::NoOperation,
+        0b00000001 => Ins::NoOperation,
 
-            // Call/Return  -- 000001_IL (instruction, length)
-            0b000001_00 => Ins::Call { len: Len16::L8 },
-            0b000001_01 => Ins::Call { len: Len16::L16 },
-            0b000001_10 => Ins::Return { len: Len16::L16 },
-            0b000001_11 => Ins::Return { len: Len16::L16 },
+        0b0000_0010 => Ins::NoOperation,
+        0b0000_0011 => Ins::NoOperation,
 
-            // Jump         -- 00001_CRL (conditional, relative, length)
-            0b00001_000 => Ins::Jump {
-                con: false,
-                rel: false,
-                len: Len16::L8,
-            },
-            0b00001_001 => Ins::Jump {
-                con: false,
-                rel: false,
-                len: Len16::L16,
-            },
-            0b00001_010 => Ins::Jump {
-                con: false,
-                rel: true,
-                len: Len16::L8,
-            },
-            0b00001_011 => Ins::Jump {
-                con: false,
-                rel: true,
-                len: Len16::L16,
-            },
-            0b00001_100 => Ins::Jump {
-                con: true,
-                rel: false,
-                len: Len16::L8,
-            },
-            0b00001_101 => Ins::Jump {
-                con: true,
-                rel: false,
-                len: Len16::L16,
-            },
-            0b00001_110 => Ins::Jump {
-                con: true,
-                rel: true,
-                len: Len16::L8,
-            },
-            0b00001_111 => Ins::Jump {
-                con: true,
-                rel: true,
-                len: Len16::L16,
-            },
+        // Call/Return  -- 000001_IL (instruction, length)
+        0b0000_0100 => Ins::Call { len: Len16::L8 },
+        0b0000_0101 => Ins::Call { len: Len16::L16 },
+        0b0000_0110 => Ins::Return { len: Len16::L16 },
+        0b0000_0111 => Ins::Return { len: Len16::L16 },
+
+        // Jump         -- 00001_CRL (conditional, relative, length)
+        0b0000_1000 => Ins::Jump {
+            con: false,
+            rel: false,
+            len: Len16::L8,
+        },
+        0b0000_1001 => Ins::Jump {
+            con: false,
+            rel: false,
+            len: Len16::L16,
+        },
+        0b0000_1010 => Ins::Jump {
+            con: false,
+            rel: true,
+            len: Len16::L8,
+        },
+        0b0000_1011 => Ins::Jump {
+            con: false,
+            rel: true,
+            len: Len16::L16,
+        },
+        0b0000_1100 => Ins::Jump {
+            con: true,
+            rel: false,
+            len: Len16::L8,
+        },
+        0b0000_1101 => Ins::Jump {
+            con: true,
+            rel: false,
+            len: Len16::L16,
+        },
+        0b0000_1110 => Ins::Jump {
+            con: true,
+            rel: true,
+            len: Len16::L8,
+        },
+        0b0000_1111 => Ins::Jump {
+            con: true,
+            rel: true,
+            len: Len16::L16,
+        },
 
-            // Float Ops    -- 0001_IIIL (instruction, length)
-            0b0001_0000 => Ins::AddF { len: LenF::L32 },
-            0b0001_0001 => Ins::AddF { len: LenF::L64 },
-            0b0001_0010 => Ins::SubtractF { len: LenF::L32 },
-            0b0001_0011 => Ins::SubtractF { len: LenF::L64 },
-            0b0001_0100 => Ins::MultiplyF { len: LenF::L32 },
-            0b0001_0101 => Ins::MultiplyF { len: LenF::L64 },
-            0b0001_0110 => Ins::DivideF { len: LenF::L32 },
-            0b0001_0111 => Ins::DivideF { len: LenF::L64 },
-            0b0001_1000 => Ins::GreaterF { len: LenF::L32 },
-            0b0001_1001 => Ins::GreaterF { len: LenF::L64 },
-            0b0001_1010 => Ins::LessF { len: LenF::L32 },
-            0b0001_1011 => Ins::LessF { len: LenF::L64 },
-            0b0001_1100 => Ins::NoOperation,
-            0b0001_1101 => Ins::NoOperation,
-            0b0001_1110 => Ins::NoOperation,
-            0b0001_1111 => Ins::NoOperation,
+        // Float Ops    -- 0001_IIIL (instruction, length)
+        0b0001_0000 => Ins::AddF { len: LenF::L32 },
+        0b0001_0001 => Ins::AddF { len: LenF::L64 },
+        0b0001_0010 => Ins::SubtractF { len: LenF::L32 },
+        0b0001_0011 => Ins::SubtractF { len: LenF::L64 },
+        0b0001_0100 => Ins::MultiplyF { len: LenF::L32 },
+        0b0001_0101 => Ins::MultiplyF { len: LenF::L64 },
+        0b0001_0110 => Ins::DivideF { len: LenF::L32 },
+        0b0001_0111 => Ins::DivideF { len: LenF::L64 },
+        0b0001_1000 => Ins::GreaterF { len: LenF::L32 },
+        0b0001_1001 => Ins::GreaterF { len: LenF::L64 },
+        0b0001_1010 => Ins::LessF { len: LenF::L32 },
+        0b0001_1011 => Ins::LessF { len: LenF::L64 },
+        0b0001_1100 => Ins::NoOperation,
+        0b0001_1101 => Ins::NoOperation,
+        0b0001_1110 => Ins::NoOperation,
+        0b0001_1111 => Ins::NoOperation,
 
-            // Unused       -- 001_XXXXX
-            0b001_00000 => Ins::NoOperation,
-            0b001_00001 => Ins::NoOperation,
-            0b001_00010 => Ins::NoOperation,
-            0b001_00011 => Ins::NoOperation,
-            0b001_00100 => Ins::NoOperation,
-            0b001_00101 => Ins::NoOperation,
-            0b001_00110 => Ins::NoOperation,
-            0b001_00111 => Ins::NoOperation,
-            0b001_01000 => Ins::NoOperation,
-            0b001_01001 => Ins::NoOperation,
-            0b001_01010 => Ins::NoOperation,
-            0b001_01011 => Ins::NoOperation,
-            0b001_01100 => Ins::NoOperation,
-            0b001_01101 => Ins::NoOperation,
-            0b001_01110 => Ins::NoOperation,
-            0b001_01111 => Ins::NoOperation,
-            0b001_10000 => Ins::NoOperation,
-            0b001_10001 => Ins::NoOperation,
-            0b001_10010 => Ins::NoOperation,
-            0b001_10011 => Ins::NoOperation,
-            0b001_10100 => Ins::NoOperation,
-            0b001_10101 => Ins::NoOperation,
-            0b001_10110 => Ins::NoOperation,
-            0b001_10111 => Ins::NoOperation,
-            0b001_11000 => Ins::NoOperation,
-            0b001_11001 => Ins::NoOperation,
-            0b001_11010 => Ins::NoOperation,
-            0b001_11011 => Ins::NoOperation,
-            0b001_11100 => Ins::NoOperation,
-            0b001_11101 => Ins::NoOperation,
-            0b001_11110 => Ins::NoOperation,
-            0b001_11111 => Ins::NoOperation,
+        // System       -- 001_XXXXX
+        0b0010_0000 => Ins::SystemCall,
+        0b0010_0001 => Ins::SystemReturn,
+        0b0010_0010 => Ins::InterruptEnable,
+        0b0010_0011 => Ins::InterruptDisable,
+        0b0010_0100 => Ins::SetSystemVector,
+        0b0010_0101 => Ins::SetDeviceVector,
+        0b0010_0110 => Ins::SetRegion,
+        0b0010_0111 => Ins::SetStack,
+        0b0010_1000 => Ins::ReadStack,
+        0b0010_1001 => Ins::SaveContext,
+        0b0010_1010 => Ins::LoadContext,
+        0b0010_1011 => Ins::SwitchContext,
+        0b0010_1100 => Ins::Catch,
+        0b0010_1101 => Ins::Throw,
+        0b0010_1110 => Ins::EndCatch,
+        0b0010_1111 => Ins::CallHash,
+        0b0011_0000 => Ins::AddressHash,
+        0b0011_0001 => Ins::NoOperation,
+        0b0011_0010 => Ins::NoOperation,
+        0b0011_0011 => Ins::NoOperation,
+        0b0011_0100 => Ins::NoOperation,
+        0b0011_0101 => Ins::NoOperation,
+        0b0011_0110 => Ins::NoOperation,
+        0b0011_0111 => Ins::NoOperation,
+        0b0011_1000 => Ins::NoOperation,
+        0b0011_1001 => Ins::NoOperation,
+        0b0011_1010 => Ins::NoOperation,
+        0b0011_1011 => Ins::NoOperation,
+        0b0011_1100 => Ins::NoOperation,
+        0b0011_1101 => Ins::NoOperation,
+        0b0011_1110 => Ins::NoOperation,
+        0b0011_1111 => Ins::NoOperation,
 
-            // Byte Ops     -- 01_XXXXXX
+        // Byte Ops     -- 01_XXXXXX
 
-            // Bitwise Ops  -- 010_IIILL (instruction, length)
-            0b010_00000 => Ins::And { len: Len64::L08 },
-            0b010_00001 => Ins::And { len: Len64::L16 },
-            0b010_00010 => Ins::And { len: Len64::L32 },
-            0b010_00011 => Ins::And { len: Len64::L64 },
-            0b010_00100 => Ins::Or { len: Len64::L08 },
-            0b010_00101 => Ins::Or { len: Len64::L16 },
-            0b010_00110 => Ins::Or { len: Len64::L32 },
-            0b010_00111 => Ins::Or { len: Len64::L64 },
-            0b010_01000 => Ins::Xor { len: Len64::L08 },
-            0b010_01001 => Ins::Xor { len: Len64::L16 },
-            0b010_01010 => Ins::Xor { len: Len64::L32 },
-            0b010_01011 => Ins::Xor { len: Len64::L64 },
-            0b010_01100 => Ins::Not { len: Len64::L08 },
-            0b010_01101 => Ins::Not { len: Len64::L16 },
-            0b010_01110 => Ins::Not { len: Len64::L32 },
-            0b010_01111 => Ins::Not { len: Len64::L64 },
-            0b010_10000 => Ins::ShiftL { len: Len64::L08 },
-            0b010_10001 => Ins::ShiftL { len: Len64::L16 },
-            0b010_10010 => Ins::ShiftL { len: Len64::L32 },
-            0b010_10011 => Ins::ShiftL { len: Len64::L64 },
-            0b010_10100 => Ins::ShiftR { len: Len64::L08 },
-            0b010_10101 => Ins::ShiftR { len: Len64::L16 },
-            0b010_10110 => Ins::ShiftR { len: Len64::L32 },
-            0b010_10111 => Ins::ShiftR { len: Len64::L64 },
-            0b010_11000 => Ins::NoOperation,
-            0b010_11001 => Ins::NoOperation,
-            0b010_11010 => Ins::NoOperation,
-            0b010_11011 => Ins::NoOperation,
-            0b010_11100 => Ins::NoOperation,
-            0b010_11101 => Ins::NoOperation,
-            0b010_11110 => Ins::NoOperation,
-            0b010_11111 => Ins::NoOperation,
+        // Bitwise Ops  -- 010_IIILL (instruction, length)
+        0b0100_0000 => Ins::And { len: Len64::L08 },
+        0b0100_0001 => Ins::And { len: Len64::L16 },
+        0b0100_0010 => Ins::And { len: Len64::L32 },
+        0b0100_0011 => Ins::And { len: Len64::L64 },
+        0b0100_0100 => Ins::Or { len: Len64::L08 },
+        0b0100_0101 => Ins::Or { len: Len64::L16 },
+        0b0100_0110 => Ins::Or { len: Len64::L32 },
+        0b0100_0111 => Ins::Or { len: Len64::L64 },
+        0b0100_1000 => Ins::Xor { len: Len64::L08 },
+        0b0100_1001 => Ins::Xor { len: Len64::L16 },
+        0b0100_1010 => Ins::Xor { len: Len64::L32 },
+        0b0100_1011 => Ins::Xor { len: Len64::L64 },
+        0b0100_1100 => Ins::Not { len: Len64::L08 },
+        0b0100_1101 => Ins::Not { len: Len64::L16 },
+        0b0100_1110 => Ins::Not { len: Len64::L32 },
+        0b0100_1111 => Ins::Not { len: Len64::L64 },
+        0b0101_0000 => Ins::ShiftL { len: Len64::L08 },
+        0b0101_0001 => Ins::ShiftL { len: Len64::L16 },
+        0b0101_0010 => Ins::ShiftL { len: Len64::L32 },
+        0b0101_0011 => Ins::ShiftL { len: Len64::L64 },
+        0b0101_0100 => Ins::ShiftR { len: Len64::L08 },
+        0b0101_0101 => Ins::ShiftR { len: Len64::L16 },
+        0b0101_0110 => Ins::ShiftR { len: Len64::L32 },
+        0b0101_0111 => Ins::ShiftR { len: Len64::L64 },
+        0b0101_1000 => Ins::NoOperation,
+        0b0101_1001 => Ins::NoOperation,
+        0b0101_1010 => Ins::NoOperation,
+        0b0101_1011 => Ins::NoOperation,
+        0b0101_1100 => Ins::NoOperation,
+        0b0101_1101 => Ins::NoOperation,
+        0b0101_1110 => Ins::NoOperation,
+        0b0101_1111 => Ins::NoOperation,
 
-            // Int Math     -- 011_IIILL (instruction, length)
-            0b011_00000 => Ins::Add { len: Len64::L08 },
-            0b011_00001 => Ins::Add { len: Len64::L16 },
-            0b011_00010 => Ins::Add { len: Len64::L32 },
-            0b011_00011 => Ins::Add { len: Len64::L64 },
-            0b011_00100 => Ins::Subtract { len: Len64::L08 },
-            0b011_00101 => Ins::Subtract { len: Len64::L16 },
-            0b011_00110 => Ins::Subtract { len: Len64::L32 },
-            0b011_00111 => Ins::Subtract { len: Len64::L64 },
-            0b011_01000 => Ins::Multiply { len: Len64::L08 },
-            0b011_01001 => Ins::Multiply { len: Len64::L16 },
-            0b011_01010 => Ins::Multiply { len: Len64::L32 },
-            0b011_01011 => Ins::Multiply { len: Len64::L64 },
-            0b011_01100 => Ins::Divide { len: Len64::L08 },
-            0b011_01101 => Ins::Divide { len: Len64::L16 },
-            0b011_01110 => Ins::Divide { len: Len64::L32 },
-            0b011_01111 => Ins::Divide { len: Len64::L64 },
-            0b011_10000 => Ins::Greater { len: Len64::L08 },
-            0b011_10001 => Ins::Greater { len: Len64::L16 },
-            0b011_10010 => Ins::Greater { len: Len64::L32 },
-            0b011_10011 => Ins::Greater { len: Len64::L64 },
-            0b011_10100 => Ins::Less { len: Len64::L08 },
-            0b011_10101 => Ins::Less { len: Len64::L16 },
-            0b011_10110 => Ins::Less { len: Len64::L32 },
-            0b011_10111 => Ins::Less { len: Len64::L64 },
-            0b011_11000 => Ins::Equal { len: Len64::L08 },
-            0b011_11001 => Ins::Equal { len: Len64::L16 },
-            0b011_11010 => Ins::Equal { len: Len64::L32 },
-            0b011_11011 => Ins::Equal { len: Len64::L64 },
-            0b011_11100 => Ins::NotEqual { len: Len64::L08 },
-            0b011_11101 => Ins::NotEqual { len: Len64::L16 },
-            0b011_11110 => Ins::NotEqual { len: Len64::L32 },
-            0b011_11111 => Ins::NotEqual { len: Len64::L64 },
+        // Int Math     -- 011_IIILL (instruction, length)
+        0b0110_0000 => Ins::Add { len: Len64::L08 },
+        0b0110_0001 => Ins::Add { len: Len64::L16 },
+        0b0110_0010 => Ins::Add { len: Len64::L32 },
+        0b0110_0011 => Ins::Add { len: Len64::L64 },
+        0b0110_0100 => Ins::Subtract { len: Len64::L08 },
+        0b0110_0101 => Ins::Subtract { len: Len64::L16 },
+        0b0110_0110 => Ins::Subtract { len: Len64::L32 },
+        0b0110_0111 => Ins::Subtract { len: Len64::L64 },
+        0b0110_1000 => Ins::Multiply { len: Len64::L08 },
+        0b0110_1001 => Ins::Multiply { len: Len64::L16 },
+        0b0110_1010 => Ins::Multiply { len: Len64::L32 },
+        0b0110_1011 => Ins::Multiply { len: Len64::L64 },
+        0b0110_1100 => Ins::Divide { len: Len64::L08 },
+        0b0110_1101 => Ins::Divide { len: Len64::L16 },
+        0b0110_1110 => Ins::Divide { len: Len64::L32 },
+        0b0110_1111 => Ins::Divide { len: Len64::L64 },
+        0b0111_0000 => Ins::Greater { len: Len64::L08 },
+        0b0111_0001 => Ins::Greater { len: Len64::L16 },
+        0b0111_0010 => Ins::Greater { len: Len64::L32 },
+        0b0111_0011 => Ins::Greater { len: Len64::L64 },
+        0b0111_0100 => Ins::Less { len: Len64::L08 },
+        0b0111_0101 => Ins::Less { len: Len64::L16 },
+        0b0111_0110 => Ins::Less { len: Len64::L32 },
+        0b0111_0111 => Ins::Less { len: Len64::L64 },
+        0b0111_1000 => Ins::Equal { len: Len64::L08 },
+        0b0111_1001 => Ins::Equal { len: Len64::L16 },
+        0b0111_1010 => Ins::Equal { len: Len64::L32 },
+        0b0111_1011 => Ins::Equal { len: Len64::L64 },
+        0b0111_1100 => Ins::NotEqual { len: Len64::L08 },
+        0b0111_1101 => Ins::NotEqual { len: Len64::L16 },
+        0b0111_1110 => Ins::NotEqual { len: Len64::L32 },
+        0b0111_1111 => Ins::NotEqual { len: Len64::L64 },
 
-            // Data Ops     -- 1xxx_xxxx
+        // Data Ops     -- 1xxx_xxxx
 
-            // Memory + IO  -- 10xx_xxxx
+        // Memory + IO  -- 10xx_xxxx
 
-            // IO           -- 100x_xxxx
+        // IO           -- 100x_xxxx
 
-            // DMA          -- 1000_xxxx
-            0b1000_0000 => Ins::DMARead,
-            0b1000_0001 => Ins::NoOperation,
-            0b1000_0010 => Ins::NoOperation,
-            0b1000_0011 => Ins::NoOperation,
-            0b1000_0100 => Ins::DMAWrite { len: Len32::L08 },
-            0b1000_0101 => Ins::DMAWrite { len: Len32::L16 },
-            0b1000_0110 => Ins::DMAWrite { len: Len32::L32 },
-            0b1000_0111 => Ins::NoOperation,
-            0b1000_1000 => Ins::DMAPoll,
-            0b1000_1001 => Ins::NoOperation,
-            0b1000_1010 => Ins::NoOperation,
-            0b1000_1011 => Ins::NoOperation,
-            0b1000_1100 => Ins::NoOperation,
-            0b1000_1101 => Ins::NoOperation,
-            0b1000_1110 => Ins::NoOperation,
-            0b1000_1111 => Ins::NoOperation,
+        // DMA          -- 1000_xxxx
+        0b1000_0000 => Ins::DMARead,
+        0b1000_0001 => Ins::NoOperation,
+        0b1000_0010 => Ins::NoOperation,
+        0b1000_0011 => Ins::NoOperation,
+        0b1000_0100 => Ins::DMAWrite { len: Len32::L08 },
+        0b1000_0101 => Ins::DMAWrite { len: Len32::L16 },
+        0b1000_0110 => Ins::DMAWrite { len: Len32::L32 },
+        0b1000_0111 => Ins::NoOperation,
+        0b1000_1000 => Ins::DMAPoll,
+        0b1000_1001 => Ins::NoOperation,
+        0b1000_1010 => Ins::NoOperation,
+        0b1000_1011 => Ins::NoOperation,
+        0b1000_1100 => Ins::NoOperation,
+        0b1000_1101 => Ins::NoOperation,
+        0b1000_1110 => Ins::NoOperation,
+        0b1000_1111 => Ins::NoOperation,
 
-            // Devices      -- 1001_xxxx
-            0b1001_0000 => Ins::DeviceRead { len: Len64::L08 },
-            0b1001_0001 => Ins::DeviceRead { len: Len64::L16 },
-            0b1001_0010 => Ins::DeviceRead { len: Len64::L32 },
-            0b1001_0011 => Ins::DeviceRead { len: Len64::L64 },
-            0b1001_0100 => Ins::DeviceWrite { len: Len64::L08 },
-            0b1001_0101 => Ins::DeviceWrite { len: Len64::L16 },
-            0b1001_0110 => Ins::DeviceWrite { len: Len64::L32 },
-            0b1001_0111 => Ins::DeviceWrite { len: Len64::L64 },
-            0b1001_1000 => Ins::DevicePoll { len: Len64::L08 },
-            0b1001_1001 => Ins::DevicePoll { len: Len64::L16 },
-            0b1001_1010 => Ins::DevicePoll { len: Len64::L32 },
-            0b1001_1011 => Ins::DevicePoll { len: Len64::L64 },
-            0b1001_1100 => Ins::NoOperation,
-            0b1001_1101 => Ins::NoOperation,
-            0b1001_1110 => Ins::NoOperation,
-            0b1001_1111 => Ins::NoOperation,
+        // Devices      -- 1001_xxxx
+        0b1001_0000 => Ins::DeviceRead { len: Len64::L08 },
+        0b1001_0001 => Ins::DeviceRead { len: Len64::L16 },
+        0b1001_0010 => Ins::DeviceRead { len: Len64::L32 },
+        0b1001_0011 => Ins::DeviceRead { len: Len64::L64 },
+        0b1001_0100 => Ins::DeviceWrite { len: Len64::L08 },
+        0b1001_0101 => Ins::DeviceWrite { len: Len64::L16 },
+        0b1001_0110 => Ins::DeviceWrite { len: Len64::L32 },
+        0b1001_0111 => Ins::DeviceWrite { len: Len64::L64 },
+        0b1001_1000 => Ins::DevicePoll { len: Len64::L08 },
+        0b1001_1001 => Ins::DevicePoll { len: Len64::L16 },
+        0b1001_1010 => Ins::DevicePoll { len: Len64::L32 },
+        0b1001_1011 => Ins::DevicePoll { len: Len64::L64 },
+        0b1001_1100 => Ins::NoOperation,
+        0b1001_1101 => Ins::NoOperation,
+        0b1001_1110 => Ins::NoOperation,
+        0b1001_1111 => Ins::NoOperation,
 
-            // Memory       -- 101x_xxxx
+        // Memory       -- 101x_xxxx
 
-            // unused       -- 1010_xxxx
-            0b1010_0000 => Ins::NoOperation,
-            0b1010_0001 => Ins::NoOperation,
-            0b1010_0010 => Ins::NoOperation,
-            0b1010_0011 => Ins::NoOperation,
-            0b1010_0100 => Ins::NoOperation,
-            0b1010_0101 => Ins::NoOperation,
-            0b1010_0110 => Ins::NoOperation,
-            0b1010_0111 => Ins::NoOperation,
-            0b1010_1000 => Ins::NoOperation,
-            0b1010_1001 => Ins::NoOperation,
-            0b1010_1010 => Ins::NoOperation,
-            0b1010_1011 => Ins::NoOperation,
-            0b1010_1100 => Ins::NoOperation,
-            0b1010_1101 => Ins::NoOperation,
-            0b1010_1110 => Ins::NoOperation,
-            0b1010_1111 => Ins::NoOperation,
+        // unused       -- 1010_xxxx
+        0b1010_0000 => Ins::NoOperation,
+        0b1010_0001 => Ins::NoOperation,
+        0b1010_0010 => Ins::NoOperation,
+        0b1010_0011 => Ins::NoOperation,
+        0b1010_0100 => Ins::NoOperation,
+        0b1010_0101 => Ins::NoOperation,
+        0b1010_0110 => Ins::NoOperation,
+        0b1010_0111 => Ins::NoOperation,
+        0b1010_1000 => Ins::NoOperation,
+        0b1010_1001 => Ins::NoOperation,
+        0b1010_1010 => Ins::NoOperation,
+        0b1010_1011 => Ins::NoOperation,
+        0b1010_1100 => Ins::NoOperation,
+        0b1010_1101 => Ins::NoOperation,
+        0b1010_1110 => Ins::NoOperation,
+        0b1010_1111 => Ins::NoOperation,
 
-            // Memory       -- 1011_xxxx
-            0b1011_0000 => Ins::Literal { len: Len64::L08 },
-            0b1011_0001 => Ins::Literal { len: Len64::L16 },
-            0b1011_0010 => Ins::Literal { len: Len64::L32 },
-            0b1011_0011 => Ins::Literal { len: Len64::L64 },
-            0b1011_0100 => Ins::Address { len: Len64::L08 },
-            0b1011_0101 => Ins::Address { len: Len64::L16 },
-            0b1011_0110 => Ins::Address { len: Len64::L32 },
-            0b1011_0111 => Ins::Address { len: Len64::L64 },
-            0b1011_1000 => Ins::Store { len: Len64::L08 },
-            0b1011_1001 => Ins::Store { len: Len64::L16 },
-            0b1011_1010 => Ins::Store { len: Len64::L32 },
-            0b1011_1011 => Ins::Store { len: Len64::L64 },
-            0b1011_1100 => Ins::Load { len: Len64::L08 },
-            0b1011_1101 => Ins::Load { len: Len64::L16 },
-            0b1011_1110 => Ins::Load { len: Len64::L32 },
-            0b1011_1111 => Ins::Load { len: Len64::L64 },
+        // Memory       -- 1011_xxxx
+        0b1011_0000 => Ins::Literal { len: Len64::L08 },
+        0b1011_0001 => Ins::Literal { len: Len64::L16 },
+        0b1011_0010 => Ins::Literal { len: Len64::L32 },
+        0b1011_0011 => Ins::Literal { len: Len64::L64 },
+        0b1011_0100 => Ins::Address { len: Len64::L08 },
+        0b1011_0101 => Ins::Address { len: Len64::L16 },
+        0b1011_0110 => Ins::Address { len: Len64::L32 },
+        0b1011_0111 => Ins::Address { len: Len64::L64 },
+        0b1011_1000 => Ins::Store { len: Len64::L08 },
+        0b1011_1001 => Ins::Store { len: Len64::L16 },
+        0b1011_1010 => Ins::Store { len: Len64::L32 },
+        0b1011_1011 => Ins::Store { len: Len64::L64 },
+        0b1011_1100 => Ins::Load { len: Len64::L08 },
+        0b1011_1101 => Ins::Load { len: Len64::L16 },
+        0b1011_1110 => Ins::Load { len: Len64::L32 },
+        0b1011_1111 => Ins::Load { len: Len64::L64 },
 
-            // Stack Ops    -- 11xx_xxxx
-            // Data Stack   -- 1100_xxxx
-            0b1100_0000 => Ins::DuplicateData { len: Len64::L08 },
-            0b1100_0001 => Ins::DuplicateData { len: Len64::L16 },
-            0b1100_0010 => Ins::DuplicateData { len: Len64::L32 },
-            0b1100_0011 => Ins::DuplicateData { len: Len64::L64 },
-            0b1100_0100 => Ins::CopyDataToSwap { len: Len64::L08 },
-            0b1100_0101 => Ins::CopyDataToSwap { len: Len64::L16 },
-            0b1100_0110 => Ins::CopyDataToSwap { len: Len64::L32 },
-            0b1100_0111 => Ins::CopyDataToSwap { len: Len64::L64 },
-            0b1100_1000 => Ins::CopyDataToReturn { len: Len64::L08 },
-            0b1100_1001 => Ins::CopyDataToReturn { len: Len64::L16 },
-            0b1100_1010 => Ins::CopyDataToReturn { len: Len64::L32 },
-            0b1100_1011 => Ins::CopyDataToReturn { len: Len64::L64 },
-            0b1100_1100 => Ins::CopyDataToHold { len: Len64::L08 },
-            0b1100_1101 => Ins::CopyDataToHold { len: Len64::L16 },
-            0b1100_1110 => Ins::CopyDataToHold { len: Len64::L32 },
-            0b1100_1111 => Ins::CopyDataToHold { len: Len64::L64 },
+        // Stack Ops    -- 11xx_xxxx
+        // Data Stack   -- 1100_xxxx
+        0b1100_0000 => Ins::DuplicateData { len: Len64::L08 },
+        0b1100_0001 => Ins::DuplicateData { len: Len64::L16 },
+        0b1100_0010 => Ins::DuplicateData { len: Len64::L32 },
+        0b1100_0011 => Ins::DuplicateData { len: Len64::L64 },
+        0b1100_0100 => Ins::CopyDataToSwap { len: Len64::L08 },
+        0b1100_0101 => Ins::CopyDataToSwap { len: Len64::L16 },
+        0b1100_0110 => Ins::CopyDataToSwap { len: Len64::L32 },
+        0b1100_0111 => Ins::CopyDataToSwap { len: Len64::L64 },
+        0b1100_1000 => Ins::CopyDataToReturn { len: Len64::L08 },
+        0b1100_1001 => Ins::CopyDataToReturn { len: Len64::L16 },
+        0b1100_1010 => Ins::CopyDataToReturn { len: Len64::L32 },
+        0b1100_1011 => Ins::CopyDataToReturn { len: Len64::L64 },
+        0b1100_1100 => Ins::CopyDataToHold { len: Len64::L08 },
+        0b1100_1101 => Ins::CopyDataToHold { len: Len64::L16 },
+        0b1100_1110 => Ins::CopyDataToHold { len: Len64::L32 },
+        0b1100_1111 => Ins::CopyDataToHold { len: Len64::L64 },
 
-            // Swap Stack   -- 1101_xxxx
-            0b1101_0000 => Ins::CopySwapToData { len: Len64::L08 },
-            0b1101_0001 => Ins::CopySwapToData { len: Len64::L16 },
-            0b1101_0010 => Ins::CopySwapToData { len: Len64::L32 },
-            0b1101_0011 => Ins::CopySwapToData { len: Len64::L64 },
-            0b1101_0100 => Ins::DuplicateSwap { len: Len64::L08 },
-            0b1101_0101 => Ins::DuplicateSwap { len: Len64::L16 },
-            0b1101_0110 => Ins::DuplicateSwap { len: Len64::L32 },
-            0b1101_0111 => Ins::DuplicateSwap { len: Len64::L64 },
-            0b1101_1000 => Ins::CopySwapToReturn { len: Len64::L08 },
-            0b1101_1001 => Ins::CopySwapToReturn { len: Len64::L16 },
-            0b1101_1010 => Ins::CopySwapToReturn { len: Len64::L32 },
-            0b1101_1011 => Ins::CopySwapToReturn { len: Len64::L64 },
-            0b1101_1100 => Ins::CopySwapToHold { len: Len64::L08 },
-            0b1101_1101 => Ins::CopySwapToHold { len: Len64::L16 },
-            0b1101_1110 => Ins::CopySwapToHold { len: Len64::L32 },
-            0b1101_1111 => Ins::CopySwapToHold { len: Len64::L64 },
+        // Swap Stack   -- 1101_xxxx
+        0b1101_0000 => Ins::CopySwapToData { len: Len64::L08 },
+        0b1101_0001 => Ins::CopySwapToData { len: Len64::L16 },
+        0b1101_0010 => Ins::CopySwapToData { len: Len64::L32 },
+        0b1101_0011 => Ins::CopySwapToData { len: Len64::L64 },
+        0b1101_0100 => Ins::DuplicateSwap { len: Len64::L08 },
+        0b1101_0101 => Ins::DuplicateSwap { len: Len64::L16 },
+        0b1101_0110 => Ins::DuplicateSwap { len: Len64::L32 },
+        0b1101_0111 => Ins::DuplicateSwap { len: Len64::L64 },
+        0b1101_1000 => Ins::CopySwapToReturn { len: Len64::L08 },
+        0b1101_1001 => Ins::CopySwapToReturn { len: Len64::L16 },
+        0b1101_1010 => Ins::CopySwapToReturn { len: Len64::L32 },
+        0b1101_1011 => Ins::CopySwapToReturn { len: Len64::L64 },
+        0b1101_1100 => Ins::CopySwapToHold { len: Len64::L08 },
+        0b1101_1101 => Ins::CopySwapToHold { len: Len64::L16 },
+        0b1101_1110 => Ins::CopySwapToHold { len: Len64::L32 },
+        0b1101_1111 => Ins::CopySwapToHold { len: Len64::L64 },
 
-            // Return Stack   -- 1110_xxxx
-            0b1110_0000 => Ins::CopyReturnToData { len: Len64::L08 },
-            0b1110_0001 => Ins::CopyReturnToData { len: Len64::L16 },
-            0b1110_0010 => Ins::CopyReturnToData { len: Len64::L32 },
-            0b1110_0011 => Ins::CopyReturnToData { len: Len64::L64 },
-            0b1110_0100 => Ins::CopyReturnToSwap { len: Len64::L08 },
-            0b1110_0101 => Ins::CopyReturnToSwap { len: Len64::L16 },
-            0b1110_0110 => Ins::CopyReturnToSwap { len: Len64::L32 },
-            0b1110_0111 => Ins::CopyReturnToSwap { len: Len64::L64 },
-            0b1110_1000 => Ins::DuplicateReturn { len: Len64::L08 },
-            0b1110_1001 => Ins::DuplicateReturn { len: Len64::L16 },
-            0b1110_1010 => Ins::DuplicateReturn { len: Len64::L32 },
-            0b1110_1011 => Ins::DuplicateReturn { len: Len64::L64 },
-            0b1110_1100 => Ins::CopyReturnToHold { len: Len64::L08 },
-            0b1110_1101 => Ins::CopyReturnToHold { len: Len64::L16 },
-            0b1110_1110 => Ins::CopyReturnToHold { len: Len64::L32 },
-            0b1110_1111 => Ins::CopyReturnToHold { len: Len64::L64 },
+        // Return Stack   -- 1110_xxxx
+        0b1110_0000 => Ins::CopyReturnToData { len: Len64::L08 },
+        0b1110_0001 => Ins::CopyReturnToData { len: Len64::L16 },
+        0b1110_0010 => Ins::CopyReturnToData { len: Len64::L32 },
+        0b1110_0011 => Ins::CopyReturnToData { len: Len64::L64 },
+        0b1110_0100 => Ins::CopyReturnToSwap { len: Len64::L08 },
+        0b1110_0101 => Ins::CopyReturnToSwap { len: Len64::L16 },
+        0b1110_0110 => Ins::CopyReturnToSwap { len: Len64::L32 },
+        0b1110_0111 => Ins::CopyReturnToSwap { len: Len64::L64 },
+        0b1110_1000 => Ins::DuplicateReturn { len: Len64::L08 },
+        0b1110_1001 => Ins::DuplicateReturn { len: Len64::L16 },
+        0b1110_1010 => Ins::DuplicateReturn { len: Len64::L32 },
+        0b1110_1011 => Ins::DuplicateReturn { len: Len64::L64 },
+        0b1110_1100 => Ins::CopyReturnToHold { len: Len64::L08 },
+        0b1110_1101 => Ins::CopyReturnToHold { len: Len64::L16 },
+        0b1110_1110 => Ins::CopyReturnToHold { len: Len64::L32 },
+        0b1110_1111 => Ins::CopyReturnToHold { len: Len64::L64 },
 
-            // Hold and Drop    -- 1111_xxxx
-            0b1111_0000 => Ins::CopyHoldToData { len: Len64::L08 },
-            0b1111_0001 => Ins::CopyHoldToData { len: Len64::L16 },
-            0b1111_0010 => Ins::CopyHoldToData { len: Len64::L32 },
-            0b1111_0011 => Ins::CopyHoldToData { len: Len64::L64 },
-            0b1111_0100 => Ins::CopyHoldToSwap { len: Len64::L08 },
-            0b1111_0101 => Ins::CopyHoldToSwap { len: Len64::L16 },
-            0b1111_0110 => Ins::CopyHoldToSwap { len: Len64::L32 },
-            0b1111_0111 => Ins::CopyHoldToSwap { len: Len64::L64 },
-            0b1111_1000 => Ins::CopyHoldToReturn { len: Len64::L08 },
-            0b1111_1001 => Ins::CopyHoldToReturn { len: Len64::L16 },
-            0b1111_1010 => Ins::CopyHoldToReturn { len: Len64::L32 },
-            0b1111_1011 => Ins::CopyHoldToReturn { len: Len64::L64 },
-            0b1111_1100 => Ins::DropData,
-            0b1111_1101 => Ins::DropSwap,
-            0b1111_1110 => Ins::DropReturn,
-            0b1111_1111 => Ins::NoOperation,
-        }
+        // Hold and Drop    -- 1111_xxxx
+        0b1111_0000 => Ins::CopyHoldToData { len: Len64::L08 },
+        0b1111_0001 => Ins::CopyHoldToData { len: Len64::L16 },
+        0b1111_0010 => Ins::CopyHoldToData { len: Len64::L32 },
+        0b1111_0011 => Ins::CopyHoldToData { len: Len64::L64 },
+        0b1111_0100 => Ins::CopyHoldToSwap { len: Len64::L08 },
+        0b1111_0101 => Ins::CopyHoldToSwap { len: Len64::L16 },
+        0b1111_0110 => Ins::CopyHoldToSwap { len: Len64::L32 },
+        0b1111_0111 => Ins::CopyHoldToSwap { len: Len64::L64 },
+        0b1111_1000 => Ins::CopyHoldToReturn { len: Len64::L08 },
+        0b1111_1001 => Ins::CopyHoldToReturn { len: Len64::L16 },
+        0b1111_1010 => Ins::CopyHoldToReturn { len: Len64::L32 },
+        0b1111_1011 => Ins::CopyHoldToReturn { len: Len64::L64 },
+        0b1111_1100 => Ins::DropData,
+        0b1111_1101 => Ins::DropSwap,
+        0b1111_1110 => Ins::DropReturn,
+        0b1111_1111 => Ins::NoOperation,
     }
 }
 
@@ -461,18 +500,18 @@ pub fn opcode_to_str(byte: u8) -> &'static str {
         // 0b00000_0001,
         // 0b00000_0010,
         // 0b00000_0011,
-        0b000001_00 => "CAL8",  // => Ins::Call { len: Len16::L8 },
-        0b000001_01 => "CAL16", // => Ins::Call { len: Len16::L16 },
-        0b000001_10 => "RTN8",  // => Ins::Return { len: Len16::L16 },
-        0b000001_11 => "RTN16", // => Ins::Return { len: Len16::L16 },
-        0b00001_000 => "JMP8",  //=> Ins::Jump { con: false, rel: false, len: Len16::L8, },
-        0b00001_001 => "JMP16", //=> Ins::Jump { con: false, rel: false, len: Len16::L16, },
-        0b00001_010 => "JPR8",  //=> Ins::Jump { con: false, rel: true, len: Len16::L8, },
-        0b00001_011 => "JPR16", //=> Ins::Jump { con: false, rel: true, len: Len16::L16, },
-        0b00001_100 => "JPC8",  //=> Ins::Jump { con: true, rel: false, len: Len16::L8, },
-        0b00001_101 => "JPC16", //=> Ins::Jump { con: true, rel: false, len: Len16::L16, },
-        0b00001_110 => "JCR8",  //=> Ins::Jump { con: true, rel: true, len: Len16::L8, },
-        0b00001_111 => "JCR16", //=> Ins::Jump { con: true, rel: true, len: Len16::L16, },
+        0b0000_0100 => "CAL8",  // => Ins::Call { len: Len16::L8 },
+        0b0000_0101 => "CAL16", // => Ins::Call { len: Len16::L16 },
+        0b0000_0110 => "RTN8",  // => Ins::Return { len: Len16::L16 },
+        0b0000_0111 => "RTN16", // => Ins::Return { len: Len16::L16 },
+        0b0000_1000 => "JMP8",  //=> Ins::Jump { con: false, rel: false, len: Len16::L8, },
+        0b0000_1001 => "JMP16", //=> Ins::Jump { con: false, rel: false, len: Len16::L16, },
+        0b0000_1010 => "JPR8",  //=> Ins::Jump { con: false, rel: true, len: Len16::L8, },
+        0b0000_1011 => "JPR16", //=> Ins::Jump { con: false, rel: true, len: Len16::L16, },
+        0b0000_1100 => "JPC8",  //=> Ins::Jump { con: true, rel: false, len: Len16::L8, },
+        0b0000_1101 => "JPC16", //=> Ins::Jump { con: true, rel: false, len: Len16::L16, },
+        0b0000_1110 => "JCR8",  //=> Ins::Jump { con: true, rel: true, len: Len16::L8, },
+        0b0000_1111 => "JCR16", //=> Ins::Jump { con: true, rel: true, len: Len16::L16, },
         0b0001_0000 => "ADF32", // => Ins::AddF { len: LenF::L32 },
         0b0001_0001 => "ADF64", // => Ins::AddF { len: LenF::L64 },
         0b0001_0010 => "SBF32", // => Ins::SubtractF { len: LenF::L32 },
@@ -489,102 +528,102 @@ pub fn opcode_to_str(byte: u8) -> &'static str {
         // 0b0001_1101,
         // 0b0001_1110,
         // 0b0001_1111,
-        // 0b001_00000,
-        // 0b001_00001,
-        // 0b001_00010,
-        // 0b001_00011,
-        // 0b001_00100,
-        // 0b001_00101,
-        // 0b001_00110,
-        // 0b001_00111,
-        // 0b001_01000,
-        // 0b001_01001,
-        // 0b001_01010,
-        // 0b001_01011,
-        // 0b001_01100,
-        // 0b001_01101,
-        // 0b001_01110,
-        // 0b001_01111,
-        // 0b001_10000,
-        // 0b001_10001,
-        // 0b001_10010,
-        // 0b001_10011,
-        // 0b001_10100,
-        // 0b001_10101,
-        // 0b001_10110,
-        // 0b001_10111,
-        // 0b001_11000,
-        // 0b001_11001,
-        // 0b001_11010,
-        // 0b001_11011,
-        // 0b001_11100,
-        // 0b001_11101,
-        // 0b001_11110,
-        // 0b001_11111,
-        0b010_00000 => "AND8",  // => Ins::And { len: Len64::L08 },
-        0b010_00001 => "AND16", // => Ins::And { len: Len64::L16 },
-        0b010_00010 => "AND32", // => Ins::And { len: Len64::L32 },
-        0b010_00011 => "AND64", // => Ins::And { len: Len64::L64 },
-        0b010_00100 => "OR8",   // => Ins::Or { len: Len64::L08 },
-        0b010_00101 => "OR16",  // => Ins::Or { len: Len64::L16 },
-        0b010_00110 => "OR32",  // => Ins::Or { len: Len64::L32 },
-        0b010_00111 => "OR64",  // => Ins::Or { len: Len64::L64 },
-        0b010_01000 => "XOR8",  // => Ins::Xor { len: Len64::L08 },
-        0b010_01001 => "XOR16", // => Ins::Xor { len: Len64::L16 },
-        0b010_01010 => "XOR32", // => Ins::Xor { len: Len64::L32 },
-        0b010_01011 => "XOR64", // => Ins::Xor { len: Len64::L64 },
-        0b010_01100 => "NOT8",  // => Ins::Not { len: Len64::L08 },
-        0b010_01101 => "NOT16", // => Ins::Not { len: Len64::L16 },
-        0b010_01110 => "NOT32", // => Ins::Not { len: Len64::L32 },
-        0b010_01111 => "NOT64", // => Ins::Not { len: Len64::L64 },
-        0b010_10000 => "BSL8",  // => Ins::ShiftL { len: Len64::L08 },
-        0b010_10001 => "BSL16", // => Ins::ShiftL { len: Len64::L16 },
-        0b010_10010 => "BSL32", // => Ins::ShiftL { len: Len64::L32 },
-        0b010_10011 => "BSL64", // => Ins::ShiftL { len: Len64::L64 },
-        0b010_10100 => "BSR8",  // => Ins::ShiftR { len: Len64::L08 },
-        0b010_10101 => "BSR16", // => Ins::ShiftR { len: Len64::L16 },
-        0b010_10110 => "BSR32", // => Ins::ShiftR { len: Len64::L32 },
-        0b010_10111 => "BSR64", // => Ins::ShiftR { len: Len64::L64 },
-        // 0b010_11000,
-        // 0b010_11001,
-        // 0b010_11010,
-        // 0b010_11011,
-        // 0b010_11100,
-        // 0b010_11101,
-        // 0b010_11110,
-        // 0b010_11111,
-        0b011_00000 => "ADD8",       // => Ins::Add { len: Len64::L08 },
-        0b011_00001 => "ADD16",      // => Ins::Add { len: Len64::L16 },
-        0b011_00010 => "ADD32",      // => Ins::Add { len: Len64::L32 },
-        0b011_00011 => "ADD64",      // => Ins::Add { len: Len64::L64 },
-        0b011_00100 => "SUB8",       // => Ins::Subtract { len: Len64::L08 },
-        0b011_00101 => "SUB16",      // => Ins::Subtract { len: Len64::L16 },
-        0b011_00110 => "SUB32",      // => Ins::Subtract { len: Len64::L32 },
-        0b011_00111 => "SUB64",      // => Ins::Subtract { len: Len64::L64 },
-        0b011_01000 => "MUL8",       // => Ins::Multiply { len: Len64::L08 },
-        0b011_01001 => "MUL16",      // => Ins::Multiply { len: Len64::L16 },
-        0b011_01010 => "MUL32",      // => Ins::Multiply { len: Len64::L32 },
-        0b011_01011 => "MUL64",      // => Ins::Multiply { len: Len64::L64 },
-        0b011_01100 => "DIV8",       // => Ins::Divide { len: Len64::L08 },
-        0b011_01101 => "DIV16",      // => Ins::Divide { len: Len64::L16 },
-        0b011_01110 => "DIV32",      // => Ins::Divide { len: Len64::L32 },
-        0b011_01111 => "DIV64",      // => Ins::Divide { len: Len64::L64 },
-        0b011_10000 => "GRT8",       // => Ins::Greater { len: Len64::L08 },
-        0b011_10001 => "GRT16",      // => Ins::Greater { len: Len64::L16 },
-        0b011_10010 => "GRT32",      // => Ins::Greater { len: Len64::L32 },
-        0b011_10011 => "GRT64",      // => Ins::Greater { len: Len64::L64 },
-        0b011_10100 => "LST8",       // => Ins::Less { len: Len64::L08 },
-        0b011_10101 => "LST16",      // => Ins::Less { len: Len64::L16 },
-        0b011_10110 => "LST32",      // => Ins::Less { len: Len64::L32 },
-        0b011_10111 => "LST64",      // => Ins::Less { len: Len64::L64 },
-        0b011_11000 => "EQU8",       // => Ins::Equal { len: Len64::L08 },
-        0b011_11001 => "EQU16",      // => Ins::Equal { len: Len64::L16 },
-        0b011_11010 => "EQU32",      // => Ins::Equal { len: Len64::L32 },
-        0b011_11011 => "EQU64",      // => Ins::Equal { len: Len64::L64 },
-        0b011_11100 => "NEQ8",       // => Ins::NotEqual { len: Len64::L08 },
-        0b011_11101 => "NEQ16",      // => Ins::NotEqual { len: Len64::L16 },
-        0b011_11110 => "NEQ32",      // => Ins::NotEqual { len: Len64::L32 },
-        0b011_11111 => "NEQ64",      // => Ins::NotEqual { len: Len64::L64 },
+        0b0010_0000 => "SYS", // => Ins::SystemCall,
+        0b0010_0001 => "SRT", // => Ins::SystemReturn,
+        0b0010_0010 => "INE", // => Ins::InterruptEnable,
+        0b0010_0011 => "IND", // => Ins::InterruptDisable,
+        0b0010_0100 => "SVC", // => Ins::SetSystemVector,
+        0b0010_0101 => "DVC", // => Ins::SetDeviceVector,
+        0b0010_0110 => "RGN", // => Ins::SetRegion,
+        0b0010_0111 => "SKS", // => Ins::SetStack,
+        0b0010_1000 => "SKR", // => Ins::ReadStack,
+        0b0010_1001 => "CXS", // => Ins::SaveContext,
+        0b0010_1010 => "CXL", // => Ins::LoadContext,
+        0b0010_1011 => "CXW", // => Ins::SwitchContext,
+        0b0010_1100 => "CTH", // => Ins::Catch,
+        0b0010_1101 => "THR", // => Ins::Throw,
+        0b0010_1110 => "CTE", // => Ins::EndCatch,
+        0b0010_1111 => "CLH", // => Ins::CallHash,
+        0b0011_0000 => "ADH", // => Ins::AddressHash,
+        // 0b0011_0001,
+        // 0b0011_0010,
+        // 0b0011_0011,
+        // 0b0011_0100,
+        // 0b0011_0101,
+        // 0b0011_0110,
+        // 0b0011_0111,
+        // 0b0011_1000,
+        // 0b0011_1001,
+        // 0b0011_1010,
+        // 0b0011_1011,
+        // 0b0011_1100,
+        // 0b0011_1101,
+        // 0b0011_1110,
+        // 0b0011_1111,
+        0b0100_0000 => "AND8",  // => Ins::And { len: Len64::L08 },
+        0b0100_0001 => "AND16", // => Ins::And { len: Len64::L16 },
+        0b0100_0010 => "AND32", // => Ins::And { len: Len64::L32 },
+        0b0100_0011 => "AND64", // => Ins::And { len: Len64::L64 },
+        0b0100_0100 => "OR8",   // => Ins::Or { len: Len64::L08 },
+        0b0100_0101 => "OR16",  // => Ins::Or { len: Len64::L16 },
+        0b0100_0110 => "OR32",  // => Ins::Or { len: Len64::L32 },
+        0b0100_0111 => "OR64",  // => Ins::Or { len: Len64::L64 },
+        0b0100_1000 => "XOR8",  // => Ins::Xor { len: Len64::L08 },
+        0b0100_1001 => "XOR16", // => Ins::Xor { len: Len64::L16 },
+        0b0100_1010 => "XOR32", // => Ins::Xor { len: Len64::L32 },
+        0b0100_1011 => "XOR64", // => Ins::Xor { len: Len64::L64 },
+        0b0100_1100 => "NOT8",  // => Ins::Not { len: Len64::L08 },
+        0b0100_1101 => "NOT16", // => Ins::Not { len: Len64::L16 },
+        0b0100_1110 => "NOT32", // => Ins::Not { len: Len64::L32 },
+        0b0100_1111 => "NOT64", // => Ins::Not { len: Len64::L64 },
+        0b0101_0000 => "BSL8",  // => Ins::ShiftL { len: Len64::L08 },
+        0b0101_0001 => "BSL16", // => Ins::ShiftL { len: Len64::L16 },
+        0b0101_0010 => "BSL32", // => Ins::ShiftL { len: Len64::L32 },
+        0b0101_0011 => "BSL64", // => Ins::ShiftL { len: Len64::L64 },
+        0b0101_0100 => "BSR8",  // => Ins::ShiftR { len: Len64::L08 },
+        0b0101_0101 => "BSR16", // => Ins::ShiftR { len: Len64::L16 },
+        0b0101_0110 => "BSR32", // => Ins::ShiftR { len: Len64::L32 },
+        0b0101_0111 => "BSR64", // => Ins::ShiftR { len: Len64::L64 },
+        // 0b0101_1000,
+        // 0b0101_1001,
+        // 0b0101_1010,
+        // 0b0101_1011,
+        // 0b0101_1100,
+        // 0b0101_1101,
+        // 0b0101_1110,
+        // 0b0101_1111,
+        0b0110_0000 => "ADD8",       // => Ins::Add { len: Len64::L08 },
+        0b0110_0001 => "ADD16",      // => Ins::Add { len: Len64::L16 },
+        0b0110_0010 => "ADD32",      // => Ins::Add { len: Len64::L32 },
+        0b0110_0011 => "ADD64",      // => Ins::Add { len: Len64::L64 },
+        0b0110_0100 => "SUB8",       // => Ins::Subtract { len: Len64::L08 },
+        0b0110_0101 => "SUB16",      // => Ins::Subtract { len: Len64::L16 },
+        0b0110_0110 => "SUB32",      // => Ins::Subtract { len: Len64::L32 },
+        0b0110_0111 => "SUB64",      // => Ins::Subtract { len: Len64::L64 },
+        0b0110_1000 => "MUL8",       // => Ins::Multiply { len: Len64::L08 },
+        0b0110_1001 => "MUL16",      // => Ins::Multiply { len: Len64::L16 },
+        0b0110_1010 => "MUL32",      // => Ins::Multiply { len: Len64::L32 },
+        0b0110_1011 => "MUL64",      // => Ins::Multiply { len: Len64::L64 },
+        0b0110_1100 => "DIV8",       // => Ins::Divide { len: Len64::L08 },
+        0b0110_1101 => "DIV16",      // => Ins::Divide { len: Len64::L16 },
+        0b0110_1110 => "DIV32",      // => Ins::Divide { len: Len64::L32 },
+        0b0110_1111 => "DIV64",      // => Ins::Divide { len: Len64::L64 },
+        0b0111_0000 => "GRT8",       // => Ins::Greater { len: Len64::L08 },
+        0b0111_0001 => "GRT16",      // => Ins::Greater { len: Len64::L16 },
+        0b0111_0010 => "GRT32",      // => Ins::Greater { len: Len64::L32 },
+        0b0111_0011 => "GRT64",      // => Ins::Greater { len: Len64::L64 },
+        0b0111_0100 => "LST8",       // => Ins::Less { len: Len64::L08 },
+        0b0111_0101 => "LST16",      // => Ins::Less { len: Len64::L16 },
+        0b0111_0110 => "LST32",      // => Ins::Less { len: Len64::L32 },
+        0b0111_0111 => "LST64",      // => Ins::Less { len: Len64::L64 },
+        0b0111_1000 => "EQU8",       // => Ins::Equal { len: Len64::L08 },
+        0b0111_1001 => "EQU16",      // => Ins::Equal { len: Len64::L16 },
+        0b0111_1010 => "EQU32",      // => Ins::Equal { len: Len64::L32 },
+        0b0111_1011 => "EQU64",      // => Ins::Equal { len: Len64::L64 },
+        0b0111_1100 => "NEQ8",       // => Ins::NotEqual { len: Len64::L08 },
+        0b0111_1101 => "NEQ16",      // => Ins::NotEqual { len: Len64::L16 },
+        0b0111_1110 => "NEQ32",      // => Ins::NotEqual { len: Len64::L32 },
+        0b0111_1111 => "NEQ64",      // => Ins::NotEqual { len: Len64::L64 },
         0b1000_0000 => "DMA_TEST_1", // => Ins::DMARead,
         // 0b1000_0001,
         // 0b1000_0010,
@@ -722,18 +761,18 @@ pub fn str_to_opcode(s: &str) -> Option<u8> {
         // 0b00000_0001,
         // 0b00000_0010,
         // 0b00000_0011,
-        "CAL8" => 0b000001_00,  // => Ins::Call { len: Len16::L8 },
-        "CAL16" => 0b000001_01, // => Ins::Call { len: Len16::L16 },
-        "RTN8" => 0b000001_10,  // => Ins::Return { len: Len16::L16 },
-        "RTN16" => 0b000001_11, // => Ins::Return { len: Len16::L16 },
-        "JMP8" => 0b00001_000,  //=> Ins::Jump { con: false, rel: false, len: Len16::L8, },
-        "JMP16" => 0b00001_001, //=> Ins::Jump { con: false, rel: false, len: Len16::L16, },
-        "JPR8" => 0b00001_010,  //=> Ins::Jump { con: false, rel: true, len: Len16::L8, },
-        "JPR16" => 0b00001_011, //=> Ins::Jump { con: false, rel: true, len: Len16::L16, },
-        "JPC8" => 0b00001_100,  //=> Ins::Jump { con: true, rel: false, len: Len16::L8, },
-        "JPC16" => 0b00001_101, //=> Ins::Jump { con: true, rel: false, len: Len16::L16, },
-        "JCR8" => 0b00001_110,  //=> Ins::Jump { con: true, rel: true, len: Len16::L8, },
-        "JCR16" => 0b00001_111, //=> Ins::Jump { con: true, rel: true, len: Len16::L16, },
+        "CAL8" => 0b0000_0100,  // => Ins::Call { len: Len16::L8 },
+        "CAL16" => 0b0000_0101, // => Ins::Call { len: Len16::L16 },
+        "RTN8" => 0b0000_0110,  // => Ins::Return { len: Len16::L16 },
+        "RTN16" => 0b0000_0111, // => Ins::Return { len: Len16::L16 },
+        "JMP8" => 0b0000_1000,  //=> Ins::Jump { con: false, rel: false, len: Len16::L8, },
+        "JMP16" => 0b0000_1001, //=> Ins::Jump { con: false, rel: false, len: Len16::L16, },
+        "JPR8" => 0b0000_1010,  //=> Ins::Jump { con: false, rel: true, len: Len16::L8, },
+        "JPR16" => 0b0000_1011, //=> Ins::Jump { con: false, rel: true, len: Len16::L16, },
+        "JPC8" => 0b0000_1100,  //=> Ins::Jump { con: true, rel: false, len: Len16::L8, },
+        "JPC16" => 0b0000_1101, //=> Ins::Jump { con: true, rel: false, len: Len16::L16, },
+        "JCR8" => 0b0000_1110,  //=> Ins::Jump { con: true, rel: true, len: Len16::L8, },
+        "JCR16" => 0b0000_1111, //=> Ins::Jump { con: true, rel: true, len: Len16::L16, },
         "ADF32" => 0b0001_0000, // => Ins::AddF { len: LenF::L32 },
         "ADF64" => 0b0001_0001, // => Ins::AddF { len: LenF::L64 },
         "SBF32" => 0b0001_0010, // => Ins::SubtractF { len: LenF::L32 },
@@ -750,102 +789,102 @@ pub fn str_to_opcode(s: &str) -> Option<u8> {
         // 0b0001_1101,
         // 0b0001_1110,
         // 0b0001_1111,
-        // 0b001_00000,
-        // 0b001_00001,
-        // 0b001_00010,
-        // 0b001_00011,
-        // 0b001_00100,
-        // 0b001_00101,
-        // 0b001_00110,
-        // 0b001_00111,
-        // 0b001_01000,
-        // 0b001_01001,
-        // 0b001_01010,
-        // 0b001_01011,
-        // 0b001_01100,
-        // 0b001_01101,
-        // 0b001_01110,
-        // 0b001_01111,
-        // 0b001_10000,
-        // 0b001_10001,
-        // 0b001_10010,
-        // 0b001_10011,
-        // 0b001_10100,
-        // 0b001_10101,
-        // 0b001_10110,
-        // 0b001_10111,
-        // 0b001_11000,
-        // 0b001_11001,
-        // 0b001_11010,
-        // 0b001_11011,
-        // 0b001_11100,
-        // 0b001_11101,
-        // 0b001_11110,
-        // 0b001_11111,
-        "AND8" => 0b010_00000,  // => Ins::And { len: Len64::L08 },
-        "AND16" => 0b010_00001, // => Ins::And { len: Len64::L16 },
-        "AND32" => 0b010_00010, // => Ins::And { len: Len64::L32 },
-        "AND64" => 0b010_00011, // => Ins::And { len: Len64::L64 },
-        "OR8" => 0b010_00100,   // => Ins::Or { len: Len64::L08 },
-        "OR16" => 0b010_00101,  // => Ins::Or { len: Len64::L16 },
-        "OR32" => 0b010_00110,  // => Ins::Or { len: Len64::L32 },
-        "OR64" => 0b010_00111,  // => Ins::Or { len: Len64::L64 },
-        "XOR8" => 0b010_01000,  // => Ins::Xor { len: Len64::L08 },
-        "XOR16" => 0b010_01001, // => Ins::Xor { len: Len64::L16 },
-        "XOR32" => 0b010_01010, // => Ins::Xor { len: Len64::L32 },
-        "XOR64" => 0b010_01011, // => Ins::Xor { len: Len64::L64 },
-        "NOT8" => 0b010_01100,  // => Ins::Not { len: Len64::L08 },
-        "NOT16" => 0b010_01101, // => Ins::Not { len: Len64::L16 },
-        "NOT32" => 0b010_01110, // => Ins::Not { len: Len64::L32 },
-        "NOT64" => 0b010_01111, // => Ins::Not { len: Len64::L64 },
-        "BSL8" => 0b010_10000,  // => Ins::ShiftL { len: Len64::L08 },
-        "BSL16" => 0b010_10001, // => Ins::ShiftL { len: Len64::L16 },
-        "BSL32" => 0b010_10010, // => Ins::ShiftL { len: Len64::L32 },
-        "BSL64" => 0b010_10011, // => Ins::ShiftL { len: Len64::L64 },
-        "BSR8" => 0b010_10100,  // => Ins::ShiftR { len: Len64::L08 },
-        "BSR16" => 0b010_10101, // => Ins::ShiftR { len: Len64::L16 },
-        "BSR32" => 0b010_10110, // => Ins::ShiftR { len: Len64::L32 },
-        "BSR64" => 0b010_10111, // => Ins::ShiftR { len: Len64::L64 },
-        // 0b010_11000,
-        // 0b010_11001,
-        // 0b010_11010,
-        // 0b010_11011,
-        // 0b010_11100,
-        // 0b010_11101,
-        // 0b010_11110,
-        // 0b010_11111,
-        "ADD8" => 0b011_00000,       // => Ins::Add { len: Len64::L08 },
-        "ADD16" => 0b011_00001,      // => Ins::Add { len: Len64::L16 },
-        "ADD32" => 0b011_00010,      // => Ins::Add { len: Len64::L32 },
-        "ADD64" => 0b011_00011,      // => Ins::Add { len: Len64::L64 },
-        "SUB8" => 0b011_00100,       // => Ins::Subtract { len: Len64::L08 },
-        "SUB16" => 0b011_00101,      // => Ins::Subtract { len: Len64::L16 },
-        "SUB32" => 0b011_00110,      // => Ins::Subtract { len: Len64::L32 },
-        "SUB64" => 0b011_00111,      // => Ins::Subtract { len: Len64::L64 },
-        "MUL8" => 0b011_01000,       // => Ins::Multiply { len: Len64::L08 },
-        "MUL16" => 0b011_01001,      // => Ins::Multiply { len: Len64::L16 },
-        "MUL32" => 0b011_01010,      // => Ins::Multiply { len: Len64::L32 },
-        "MUL64" => 0b011_01011,      // => Ins::Multiply { len: Len64::L64 },
-        "DIV8" => 0b011_01100,       // => Ins::Divide { len: Len64::L08 },
-        "DIV16" => 0b011_01101,      // => Ins::Divide { len: Len64::L16 },
-        "DIV32" => 0b011_01110,      // => Ins::Divide { len: Len64::L32 },
-        "DIV64" => 0b011_01111,      // => Ins::Divide { len: Len64::L64 },
-        "GRT8" => 0b011_10000,       // => Ins::Greater { len: Len64::L08 },
-        "GRT16" => 0b011_10001,      // => Ins::Greater { len: Len64::L16 },
-        "GRT32" => 0b011_10010,      // => Ins::Greater { len: Len64::L32 },
-        "GRT64" => 0b011_10011,      // => Ins::Greater { len: Len64::L64 },
-        "LST8" => 0b011_10100,       // => Ins::Less { len: Len64::L08 },
-        "LST16" => 0b011_10101,      // => Ins::Less { len: Len64::L16 },
-        "LST32" => 0b011_10110,      // => Ins::Less { len: Len64::L32 },
-        "LST64" => 0b011_10111,      // => Ins::Less { len: Len64::L64 },
-        "EQU8" => 0b011_11000,       // => Ins::Equal { len: Len64::L08 },
-        "EQU16" => 0b011_11001,      // => Ins::Equal { len: Len64::L16 },
-        "EQU32" => 0b011_11010,      // => Ins::Equal { len: Len64::L32 },
-        "EQU64" => 0b011_11011,      // => Ins::Equal { len: Len64::L64 },
-        "NEQ8" => 0b011_11100,       // => Ins::NotEqual { len: Len64::L08 },
-        "NEQ16" => 0b011_11101,      // => Ins::NotEqual { len: Len64::L16 },
-        "NEQ32" => 0b011_11110,      // => Ins::NotEqual { len: Len64::L32 },
-        "NEQ64" => 0b011_11111,      // => Ins::NotEqual { len: Len64::L64 },
+        "SYS" => 0b0010_0000, // => Ins::SystemCall,
+        "SRT" => 0b0010_0001, // => Ins::SystemReturn,
+        "INE" => 0b0010_0010, // => Ins::InterruptEnable,
+        "IND" => 0b0010_0011, // => Ins::InterruptDisable,
+        "SVC" => 0b0010_0100, // => Ins::SetSystemVector,
+        "DVC" => 0b0010_0101, // => Ins::SetDeviceVector,
+        "RGN" => 0b0010_0110, // => Ins::SetRegion,
+        "SKS" => 0b0010_0111, // => Ins::SetStack,
+        "SKR" => 0b0010_1000, // => Ins::ReadStack,
+        "CXS" => 0b0010_1001, // => Ins::SaveContext,
+        "CXL" => 0b0010_1010, // => Ins::LoadContext,
+        "CXW" => 0b0010_1011, // => Ins::SwitchContext,
+        "CTH" => 0b0010_1100, // => Ins::Catch,
+        "THR" => 0b0010_1101, // => Ins::Throw,
+        "CTE" => 0b0010_1110, // => Ins::EndCatch,
+        "CLH" => 0b0010_1111, // => Ins::CallHash,
+        "ADH" => 0b0011_0000, // => Ins::AddressHash,
+        // 0b0011_0001,
+        // 0b0011_0010,
+        // 0b0011_0011,
+        // 0b0011_0100,
+        // 0b0011_0101,
+        // 0b0011_0110,
+        // 0b0011_0111,
+        // 0b0011_1000,
+        // 0b0011_1001,
+        // 0b0011_1010,
+        // 0b0011_1011,
+        // 0b0011_1100,
+        // 0b0011_1101,
+        // 0b0011_1110,
+        // 0b0011_1111,
+        "AND8" => 0b0100_0000,  // => Ins::And { len: Len64::L08 },
+        "AND16" => 0b0100_0001, // => Ins::And { len: Len64::L16 },
+        "AND32" => 0b0100_0010, // => Ins::And { len: Len64::L32 },
+        "AND64" => 0b0100_0011, // => Ins::And { len: Len64::L64 },
+        "OR8" => 0b0100_0100,   // => Ins::Or { len: Len64::L08 },
+        "OR16" => 0b0100_0101,  // => Ins::Or { len: Len64::L16 },
+        "OR32" => 0b0100_0110,  // => Ins::Or { len: Len64::L32 },
+        "OR64" => 0b0100_0111,  // => Ins::Or { len: Len64::L64 },
+        "XOR8" => 0b0100_1000,  // => Ins::Xor { len: Len64::L08 },
+        "XOR16" => 0b0100_1001, // => Ins::Xor { len: Len64::L16 },
+        "XOR32" => 0b0100_1010, // => Ins::Xor { len: Len64::L32 },
+        "XOR64" => 0b0100_1011, // => Ins::Xor { len: Len64::L64 },
+        "NOT8" => 0b0100_1100,  // => Ins::Not { len: Len64::L08 },
+        "NOT16" => 0b0100_1101, // => Ins::Not { len: Len64::L16 },
+        "NOT32" => 0b0100_1110, // => Ins::Not { len: Len64::L32 },
+        "NOT64" => 0b0100_1111, // => Ins::Not { len: Len64::L64 },
+        "BSL8" => 0b0101_0000,  // => Ins::ShiftL { len: Len64::L08 },
+        "BSL16" => 0b0101_0001, // => Ins::ShiftL { len: Len64::L16 },
+        "BSL32" => 0b0101_0010, // => Ins::ShiftL { len: Len64::L32 },
+        "BSL64" => 0b0101_0011, // => Ins::ShiftL { len: Len64::L64 },
+        "BSR8" => 0b0101_0100,  // => Ins::ShiftR { len: Len64::L08 },
+        "BSR16" => 0b0101_0101, // => Ins::ShiftR { len: Len64::L16 },
+        "BSR32" => 0b0101_0110, // => Ins::ShiftR { len: Len64::L32 },
+        "BSR64" => 0b0101_0111, // => Ins::ShiftR { len: Len64::L64 },
+        // 0b0101_1000,
+        // 0b0101_1001,
+        // 0b0101_1010,
+        // 0b0101_1011,
+        // 0b0101_1100,
+        // 0b0101_1101,
+        // 0b0101_1110,
+        // 0b0101_1111,
+        "ADD8" => 0b0110_0000,       // => Ins::Add { len: Len64::L08 },
+        "ADD16" => 0b0110_0001,      // => Ins::Add { len: Len64::L16 },
+        "ADD32" => 0b0110_0010,      // => Ins::Add { len: Len64::L32 },
+        "ADD64" => 0b0110_0011,      // => Ins::Add { len: Len64::L64 },
+        "SUB8" => 0b0110_0100,       // => Ins::Subtract { len: Len64::L08 },
+        "SUB16" => 0b0110_0101,      // => Ins::Subtract { len: Len64::L16 },
+        "SUB32" => 0b0110_0110,      // => Ins::Subtract { len: Len64::L32 },
+        "SUB64" => 0b0110_0111,      // => Ins::Subtract { len: Len64::L64 },
+        "MUL8" => 0b0110_1000,       // => Ins::Multiply { len: Len64::L08 },
+        "MUL16" => 0b0110_1001,      // => Ins::Multiply { len: Len64::L16 },
+        "MUL32" => 0b0110_1010,      // => Ins::Multiply { len: Len64::L32 },
+        "MUL64" => 0b0110_1011,      // => Ins::Multiply { len: Len64::L64 },
+        "DIV8" => 0b0110_1100,       // => Ins::Divide { len: Len64::L08 },
+        "DIV16" => 0b0110_1101,      // => Ins::Divide { len: Len64::L16 },
+        "DIV32" => 0b0110_1110,      // => Ins::Divide { len: Len64::L32 },
+        "DIV64" => 0b0110_1111,      // => Ins::Divide { len: Len64::L64 },
+        "GRT8" => 0b0111_0000,       // => Ins::Greater { len: Len64::L08 },
+        "GRT16" => 0b0111_0001,      // => Ins::Greater { len: Len64::L16 },
+        "GRT32" => 0b0111_0010,      // => Ins::Greater { len: Len64::L32 },
+        "GRT64" => 0b0111_0011,      // => Ins::Greater { len: Len64::L64 },
+        "LST8" => 0b0111_0100,       // => Ins::Less { len: Len64::L08 },
+        "LST16" => 0b0111_0101,      // => Ins::Less { len: Len64::L16 },
+        "LST32" => 0b0111_0110,      // => Ins::Less { len: Len64::L32 },
+        "LST64" => 0b0111_0111,      // => Ins::Less { len: Len64::L64 },
+        "EQU8" => 0b0111_1000,       // => Ins::Equal { len: Len64::L08 },
+        "EQU16" => 0b0111_1001,      // => Ins::Equal { len: Len64::L16 },
+        "EQU32" => 0b0111_1010,      // => Ins::Equal { len: Len64::L32 },
+        "EQU64" => 0b0111_1011,      // => Ins::Equal { len: Len64::L64 },
+        "NEQ8" => 0b0111_1100,       // => Ins::NotEqual { len: Len64::L08 },
+        "NEQ16" => 0b0111_1101,      // => Ins::NotEqual { len: Len64::L16 },
+        "NEQ32" => 0b0111_1110,      // => Ins::NotEqual { len: Len64::L32 },
+        "NEQ64" => 0b0111_1111,      // => Ins::NotEqual { len: Len64::L64 },
         "DMA_TEST_1" => 0b1000_0000, // => Ins::DMARead,
         // 0b1000_0001,
         // 0b1000_0010,
@@ -982,7 +1021,7 @@ pub fn str_to_opcode(s: &str) -> Option<u8> {
 impl std::fmt::Display for Ins {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
-            Ins::NoOperation => format!("NOP"),
+            Ins::NoOperation => String::from("NOP"),
             Ins::DuplicateData { len } => format!("DUP{} DATA", len),
             Ins::CopyDataToSwap { len } => format!("COPY{} DATA SWAP", len),
             Ins::CopyDataToReturn { len } => format!("COPY{} DATA RTRN", len),
@@ -998,9 +1037,9 @@ impl std::fmt::Display for Ins {
             Ins::CopyHoldToData { len } => format!("COPY{} HOLD DATA", len),
             Ins::CopyHoldToSwap { len } => format!("COPY{} HOLD SWAP", len),
             Ins::CopyHoldToReturn { len } => format!("COPY{} HOLD RTRN", len),
-            Ins::DropData => format!("DROP DATA"),
-            Ins::DropSwap => format!("DROP SWAP"),
-            Ins::DropReturn => format!("DROP RTRN"),
+            Ins::DropData => String::from("DROP DATA"),
+            Ins::DropSwap => String::from("DROP SWAP"),
+            Ins::DropReturn => String::from("DROP RTRN"),
             Ins::Jump { len, con, rel } => match con {
                 true => match rel {
                     true => format!("JUMP{} REL COND", len),
@@ -1017,12 +1056,29 @@ impl std::fmt::Display for Ins {
             Ins::Store { len } => format!("MEM STOR{}", len),
             Ins::Load { len } => format!("MEM LOAD{}", len),
             Ins::Literal { len } => format!("LIT{}", len),
-            Ins::DMARead => format!("DMA READ"),
+            Ins::DMARead => String::from("DMA READ"),
             Ins::DMAWrite { len } => format!("DMA WRIT{}", len),
-            Ins::DMAPoll => format!("DMA POLL"),
+            Ins::DMAPoll => String::from("DMA POLL"),
             Ins::DeviceRead { len } => format!("DEV READ{}", len),
             Ins::DeviceWrite { len } => format!("DEV WRIT{}", len),
             Ins::DevicePoll { len } => format!("DEV POLL{}", len),
+            Ins::SystemCall => String::from("SYSCALL"),
+            Ins::SystemReturn => String::from("SYS RTRN"),
+            Ins::InterruptEnable => String::from("INT ENBL"),
+            Ins::InterruptDisable => String::from("INT DSBL"),
+            Ins::SetSystemVector => String::from("SET SVEC"),
+            Ins::SetDeviceVector => String::from("SET DVEC"),
+            Ins::SetRegion => String::from("SET RGN"),
+            Ins::SetStack => String::from("SET STCK"),
+            Ins::ReadStack => String::from("READ STCK"),
+            Ins::SaveContext => String::from("SAVE CTXT"),
+            Ins::LoadContext => String::from("LOAD CTXT"),
+            Ins::SwitchContext => String::from("SWAP CTXT"),
+            Ins::Catch => String::from("CATCH"),
+            Ins::Throw => String::from("THROW"),
+            Ins::EndCatch => String::from("END CTCH"),
+            Ins::CallHash => String::from("CALL HASH"),
+            Ins::AddressHash => String::from("ADDR HASH"),
             Ins::Add { len } => format!("+{}", len),
             Ins::Subtract { len } => format!("-{}", len),
             Ins::Multiply { len } => format!("*{}", len),
@@ -0,0 +1,266 @@
+use super::{
+    Backing, DeviceSlot, InitCheck, Region, Stack, CPU, DEVICE_COUNT, DMA, DMA_COUNT, REGION_COUNT,
+    STACK_COUNT,
+};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+// the state of the CPU just before an instruction ran, minus memory
+struct Snapshot {
+    program_counter: u16,
+    memory_address: u64,
+    status: u8,
+    syscall_vector: u16,
+    catch_depth: u32,
+    hold: u64,
+    stacks: [Stack; STACK_COUNT],
+    backings: [Option<Backing>; STACK_COUNT],
+    devices: [DeviceSlot; DEVICE_COUNT],
+    dmas: [DMA; DMA_COUNT],
+    regions: [Region; REGION_COUNT],
+    device_events: u16,
+    dma_events: u8,
+    reports: usize,
+}
+impl Snapshot {
+    fn new() -> Snapshot {
+        Snapshot {
+            program_counter: 0,
+            memory_address: 0,
+            status: 0,
+            syscall_vector: 0,
+            catch_depth: 0,
+            hold: 0,
+            stacks: [Stack::new(); STACK_COUNT],
+            backings: [None; STACK_COUNT],
+            devices: [DeviceSlot::new(); DEVICE_COUNT],
+            dmas: [DMA::new(); DMA_COUNT],
+            regions: [Region::new(); REGION_COUNT],
+            device_events: 0,
+            dma_events: 0,
+            reports: 0,
+        }
+    }
+    fn take(&mut self, cpu: &CPU) {
+        self.program_counter = cpu.program_counter;
+        self.memory_address = cpu.memory_address;
+        self.status = cpu.status_reg;
+        self.syscall_vector = cpu.syscall_vector;
+        self.catch_depth = cpu.catch_depth;
+        self.hold = cpu.hold_reg.get();
+        self.stacks = [cpu.data_st, cpu.swap_st, cpu.return_st];
+        self.backings = cpu.backings;
+        self.devices = cpu.devices;
+        self.dmas = cpu.dma_controllers;
+        self.regions = cpu.regions;
+        self.device_events = cpu.device_events;
+        self.dma_events = cpu.dma_events;
+        self.reports = cpu.init.as_ref().map_or(0, |init| init.reports.len());
+    }
+}
+
+// the bytes of a stack above the point where it diverged, and its old length
+struct StackChange {
+    stack: usize,
+    len: usize,
+    start: usize,
+    bytes: Vec<u8>,
+}
+
+// everything needed to undo one step
+struct Entry {
+    program_counter: u16,
+    memory_address: u64,
+    status: u8,
+    syscall_vector: u16,
+    catch_depth: u32,
+    hold: u64,
+    stacks: Vec<StackChange>,
+    backings: [Option<Backing>; STACK_COUNT],
+    memory: Vec<(usize, Vec<u8>)>,
+    devices: Vec<(usize, DeviceSlot)>,
+    dmas: Vec<(usize, DMA)>,
+    regions: Vec<(usize, Region)>,
+    device_events: u16,
+    dma_events: u8,
+    reports: usize,
+    init: Vec<(usize, Vec<u64>)>,
+}
+
+/*
+    An undo log of executed instructions, holding at most `capacity` steps.
+
+    A step is recorded lazily: the snapshot taken before an instruction is
+    diffed against the CPU when the next instruction starts (or when stepping
+    back), so interrupts and device slot changes made by the host in between
+    are undone along with the instruction. Memory is too big to snapshot, so
+    instructions report the bytes they overwrite instead, and likewise the
+    initialized bits they set.
+
+    Every step copies the registers, stacks, devices, DMA controllers and
+    regions into the snapshot, then keeps the parts that changed, so memory
+    use grows with the capacity and with how much each step changes.
+*/
+pub struct Journal {
+    capacity: usize,
+    entries: VecDeque<Entry>,
+    snapshot: Box<Snapshot>,
+    open: bool,
+    writes: Vec<(usize, Vec<u8>)>,
+    init: Vec<(usize, Vec<u64>)>,
+}
+impl Journal {
+    pub const DEFAULT_CAPACITY: usize = 100_000;
+
+    pub fn new(capacity: usize) -> Journal {
+        Journal {
+            capacity,
+            entries: VecDeque::new(),
+            snapshot: Box::new(Snapshot::new()),
+            open: false,
+            writes: Vec::new(),
+            init: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.open as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.open = false;
+        self.writes.clear();
+        self.init.clear();
+    }
+
+    pub(super) fn begin(&mut self, cpu: &CPU) {
+        self.close(cpu);
+        self.snapshot.take(cpu);
+        self.open = true;
+    }
+
+    pub(super) fn record_write(&mut self, memory: &[u8], range: Range<usize>) {
+        if self.open {
+            self.writes.push((range.start, memory[range].to_vec()));
+        }
+    }
+
+    pub(super) fn record_init(&mut self, init: &InitCheck, range: Range<usize>) {
+        if self.open {
+            self.init.push(init.words(range));
+        }
+    }
+
+    // undoes the most recent step, returning false once history runs out
+    pub(super) fn undo(&mut self, cpu: &mut CPU) -> bool {
+        self.close(cpu);
+        let Some(entry) = self.entries.pop_back() else {
+            return false;
+        };
+
+        cpu.program_counter = entry.program_counter;
+        cpu.memory_address = entry.memory_address;
+        cpu.status_reg = entry.status;
+        cpu.syscall_vector = entry.syscall_vector;
+        cpu.catch_depth = entry.catch_depth;
+        cpu.hold_reg.set(entry.hold);
+        cpu.backings = entry.backings;
+        for change in entry.stacks {
+            let stack = match change.stack {
+                0 => &mut cpu.data_st,
+                1 => &mut cpu.swap_st,
+                _ => &mut cpu.return_st,
+            };
+            stack.restore(change.len, change.start, &change.bytes);
+        }
+        for (address, bytes) in entry.memory.into_iter().rev() {
+            let range = address..address + bytes.len();
+            cpu.blocks.invalidate(range.clone());
+            cpu.memory[range].copy_from_slice(&bytes);
+        }
+        for (index, slot) in entry.devices {
+            cpu.devices[index] = slot;
+        }
+        for (index, dma) in entry.dmas {
+            cpu.dma_controllers[index] = dma;
+        }
+        for (index, region) in entry.regions {
+            let _ = cpu.set_region(index as u8, region);
+        }
+        cpu.device_events = entry.device_events;
+        cpu.dma_events = entry.dma_events;
+        if let Some(init) = &mut cpu.init {
+            for (first, words) in entry.init.iter().rev() {
+                init.restore(*first, words);
+            }
+            init.reports.truncate(entry.reports);
+        }
+        true
+    }
+
+    fn close(&mut self, cpu: &CPU) {
+        if !self.open {
+            return;
+        }
+        self.open = false;
+        let before = &self.snapshot;
+
+        let after = [&cpu.data_st, &cpu.swap_st, &cpu.return_st];
+        let stacks = before
+            .stacks
+            .iter()
+            .zip(after)
+            .enumerate()
+            .filter_map(|(stack, (before, after))| {
+                let (old, new) = (before.as_slice(), after.as_slice());
+                let start = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+                match start == old.len() && old.len() == new.len() {
+                    true => None,
+                    false => Some(StackChange {
+                        stack,
+                        len: old.len(),
+                        start,
+                        bytes: old[start..].to_vec(),
+                    }),
+                }
+            })
+            .collect();
+        let devices = (0..DEVICE_COUNT)
+            .filter(|&i| before.devices[i] != cpu.devices[i])
+            .map(|i| (i, before.devices[i]))
+            .collect();
+        let dmas = (0..DMA_COUNT)
+            .filter(|&i| before.dmas[i] != cpu.dma_controllers[i])
+            .map(|i| (i, before.dmas[i]))
+            .collect();
+        let regions = (0..REGION_COUNT)
+            .filter(|&i| before.regions[i] != cpu.regions[i])
+            .map(|i| (i, before.regions[i]))
+            .collect();
+
+        self.entries.push_back(Entry {
+            program_counter: before.program_counter,
+            memory_address: before.memory_address,
+            status: before.status,
+            syscall_vector: before.syscall_vector,
+            catch_depth: before.catch_depth,
+            hold: before.hold,
+            stacks,
+            backings: before.backings,
+            memory: std::mem::take(&mut self.writes),
+            devices,
+            dmas,
+            regions,
+            device_events: before.device_events,
+            dma_events: before.dma_events,
+            reports: before.reports,
+            init: std::mem::take(&mut self.init),
+        });
+        if self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+}
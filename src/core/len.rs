@@ -1,5 +1,5 @@
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum LenF {
     L32 = 4,
     L64 = 8,
@@ -24,7 +24,7 @@ impl std::fmt::Display for LenF {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Len16 {
     L8 = 1,
     L16 = 2,
@@ -49,7 +49,7 @@ impl std::fmt::Display for Len16 {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Len32 {
     L08 = 1,
     L16 = 2,
@@ -79,7 +79,7 @@ impl std::fmt::Display for Len32 {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Len64 {
     L08 = 1,
     L16 = 2,
@@ -0,0 +1,97 @@
+use super::{Fault, CPU};
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub type Hash = [u8; 32];
+
+/*
+    Routines called by hash, with CLH and ADH, are loaded the first time
+    they're used. A routine's dependencies are loaded first, then it's
+    copied to the next free byte of the linker's window of memory and its
+    links are patched: offsets into the routine get its address added, and
+    links to other routines get theirs. Where each hash was loaded is kept,
+    so later calls go straight to it.
+
+    Loading writes memory the way `load_rom` does, not the way instructions
+    do: it isn't recorded in the journal, and regions don't apply, since the
+    host gave the linker its window. Stepping back past a load leaves the
+    routine in memory, and initialized, where the linker still expects it
+    to be.
+*/
+pub struct Linker {
+    routines: HashMap<Hash, (Vec<u8>, Vec<Link>)>,
+    loaded: HashMap<Hash, u16>,
+    next: usize,
+    end: usize,
+}
+
+// a 16-bit operand in a routine that depends on where things are loaded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Link {
+    // an offset into the routine, to add the routine's address to
+    Local { operand: u16 },
+    // the address of another routine
+    Routine { operand: u16, hash: Hash },
+}
+
+impl Linker {
+    pub fn new(window: Range<usize>) -> Linker {
+        Linker {
+            routines: HashMap::new(),
+            loaded: HashMap::new(),
+            next: window.start,
+            end: usize::min(window.end, 65_536),
+        }
+    }
+
+    pub fn add(&mut self, hash: Hash, code: Vec<u8>, links: Vec<Link>) {
+        self.routines.insert(hash, (code, links));
+    }
+
+    // where a routine was loaded, if it has been
+    pub fn address(&self, hash: &Hash) -> Option<u16> {
+        self.loaded.get(hash).copied()
+    }
+
+    pub(super) fn load(&mut self, hash: Hash, cpu: &mut CPU) -> Result<u16, Fault> {
+        if let Some(address) = self.address(&hash) {
+            return Ok(address);
+        }
+        let Some((code, links)) = self.routines.get(&hash) else {
+            return Err(Fault::UnknownRoutine { hash });
+        };
+        let (mut code, links) = (code.clone(), links.clone());
+
+        for link in &links {
+            if let Link::Routine { hash, .. } = link {
+                self.load(*hash, cpu)?;
+            }
+        }
+
+        let start = self.next;
+        let end = start + code.len();
+        if end > self.end {
+            return Err(Fault::LinkerFull { hash });
+        }
+        for link in &links {
+            let (operand, address) = match link {
+                Link::Local { operand } => {
+                    let at = *operand as usize;
+                    let offset = u16::from_le_bytes([code[at], code[at + 1]]);
+                    (at, offset.wrapping_add(start as u16))
+                }
+                Link::Routine { operand, hash } => (*operand as usize, self.loaded[hash]),
+            };
+            code[operand..operand + 2].copy_from_slice(&address.to_le_bytes());
+        }
+
+        cpu.memory[start..end].copy_from_slice(&code);
+        cpu.blocks.invalidate(start..end);
+        if let Some(init) = &mut cpu.init {
+            init.mark(start..end);
+        }
+        self.next = end;
+        self.loaded.insert(hash, start as u16);
+        Ok(start as u16)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Pop, Push};
+use super::{Fault, Pop, Push};
 
 pub struct Register64 {
     buffer: [u8; 8],
@@ -8,29 +8,40 @@ impl Register64 {
     pub fn new() -> Register64 {
         Register64 { buffer: [0; 8] }
     }
+    pub fn get(&self) -> u64 {
+        u64::from_le_bytes(self.buffer)
+    }
+    pub fn set(&mut self, value: u64) {
+        self.buffer = value.to_le_bytes();
+    }
+}
+impl Default for Register64 {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Push for Register64 {
-    fn push(&mut self, bytes: &[u8]) {
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Fault> {
         let len = bytes.len();
 
         if len > 8 {
-            panic!("Register Overflow")
+            return Err(Fault::RegisterOverflow);
         }
 
-        for i in 0..8 {
-            if i < len {
-                self.buffer[i] = bytes[i];
-            } else {
-                self.buffer[i] = 0;
-            }
-        }
+        self.buffer = [0; 8];
+        self.buffer[..len].copy_from_slice(bytes);
+        Ok(())
     }
 }
 
 impl Pop for Register64 {
-    fn pop(&self, len: usize) -> &[u8] {
-        &self.buffer[0..len]
+    fn pop(&self, len: usize) -> Result<&[u8], Fault> {
+        if len > 8 {
+            return Err(Fault::RegisterOverflow);
+        }
+
+        Ok(&self.buffer[0..len])
     }
 }
 
@@ -0,0 +1,75 @@
+use super::Fault;
+use std::collections::HashMap;
+
+/*
+    A host-side copy of the return addresses pushed by Call and by traps,
+    each with the return stack depth just after its push. A Return, or SRT,
+    that starts from a frame's depth must jump to that frame's address.
+    Frames deeper than the return stack were unwound (by THR, or by code
+    dropping return bytes) and are discarded.
+
+    Returns with no frame left aren't checked: the supervisor starting a
+    user program with SRT, or a context the shadow stack hasn't seen. Saved
+    contexts keep their frames, keyed by block address, and stepping back
+    through the journal forgets every frame.
+*/
+pub struct ShadowStack {
+    frames: Vec<Frame>,
+    contexts: HashMap<u16, Vec<Frame>>,
+}
+#[derive(Clone, Copy)]
+struct Frame {
+    depth: usize,
+    address: u16,
+}
+impl ShadowStack {
+    pub fn new() -> ShadowStack {
+        ShadowStack {
+            frames: Vec::new(),
+            contexts: HashMap::new(),
+        }
+    }
+
+    // return addresses from the innermost frame out
+    pub fn addresses(&self) -> impl Iterator<Item = u16> + '_ {
+        self.frames.iter().rev().map(|frame| frame.address)
+    }
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.contexts.clear();
+    }
+
+    pub(super) fn push(&mut self, depth: usize, address: u16) {
+        self.frames.push(Frame { depth, address });
+    }
+
+    // checks a return from `depth` to `target`
+    pub(super) fn pop(&mut self, depth: usize, target: u16) -> Result<(), Fault> {
+        while self.frames.last().is_some_and(|frame| frame.depth > depth) {
+            self.frames.pop();
+        }
+        let Some(frame) = self.frames.pop() else {
+            return Ok(());
+        };
+        if frame.depth != depth || frame.address != target {
+            self.frames.push(frame);
+            return Err(Fault::ReturnMismatch {
+                expected: frame.address,
+                found: target,
+            });
+        }
+        Ok(())
+    }
+
+    pub(super) fn save(&mut self, context: u16) {
+        self.contexts.insert(context, self.frames.clone());
+    }
+    pub(super) fn load(&mut self, context: u16) {
+        self.frames = self.contexts.get(&context).cloned().unwrap_or_default();
+    }
+}
+impl Default for ShadowStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,8 @@
-use super::{Pop, Push};
+use super::{Fault, Pop, Push};
 
-const STACK_SIZE: usize = 256;
+pub(super) const STACK_SIZE: usize = 256;
 
+#[derive(Clone, Copy)]
 pub struct Stack {
     pointer: usize,
     buffer: [u8; STACK_SIZE],
@@ -16,47 +17,126 @@ impl Stack {
     pub fn len(&self) -> usize {
         self.pointer
     }
-    pub fn duplicate(&mut self, len: usize) {
+    pub fn is_empty(&self) -> bool {
+        self.pointer == 0
+    }
+    pub fn set_len(&mut self, len: usize) -> Result<(), Fault> {
+        if len > STACK_SIZE {
+            return Err(Fault::StackOverflow);
+        }
+
+        self.pointer = len;
+        Ok(())
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[0..self.pointer]
+    }
+    pub(super) fn restore(&mut self, len: usize, start: usize, bytes: &[u8]) {
+        self.buffer[start..start + bytes.len()].copy_from_slice(bytes);
+        self.pointer = len;
+    }
+
+    // the oldest bytes on the stack, which spill to memory first
+    pub(super) fn bottom(&self, len: usize) -> &[u8] {
+        &self.buffer[..len]
+    }
+    pub(super) fn remove_bottom(&mut self, len: usize) {
+        self.buffer.copy_within(len..self.pointer, 0);
+        self.pointer -= len;
+    }
+    pub(super) fn insert_bottom(&mut self, bytes: &[u8]) -> Result<(), Fault> {
+        let len = bytes.len();
+        if self.pointer + len > STACK_SIZE {
+            return Err(Fault::StackOverflow);
+        }
+        self.buffer.copy_within(..self.pointer, len);
+        self.buffer[..len].copy_from_slice(bytes);
+        self.pointer += len;
+        Ok(())
+    }
+
+    // operands up to 8 bytes go through a fixed width read or write when
+    // there is room, which avoids a variable length copy
+    pub(super) fn pop_value(&mut self, len: usize) -> Result<u64, Fault> {
         if self.pointer < len {
-            panic!("Stack Underflow");
+            return Err(Fault::StackUnderflow);
+        }
+
+        let value = match self.pointer >= 8 {
+            true => self.word(self.pointer - 8) >> ((8 - len) * 8),
+            false => (self.word(0) >> ((self.pointer - len) * 8)) & (u64::MAX >> ((8 - len) * 8)),
+        };
+        self.pointer -= len;
+        Ok(value)
+    }
+    fn word(&self, start: usize) -> u64 {
+        u64::from_le_bytes(self.buffer[start..start + 8].try_into().unwrap())
+    }
+    pub(super) fn push_value(&mut self, len: usize, value: u64) -> Result<(), Fault> {
+        let end = self.pointer + len;
+        if end > STACK_SIZE {
+            return Err(Fault::StackOverflow);
+        }
+
+        match self.pointer + 8 <= STACK_SIZE {
+            true => {
+                self.buffer[self.pointer..self.pointer + 8].copy_from_slice(&value.to_le_bytes())
+            }
+            false => self.buffer[self.pointer..end].copy_from_slice(&value.to_le_bytes()[..len]),
+        }
+        self.pointer = end;
+        Ok(())
+    }
+
+    pub fn duplicate(&mut self, len: usize) -> Result<(), Fault> {
+        if self.pointer < len {
+            return Err(Fault::StackUnderflow);
         }
         if self.pointer + len > STACK_SIZE {
-            panic!("Stack Overflow")
+            return Err(Fault::StackOverflow);
         }
 
         let top_range = self.pointer - len..self.pointer;
         self.buffer.copy_within(top_range, self.pointer);
         self.pointer += len;
+        Ok(())
     }
-    pub fn drop(&mut self, len: usize) {
+    pub fn drop(&mut self, len: usize) -> Result<(), Fault> {
         if self.pointer < len {
-            panic!("Stack Underflow");
+            return Err(Fault::StackUnderflow);
         }
 
         self.pointer -= len;
+        Ok(())
+    }
+}
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Push for Stack {
-    fn push(&mut self, bytes: &[u8]) {
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Fault> {
         let start = self.pointer;
         let end = self.pointer + bytes.len();
         if end > STACK_SIZE {
-            panic!("Stack Overflow")
+            return Err(Fault::StackOverflow);
         }
 
         self.buffer[start..end].copy_from_slice(bytes);
         self.pointer = end;
+        Ok(())
     }
 }
 
 impl Pop for Stack {
-    fn pop(&self, len: usize) -> &[u8] {
+    fn pop(&self, len: usize) -> Result<&[u8], Fault> {
         if self.pointer < len {
-            panic!("Stack Underflow");
+            return Err(Fault::StackUnderflow);
         }
 
-        &self.buffer[self.pointer - len..self.pointer]
+        Ok(&self.buffer[self.pointer - len..self.pointer])
     }
 }
 
@@ -0,0 +1,277 @@
+use super::instruction::Ins;
+use super::STACK_COUNT;
+
+/*
+    Tags the items on each stack with their width and the address of the
+    instruction that pushed them. Before an instruction runs, every operand
+    it takes is compared against the tag on top of its stack; a different
+    width is a mismatch, kept as a report or raised as a fault.
+
+    Tags only cover what was pushed while checking: bytes below them, and
+    stacks replaced wholesale by a context load, are unknown and never
+    reported. Stepping back through the journal forgets every tag.
+*/
+pub struct WidthCheck {
+    pub fault: bool,
+    pub reports: Vec<Mismatch>,
+    tags: [Vec<Tag>; STACK_COUNT],
+}
+#[derive(Clone, Copy)]
+struct Tag {
+    width: usize,
+    producer: u16,
+}
+
+// an operand taken at `address` that `producer` pushed with another width
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub address: u16,
+    pub producer: u16,
+    pub stack: u8,
+    pub pushed: u8,
+    pub taken: u8,
+}
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stack = match self.stack {
+            0 => "DATA",
+            1 => "SWAP",
+            _ => "RTRN",
+        };
+        write!(
+            f,
+            "{:#06X} took {} bytes from {}, pushed as {} bytes at {:#06X}",
+            self.address, self.taken, stack, self.pushed, self.producer
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Take(usize, usize),
+    Peek(usize, usize),
+    Push(usize, usize),
+}
+
+impl WidthCheck {
+    pub fn new(fault: bool) -> WidthCheck {
+        WidthCheck {
+            fault,
+            reports: Vec::new(),
+            tags: [Vec::new(), Vec::new(), Vec::new()],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.tags.iter_mut().for_each(Vec::clear);
+    }
+
+    // checks and takes the operands of `instruction`, returning the first mismatch
+    pub(super) fn before(&mut self, instruction: &Ins, address: u16) -> Option<Mismatch> {
+        let mut first = None;
+        for op in effect(instruction) {
+            let (stack, width, take) = match op {
+                Op::Take(stack, width) => (stack, width, true),
+                Op::Peek(stack, width) => (stack, width, false),
+                Op::Push(..) => continue,
+            };
+            let Some(tag) = self.tags[stack].last().copied() else {
+                continue;
+            };
+            if tag.width != width && first.is_none() {
+                first = Some(Mismatch {
+                    address,
+                    producer: tag.producer,
+                    stack: stack as u8,
+                    pushed: tag.width as u8,
+                    taken: width as u8,
+                });
+            }
+            if take {
+                self.take(stack, width);
+            }
+        }
+        if let Some(mismatch) = first {
+            self.reports.push(mismatch);
+        }
+        first
+    }
+
+    // tags what `instruction` pushed, once it ran, and trims tags to `depths`
+    pub(super) fn after(&mut self, instruction: &Ins, address: u16, depths: [usize; STACK_COUNT]) {
+        match instruction {
+            Ins::LoadContext | Ins::SwitchContext => self.clear(),
+            Ins::Throw => {
+                self.trim(depths[0] - 1, depths);
+                self.push(0, 1, address);
+            }
+            _ => {
+                for op in effect(instruction) {
+                    if let Op::Push(stack, width) = op {
+                        self.push(stack, width, address);
+                    }
+                }
+                self.trim(depths[0], depths);
+            }
+        }
+    }
+
+    // a return address pushed by a trap
+    pub(super) fn trap(&mut self, address: u16) {
+        self.push(2, 2, address);
+    }
+
+    // drops tags for bytes that are no longer on their stacks
+    pub(super) fn trim(&mut self, data: usize, depths: [usize; STACK_COUNT]) {
+        let depths = [data, depths[1], depths[2]];
+        for (stack, depth) in depths.into_iter().enumerate() {
+            let tagged: usize = self.tags[stack].iter().map(|tag| tag.width).sum();
+            if tagged > depth {
+                self.take(stack, tagged - depth);
+            }
+        }
+    }
+
+    fn push(&mut self, stack: usize, width: usize, producer: u16) {
+        self.tags[stack].push(Tag { width, producer });
+    }
+
+    // removes `width` bytes of tags, leaving what's left of a wider item
+    fn take(&mut self, stack: usize, mut width: usize) {
+        let tags = &mut self.tags[stack];
+        while width > 0 {
+            let Some(tag) = tags.last_mut() else {
+                return;
+            };
+            if tag.width > width {
+                tag.width -= width;
+                return;
+            }
+            width -= tag.width;
+            tags.pop();
+        }
+    }
+}
+
+// the stack operands an instruction takes and pushes, in order
+fn effect(instruction: &Ins) -> Vec<Op> {
+    const DATA: usize = 0;
+    const SWAP: usize = 1;
+    const RTRN: usize = 2;
+    use Op::*;
+
+    match *instruction {
+        Ins::NoOperation => vec![],
+
+        Ins::DuplicateData { len } => vec![Peek(DATA, len as usize), Push(DATA, len as usize)],
+        Ins::CopyDataToSwap { len } => vec![Peek(DATA, len as usize), Push(SWAP, len as usize)],
+        Ins::CopyDataToReturn { len } => vec![Peek(DATA, len as usize), Push(RTRN, len as usize)],
+        Ins::CopyDataToHold { len } => vec![Peek(DATA, len as usize)],
+        Ins::CopySwapToData { len } => vec![Peek(SWAP, len as usize), Push(DATA, len as usize)],
+        Ins::DuplicateSwap { len } => vec![Peek(SWAP, len as usize), Push(SWAP, len as usize)],
+        Ins::CopySwapToReturn { len } => vec![Peek(SWAP, len as usize), Push(RTRN, len as usize)],
+        Ins::CopySwapToHold { len } => vec![Peek(SWAP, len as usize)],
+        Ins::CopyReturnToData { len } => vec![Peek(RTRN, len as usize), Push(DATA, len as usize)],
+        Ins::CopyReturnToSwap { len } => vec![Peek(RTRN, len as usize), Push(SWAP, len as usize)],
+        Ins::DuplicateReturn { len } => vec![Peek(RTRN, len as usize), Push(RTRN, len as usize)],
+        Ins::CopyReturnToHold { len } => vec![Peek(RTRN, len as usize)],
+        Ins::CopyHoldToData { len } => vec![Push(DATA, len as usize)],
+        Ins::CopyHoldToSwap { len } => vec![Push(SWAP, len as usize)],
+        Ins::CopyHoldToReturn { len } => vec![Push(RTRN, len as usize)],
+        Ins::DropData => vec![Take(DATA, 1)],
+        Ins::DropSwap => vec![Take(SWAP, 1)],
+        Ins::DropReturn => vec![Take(RTRN, 1)],
+
+        Ins::Literal { len } => vec![Push(DATA, len as usize)],
+        Ins::Address { len } => vec![Take(DATA, len as usize)],
+        Ins::Store { len } => vec![Peek(DATA, len as usize)],
+        Ins::Load { len } => vec![Push(DATA, len as usize)],
+
+        Ins::DMARead => vec![Take(DATA, 1), Push(DATA, 4), Push(DATA, 4)],
+        Ins::DMAWrite { len } => vec![
+            Take(DATA, 1),
+            Take(DATA, 1),
+            Take(DATA, len as usize),
+            Take(DATA, len as usize),
+        ],
+        Ins::DMAPoll => vec![Take(DATA, 1), Take(DATA, 1), Push(DATA, 1)],
+
+        Ins::DeviceRead { len } => vec![Take(DATA, 1), Take(DATA, 1), Push(DATA, len as usize)],
+        Ins::DeviceWrite { len } => vec![
+            Take(DATA, 1),
+            Take(DATA, 1),
+            Take(DATA, 1),
+            Peek(DATA, len as usize),
+        ],
+        Ins::DevicePoll { len } => vec![Take(DATA, 1), Take(DATA, len as usize)],
+
+        Ins::Jump { con, len, .. } => match con {
+            true => vec![Take(DATA, len as usize), Take(DATA, 1)],
+            false => vec![Take(DATA, len as usize)],
+        },
+        Ins::Call { len } => vec![Take(DATA, len as usize), Push(RTRN, 2)],
+        Ins::Return { len } => vec![Take(RTRN, len as usize)],
+
+        Ins::SystemCall => vec![Push(RTRN, 2)],
+        Ins::SystemReturn => vec![Take(RTRN, 2)],
+        Ins::InterruptEnable | Ins::InterruptDisable => vec![],
+        Ins::SetSystemVector => vec![Take(DATA, 2)],
+        Ins::SetDeviceVector => vec![Take(DATA, 1), Take(DATA, 2)],
+        Ins::SetRegion => vec![Take(DATA, 1), Take(DATA, 1), Take(DATA, 2), Take(DATA, 2)],
+        Ins::SetStack => vec![Take(DATA, 1), Take(DATA, 2), Take(DATA, 2)],
+        Ins::ReadStack => vec![Take(DATA, 1), Push(DATA, 2), Push(DATA, 2), Push(DATA, 2)],
+        Ins::SaveContext | Ins::LoadContext => vec![Take(DATA, 2)],
+        Ins::SwitchContext => vec![Take(DATA, 2), Take(DATA, 2)],
+        Ins::Catch => vec![
+            Take(DATA, 2),
+            Push(RTRN, 4),
+            Push(RTRN, 4),
+            Push(RTRN, 4),
+            Push(RTRN, 2),
+        ],
+        Ins::Throw => vec![Take(DATA, 1)],
+        Ins::EndCatch => vec![Take(RTRN, 2), Take(RTRN, 4), Take(RTRN, 4), Take(RTRN, 4)],
+        Ins::CallHash => vec![Push(RTRN, 2)],
+        Ins::AddressHash => vec![Push(DATA, 2)],
+
+        Ins::Add { len }
+        | Ins::Subtract { len }
+        | Ins::Multiply { len }
+        | Ins::Divide { len }
+        | Ins::And { len }
+        | Ins::Or { len }
+        | Ins::Xor { len }
+        | Ins::ShiftL { len }
+        | Ins::ShiftR { len } => vec![
+            Take(DATA, len as usize),
+            Take(DATA, len as usize),
+            Push(DATA, len as usize),
+        ],
+        Ins::Not { len } => vec![Take(DATA, len as usize), Push(DATA, len as usize)],
+        Ins::Greater { len } | Ins::Less { len } | Ins::Equal { len } | Ins::NotEqual { len } => {
+            vec![
+                Take(DATA, len as usize),
+                Take(DATA, len as usize),
+                Push(DATA, 1),
+            ]
+        }
+
+        Ins::AddF { len }
+        | Ins::SubtractF { len }
+        | Ins::MultiplyF { len }
+        | Ins::DivideF { len } => {
+            vec![
+                Take(DATA, len as usize),
+                Take(DATA, len as usize),
+                Push(DATA, len as usize),
+            ]
+        }
+        Ins::GreaterF { len } | Ins::LessF { len } => {
+            vec![
+                Take(DATA, len as usize),
+                Take(DATA, len as usize),
+                Push(DATA, 1),
+            ]
+        }
+    }
+}
@@ -0,0 +1,167 @@
+pub mod dap;
+pub mod gdb;
+pub mod json;
+mod profile;
+mod source_map;
+
+pub use profile::{Profile, RoutineCount};
+pub use source_map::{LineEntry, RoutineSymbol, SourceMap};
+
+use crate::core::{Fault, Instruction, CPU};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+pub type Service<'a> = Box<dyn FnMut(&mut CPU) -> Result<(), Fault> + 'a>;
+
+pub enum Stop {
+    Step,
+    Breakpoint,
+    Interrupt,
+    Watchpoint(u16),
+    HistoryStart,
+    Fault(Fault),
+}
+
+// a routine entered through `Call` or by hash, tracked on the host while debugging;
+// popped once the return stack drops below the depth the call left it at
+#[derive(Clone, Copy)]
+pub struct Frame {
+    pub call_site: u16,
+    pub entry: u16,
+    pub depth: usize,
+}
+
+pub struct Session<'a> {
+    pub cpu: &'a mut CPU,
+    pub breakpoints: BTreeSet<u16>,
+    pub watchpoints: BTreeMap<u16, u16>,
+    pub frames: Vec<Frame>,
+    returned: VecDeque<Vec<Frame>>,
+    service: Service<'a>,
+}
+impl<'a> Session<'a> {
+    pub fn new(cpu: &'a mut CPU, service: Service<'a>) -> Session<'a> {
+        Session {
+            cpu,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            frames: Vec::new(),
+            returned: VecDeque::new(),
+            service,
+        }
+    }
+
+    pub fn step(&mut self) -> Stop {
+        let watched = self.read_watched();
+        let call_site = self.cpu.program_counter;
+        let instruction = Instruction::from(self.cpu.memory[call_site as usize]);
+
+        let executed = self.cpu.execute();
+        let returned = self.pop_returned();
+        self.remember_returned(returned);
+        if let Err(fault) = executed {
+            return Stop::Fault(fault);
+        }
+        if let Instruction::Call { .. } | Instruction::CallHash = instruction {
+            self.frames.push(Frame {
+                call_site,
+                entry: self.cpu.program_counter,
+                depth: self.cpu.return_depth(),
+            });
+        }
+
+        if let Err(fault) = (self.service)(self.cpu) {
+            return Stop::Fault(fault);
+        }
+        self.check_watched(watched)
+    }
+
+    // undoes the last step, if the CPU is keeping a journal
+    pub fn step_back(&mut self) -> Stop {
+        let watched = self.read_watched();
+        if !self.cpu.step_back() {
+            return Stop::HistoryStart;
+        }
+
+        // an undone call leaves its frame deeper than the return stack
+        self.pop_returned();
+        if let Some(frames) = self.returned.pop_back() {
+            self.frames.extend(frames.into_iter().rev());
+        }
+
+        self.check_watched(watched)
+    }
+
+    pub fn resume(&mut self, interrupted: &mut dyn FnMut() -> bool) -> Stop {
+        self.run(Session::step, interrupted)
+    }
+
+    pub fn reverse_resume(&mut self, interrupted: &mut dyn FnMut() -> bool) -> Stop {
+        self.run(Session::step_back, interrupted)
+    }
+
+    fn run(
+        &mut self,
+        step: fn(&mut Session<'a>) -> Stop,
+        interrupted: &mut dyn FnMut() -> bool,
+    ) -> Stop {
+        loop {
+            match step(self) {
+                Stop::Step => {}
+                stop => return stop,
+            }
+            if self.breakpoints.contains(&self.cpu.program_counter) {
+                return Stop::Breakpoint;
+            }
+            if interrupted() {
+                return Stop::Interrupt;
+            }
+        }
+    }
+
+    // pops the frames a return or throw left, innermost first
+    fn pop_returned(&mut self) -> Vec<Frame> {
+        let depth = self.cpu.return_depth();
+        let mut returned = Vec::new();
+        while self.frames.last().is_some_and(|frame| frame.depth > depth) {
+            returned.extend(self.frames.pop());
+        }
+        returned
+    }
+
+    // the frames each step popped are kept as long as the journal could undo it
+    fn remember_returned(&mut self, frames: Vec<Frame>) {
+        let Some(journal) = &self.cpu.journal else {
+            return;
+        };
+        self.returned.push_back(frames);
+        if self.returned.len() > journal.len() {
+            self.returned.pop_front();
+        }
+    }
+
+    fn read_watched(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (&address, &len) in &self.watchpoints {
+            bytes.extend(self.watched_range(address, len));
+        }
+        bytes
+    }
+
+    fn check_watched(&self, before: Vec<u8>) -> Stop {
+        let mut offset = 0;
+        for (&address, &len) in &self.watchpoints {
+            let current = self.watched_range(address, len);
+            let previous = &before[offset..offset + current.len()];
+            offset += current.len();
+            if current != previous {
+                return Stop::Watchpoint(address);
+            }
+        }
+        Stop::Step
+    }
+
+    fn watched_range(&self, address: u16, len: u16) -> &[u8] {
+        let end = usize::min(address as usize + len as usize, self.cpu.memory.len());
+        &self.cpu.memory[address as usize..end]
+    }
+}
@@ -0,0 +1,553 @@
+use super::json::Json;
+use super::{Session, SourceMap, Stop};
+use crate::core::{Journal, CPU};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, BufRead, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+const THREAD_ID: i64 = 1;
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
+
+// variable references handed out by `scopes` and `variables`
+const STACKS_REFERENCE: i64 = 1;
+const REGISTERS_REFERENCE: i64 = 2;
+const DATA_REFERENCE: i64 = 3;
+const SWAP_REFERENCE: i64 = 4;
+const RETURN_REFERENCE: i64 = 5;
+
+pub fn read_message(input: &mut dyn BufRead) -> io::Result<Option<Json>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            match length {
+                Some(_) => break,
+                None => continue,
+            }
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    String::from_utf8_lossy(&body)
+        .parse()
+        .map(Some)
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+pub fn write_message(output: &mut dyn Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+// reads messages on their own thread, so a running program can still be paused
+pub fn spawn_reader(mut input: impl BufRead + Send + 'static) -> Receiver<Json> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        while let Ok(Some(message)) = read_message(&mut input) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Continue,
+    StepIn {
+        from: Option<(usize, u32)>,
+    },
+    StepOver {
+        depth: usize,
+        from: Option<(usize, u32)>,
+    },
+    StepOut {
+        depth: usize,
+    },
+    StepBack {
+        from: Option<(usize, u32)>,
+    },
+    ReverseContinue,
+}
+
+struct Adapter<'a> {
+    session: Session<'a>,
+    output: &'a mut dyn Write,
+    sequence: i64,
+    source_map: SourceMap,
+    source_breakpoints: BTreeMap<usize, Vec<u16>>,
+    stop_on_entry: bool,
+    mode: Option<Mode>,
+    pending: VecDeque<Json>,
+    exit: bool,
+}
+
+pub fn serve(messages: Receiver<Json>, output: &mut dyn Write) -> io::Result<()> {
+    let mut cpu = CPU::new();
+    let mut adapter = Adapter {
+        session: Session::new(&mut cpu, Box::new(|_| Ok(()))),
+        output,
+        sequence: 0,
+        source_map: SourceMap::new(),
+        source_breakpoints: BTreeMap::new(),
+        stop_on_entry: false,
+        mode: None,
+        pending: VecDeque::new(),
+        exit: false,
+    };
+
+    loop {
+        while let Some(message) = adapter.pending.pop_front() {
+            adapter.handle(&message)?;
+        }
+        if adapter.exit {
+            return Ok(());
+        }
+        match adapter.mode {
+            Some(mode) => adapter.run(mode, &messages)?,
+            None => match messages.recv() {
+                Ok(message) => adapter.pending.push_back(message),
+                Err(_) => return Ok(()),
+            },
+        }
+    }
+}
+
+impl Adapter<'_> {
+    fn handle(&mut self, request: &Json) -> io::Result<()> {
+        let command = request.get("command").and_then(Json::as_str).unwrap_or("");
+        let empty = Json::Object(vec![]);
+        let args = request.get("arguments").unwrap_or(&empty);
+
+        match command {
+            "initialize" => {
+                let capabilities = Json::object(vec![
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsTerminateRequest", true.into()),
+                    ("supportsStepBack", true.into()),
+                ]);
+                self.respond(request, Ok(capabilities))?;
+                self.event("initialized", Json::Object(vec![]))
+            }
+            "launch" => {
+                let result = self.launch(args).map(|_| Json::Object(vec![]));
+                self.respond(request, result)
+            }
+            "setBreakpoints" => {
+                let body = self.set_breakpoints(args);
+                self.respond(request, Ok(body))
+            }
+            "setExceptionBreakpoints" => self.respond(request, Ok(Json::Object(vec![]))),
+            "configurationDone" => {
+                self.respond(request, Ok(Json::Object(vec![])))?;
+                match self.stop_on_entry {
+                    true => self.stopped("entry", None),
+                    false => {
+                        self.mode = Some(Mode::Continue);
+                        Ok(())
+                    }
+                }
+            }
+            "threads" => {
+                let thread = Json::object(vec![
+                    ("id", THREAD_ID.into()),
+                    ("name", Json::string("cpu")),
+                ]);
+                let body = Json::object(vec![("threads", vec![thread].into())]);
+                self.respond(request, Ok(body))
+            }
+            "stackTrace" => {
+                let body = self.stack_trace();
+                self.respond(request, Ok(body))
+            }
+            "scopes" => {
+                let scope = |name, reference: i64| {
+                    Json::object(vec![
+                        ("name", Json::string(name)),
+                        ("variablesReference", reference.into()),
+                        ("expensive", false.into()),
+                    ])
+                };
+                let scopes = vec![
+                    scope("Stacks", STACKS_REFERENCE),
+                    scope("Registers", REGISTERS_REFERENCE),
+                ];
+                let body = Json::object(vec![("scopes", scopes.into())]);
+                self.respond(request, Ok(body))
+            }
+            "variables" => {
+                let reference = args
+                    .get("variablesReference")
+                    .and_then(Json::as_i64)
+                    .unwrap_or(0);
+                let body = Json::object(vec![("variables", self.variables(reference).into())]);
+                self.respond(request, Ok(body))
+            }
+            "continue" => {
+                self.mode = Some(Mode::Continue);
+                let body = Json::object(vec![("allThreadsContinued", true.into())]);
+                self.respond(request, Ok(body))
+            }
+            "next" => {
+                self.mode = Some(Mode::StepOver {
+                    depth: self.session.frames.len(),
+                    from: self.current_line(),
+                });
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            "stepIn" => {
+                self.mode = Some(Mode::StepIn {
+                    from: self.current_line(),
+                });
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            "stepOut" => {
+                self.mode = Some(Mode::StepOut {
+                    depth: self.session.frames.len(),
+                });
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            "stepBack" => {
+                self.mode = Some(Mode::StepBack {
+                    from: self.current_line(),
+                });
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            "reverseContinue" => {
+                self.mode = Some(Mode::ReverseContinue);
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            "pause" => {
+                self.respond(request, Ok(Json::Object(vec![])))?;
+                match self.mode.take() {
+                    Some(_) => self.stopped("pause", None),
+                    None => Ok(()),
+                }
+            }
+            "terminate" => {
+                self.mode = None;
+                self.respond(request, Ok(Json::Object(vec![])))?;
+                self.event("terminated", Json::Object(vec![]))
+            }
+            "disconnect" => {
+                self.exit = true;
+                self.respond(request, Ok(Json::Object(vec![])))
+            }
+            _ => self.respond(request, Err(format!("Unsupported request '{}'", command))),
+        }
+    }
+
+    fn launch(&mut self, args: &Json) -> Result<(), String> {
+        let program = args
+            .get("program")
+            .and_then(Json::as_str)
+            .ok_or("launch: missing `program`")?;
+        let program = PathBuf::from(program);
+        let rom = std::fs::read(&program)
+            .map_err(|e| format!("Couldn't read rom {}: {}", program.display(), e))?;
+        if rom.len() > self.session.cpu.memory.len() {
+            return Err(format!("Rom {} is too big for memory", program.display()));
+        }
+
+        // use the given source map, or one sitting next to the rom
+        let map = match args.get("sourceMap").and_then(Json::as_str) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(program.with_extension("map")).filter(|path| path.exists()),
+        };
+        if let Some(map) = map {
+            self.source_map = SourceMap::load(&map)?;
+        }
+
+        self.stop_on_entry = args
+            .get("stopOnEntry")
+            .and_then(Json::as_bool)
+            .unwrap_or(false);
+        let history = args
+            .get("history")
+            .and_then(Json::as_i64)
+            .map_or(Journal::DEFAULT_CAPACITY, |history| history.max(0) as usize);
+        self.session.cpu.journal = match history {
+            0 => None,
+            history => Some(Journal::new(history)),
+        };
+        self.session.cpu.load_rom(rom);
+        Ok(())
+    }
+
+    fn set_breakpoints(&mut self, args: &Json) -> Json {
+        let path = args
+            .get("source")
+            .and_then(|source| source.get("path"))
+            .and_then(Json::as_str)
+            .unwrap_or("");
+        let source = self.source_map.source_index(Path::new(path));
+        let lines = args
+            .get("breakpoints")
+            .and_then(Json::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut addresses = Vec::new();
+        let mut breakpoints = Vec::new();
+        for requested in lines {
+            let line = requested.get("line").and_then(Json::as_i64).unwrap_or(0);
+            let entry =
+                source.and_then(|source| self.source_map.address_of_line(source, line as u32));
+            let breakpoint = match entry {
+                Some(entry) => {
+                    addresses.push(entry.address);
+                    Json::object(vec![
+                        ("verified", true.into()),
+                        ("line", (entry.line as i64).into()),
+                    ])
+                }
+                None => Json::object(vec![
+                    ("verified", false.into()),
+                    ("line", line.into()),
+                    ("message", Json::string("No code at or after this line")),
+                ]),
+            };
+            breakpoints.push(breakpoint);
+        }
+
+        if let Some(source) = source {
+            self.source_breakpoints.insert(source, addresses);
+        }
+        self.session.breakpoints = self
+            .source_breakpoints
+            .values()
+            .flatten()
+            .copied()
+            .collect();
+
+        Json::object(vec![("breakpoints", breakpoints.into())])
+    }
+
+    fn stack_trace(&self) -> Json {
+        let mut frames = vec![self.frame(0, self.session.cpu.program_counter)];
+        for (i, frame) in self.session.frames.iter().rev().enumerate() {
+            frames.push(self.frame(i as i64 + 1, frame.call_site));
+        }
+        let total = frames.len() as i64;
+        Json::object(vec![
+            ("stackFrames", frames.into()),
+            ("totalFrames", total.into()),
+        ])
+    }
+
+    fn frame(&self, id: i64, address: u16) -> Json {
+        let name = match self.source_map.routine_at(address) {
+            Some(routine) => routine.name.clone(),
+            None => format!("{:#06x}", address),
+        };
+        let mut fields = vec![
+            ("id", id.into()),
+            ("name", name.into()),
+            (
+                "instructionPointerReference",
+                format!("{:#06x}", address).into(),
+            ),
+        ];
+        match self.source_map.line_at(address) {
+            Some(entry) => {
+                let path = &self.source_map.sources[entry.source];
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let source = Json::object(vec![
+                    ("name", file_name.into_owned().into()),
+                    ("path", path.display().to_string().into()),
+                ]);
+                fields.push(("source", source));
+                fields.push(("line", (entry.line as i64).into()));
+                fields.push(("column", 1.into()));
+            }
+            None => {
+                fields.push(("line", 0.into()));
+                fields.push(("column", 0.into()));
+            }
+        }
+        Json::object(fields)
+    }
+
+    fn variables(&self, reference: i64) -> Vec<Json> {
+        fn variable(name: &str, value: String, reference: i64) -> Json {
+            Json::object(vec![
+                ("name", Json::string(name)),
+                ("value", value.into()),
+                ("variablesReference", reference.into()),
+            ])
+        }
+        fn expandable(stack: &crate::core::Stack, reference: i64) -> i64 {
+            match stack.is_empty() {
+                true => 0,
+                false => reference,
+            }
+        }
+
+        let cpu = &self.session.cpu;
+        match reference {
+            STACKS_REFERENCE => vec![
+                variable(
+                    "data",
+                    cpu.data_st.to_string(),
+                    expandable(&cpu.data_st, DATA_REFERENCE),
+                ),
+                variable(
+                    "swap",
+                    cpu.swap_st.to_string(),
+                    expandable(&cpu.swap_st, SWAP_REFERENCE),
+                ),
+                variable(
+                    "return",
+                    cpu.return_st.to_string(),
+                    expandable(&cpu.return_st, RETURN_REFERENCE),
+                ),
+            ],
+            REGISTERS_REFERENCE => vec![
+                variable("pc", format!("{:#06x}", cpu.program_counter), 0),
+                variable("address", format!("{:#x}", cpu.memory_address), 0),
+                variable("hold", format!("{:#018x}", cpu.hold_reg.get()), 0),
+            ],
+            DATA_REFERENCE | SWAP_REFERENCE | RETURN_REFERENCE => {
+                let stack = match reference {
+                    DATA_REFERENCE => &cpu.data_st,
+                    SWAP_REFERENCE => &cpu.swap_st,
+                    _ => &cpu.return_st,
+                };
+                // listed from the top of the stack down
+                stack
+                    .as_slice()
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(depth, byte)| {
+                        variable(&format!("[{}]", depth), format!("{:#04x}", byte), 0)
+                    })
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn current_line(&self) -> Option<(usize, u32)> {
+        let entry = self.source_map.line_at(self.session.cpu.program_counter)?;
+        Some((entry.source, entry.line))
+    }
+
+    fn run(&mut self, mode: Mode, messages: &Receiver<Json>) -> io::Result<()> {
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            if steps % INTERRUPT_CHECK_INTERVAL == 0 {
+                match messages.try_recv() {
+                    Ok(message) => {
+                        self.pending.push_back(message);
+                        return Ok(());
+                    }
+                    Err(TryRecvError::Disconnected) => {
+                        self.exit = true;
+                        return Ok(());
+                    }
+                    Err(TryRecvError::Empty) => {}
+                }
+            }
+
+            let stop = match mode {
+                Mode::StepBack { .. } | Mode::ReverseContinue => self.session.step_back(),
+                _ => self.session.step(),
+            };
+            match stop {
+                Stop::Fault(fault) => {
+                    self.mode = None;
+                    return self.stopped("exception", Some(fault.to_string()));
+                }
+                Stop::HistoryStart => {
+                    self.mode = None;
+                    return self.stopped("step", Some(String::from("Start of history")));
+                }
+                _ => {}
+            }
+            if self
+                .session
+                .breakpoints
+                .contains(&self.session.cpu.program_counter)
+            {
+                self.mode = None;
+                return self.stopped("breakpoint", None);
+            }
+
+            let line = self.current_line();
+            let left_line =
+                |from: Option<(usize, u32)>| from.is_none() || (line.is_some() && line != from);
+            let depth = self.session.frames.len();
+            let done = match mode {
+                Mode::Continue | Mode::ReverseContinue => false,
+                Mode::StepIn { from } | Mode::StepBack { from } => left_line(from),
+                Mode::StepOver { depth: start, from } => depth <= start && left_line(from),
+                Mode::StepOut { depth: start } => depth < start,
+            };
+            if done {
+                self.mode = None;
+                return self.stopped("step", None);
+            }
+        }
+    }
+
+    fn stopped(&mut self, reason: &str, text: Option<String>) -> io::Result<()> {
+        let mut body = vec![
+            ("reason", Json::string(reason)),
+            ("threadId", THREAD_ID.into()),
+            ("allThreadsStopped", true.into()),
+        ];
+        if let Some(text) = text {
+            body.push(("description", text.clone().into()));
+            body.push(("text", text.into()));
+        }
+        self.event("stopped", Json::object(body))
+    }
+
+    fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+        let request_seq = request.get("seq").and_then(Json::as_i64).unwrap_or(0);
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+        let mut fields = vec![
+            ("type", Json::string("response")),
+            ("request_seq", request_seq.into()),
+            ("command", command),
+        ];
+        match result {
+            Ok(body) => {
+                fields.push(("success", true.into()));
+                fields.push(("body", body));
+            }
+            Err(message) => {
+                fields.push(("success", false.into()));
+                fields.push(("message", message.into()));
+            }
+        }
+        self.send(fields)
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(vec![
+            ("type", Json::string("event")),
+            ("event", Json::string(event)),
+            ("body", body),
+        ])
+    }
+
+    fn send(&mut self, mut fields: Vec<(&str, Json)>) -> io::Result<()> {
+        self.sequence += 1;
+        fields.insert(0, ("seq", self.sequence.into()));
+        write_message(self.output, &Json::object(fields))
+    }
+}
@@ -0,0 +1,522 @@
+use super::{Session, Stop};
+use crate::core::{Fault, CPU};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+const INTERRUPT: u8 = 0x03;
+const INTERRUPT_CHECK_INTERVAL: usize = 1024;
+const PACKET_SIZE: usize = 0x4000;
+
+const SIGINT: u8 = 0x02;
+const SIGILL: u8 = 0x04;
+const SIGTRAP: u8 = 0x05;
+const SIGABRT: u8 = 0x06;
+const SIGBUS: u8 = 0x07;
+const SIGFPE: u8 = 0x08;
+const SIGSEGV: u8 = 0x0b;
+
+// byte widths of: pc, memory_address, data sp, swap sp, return sp, hold
+const REGISTER_WIDTHS: [usize; 6] = [2, 8, 2, 2, 2, 8];
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.cohost.core">
+    <reg name="pc" bitsize="16" type="code_ptr" regnum="0"/>
+    <reg name="mar" bitsize="64" type="uint64" regnum="1"/>
+    <reg name="dsp" bitsize="16" type="uint16" regnum="2"/>
+    <reg name="ssp" bitsize="16" type="uint16" regnum="3"/>
+    <reg name="rsp" bitsize="16" type="uint16" regnum="4"/>
+    <reg name="hold" bitsize="64" type="uint64" regnum="5"/>
+  </feature>
+</target>
+"#;
+
+pub trait Connection: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+impl Connection for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+}
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+}
+
+// waits for a single client on a loopback port, or on a unix socket path
+pub fn listen(address: &str) -> io::Result<Box<dyn Connection>> {
+    if let Ok(port) = address.parse::<u16>() {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let (stream, _) = listener.accept()?;
+        stream.set_nodelay(true)?;
+        return Ok(Box::new(stream));
+    }
+
+    #[cfg(unix)]
+    {
+        let listener = UnixListener::bind(address)?;
+        let accepted = listener.accept();
+        std::fs::remove_file(address)?;
+        let (stream, _) = accepted?;
+        Ok(Box::new(stream))
+    }
+    #[cfg(not(unix))]
+    Err(io::Error::new(
+        ErrorKind::InvalidInput,
+        format!("'{}' is not a port", address),
+    ))
+}
+
+enum Action {
+    Reply(String),
+    Exit(Option<String>),
+}
+
+struct Stub<'c> {
+    connection: &'c mut dyn Connection,
+    acknowledge: bool,
+    signal: u8,
+}
+
+pub fn serve(session: &mut Session, connection: &mut dyn Connection) -> io::Result<()> {
+    let mut stub = Stub {
+        connection,
+        acknowledge: true,
+        signal: SIGTRAP,
+    };
+
+    while let Some(packet) = stub.read_packet()? {
+        match stub.handle(session, &packet) {
+            Action::Reply(reply) => stub.write_packet(&reply)?,
+            Action::Exit(reply) => {
+                if let Some(reply) = reply {
+                    stub.write_packet(&reply)?;
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Stub<'_> {
+    fn handle(&mut self, session: &mut Session, packet: &str) -> Action {
+        let Some(command) = packet.chars().next() else {
+            return Action::Reply(String::new());
+        };
+        let args = packet.get(1..).unwrap_or_default();
+
+        let reply = match command {
+            '?' => format!("S{:02x}", self.signal),
+            'g' => {
+                let mut reply = String::new();
+                for register in 0..REGISTER_WIDTHS.len() {
+                    reply.push_str(&encode_hex(&read_register(session.cpu, register)));
+                }
+                reply
+            }
+            'G' => write_registers(session.cpu, args),
+            'p' => match usize::from_str_radix(args, 16) {
+                Ok(register) if register < REGISTER_WIDTHS.len() => {
+                    encode_hex(&read_register(session.cpu, register))
+                }
+                _ => String::from("E01"),
+            },
+            'P' => match args.split_once('=') {
+                Some((register, value)) => match usize::from_str_radix(register, 16) {
+                    Ok(register) => write_register(session.cpu, register, value),
+                    Err(_) => String::from("E01"),
+                },
+                None => String::from("E01"),
+            },
+            'm' => read_memory(session.cpu, args),
+            'M' => write_memory(session.cpu, args),
+            'c' | 's' => {
+                if !args.is_empty() {
+                    match u16::from_str_radix(args, 16) {
+                        Ok(address) => session.cpu.program_counter = address,
+                        Err(_) => return Action::Reply(String::from("E01")),
+                    }
+                }
+                let stop = match command {
+                    'c' => self.resume(session, false),
+                    _ => session.step(),
+                };
+                self.stop_reply(&stop)
+            }
+            'b' => {
+                let stop = match args {
+                    "c" => self.resume(session, true),
+                    "s" => session.step_back(),
+                    _ => return Action::Reply(String::new()),
+                };
+                self.stop_reply(&stop)
+            }
+            'Z' | 'z' => match parse_breakpoint(args) {
+                Some((kind, address, len)) => {
+                    match (kind, command) {
+                        (Breakpoint::Software, 'Z') => session.breakpoints.insert(address),
+                        (Breakpoint::Software, _) => session.breakpoints.remove(&address),
+                        (Breakpoint::Write, 'Z') => {
+                            session.watchpoints.insert(address, len);
+                            true
+                        }
+                        (Breakpoint::Write, _) => session.watchpoints.remove(&address).is_some(),
+                    };
+                    String::from("OK")
+                }
+                None => String::new(), // unsupported breakpoint type
+            },
+            'H' | 'T' => String::from("OK"),
+            'D' => return Action::Exit(Some(String::from("OK"))),
+            'k' => return Action::Exit(None),
+            'q' | 'Q' => self.query(packet, session.cpu.journal.is_some()),
+            _ => String::new(),
+        };
+
+        Action::Reply(reply)
+    }
+
+    fn query(&mut self, packet: &str, reversible: bool) -> String {
+        if packet.starts_with("qSupported") {
+            let reverse = match reversible {
+                true => ";ReverseStep+;ReverseContinue+",
+                false => "",
+            };
+            return format!(
+                "PacketSize={:x};qXfer:features:read+;swbreak+;QStartNoAckMode+{}",
+                PACKET_SIZE, reverse
+            );
+        }
+        if packet == "QStartNoAckMode" {
+            self.acknowledge = false;
+            return String::from("OK");
+        }
+        if let Some(request) = packet.strip_prefix("qXfer:features:read:") {
+            let Some(("target.xml", range)) = request.split_once(':') else {
+                return String::from("E00");
+            };
+            return match parse_address_length(range) {
+                Some((offset, length)) => transfer_chunk(TARGET_XML, offset, length),
+                None => String::from("E01"),
+            };
+        }
+        match packet {
+            "qAttached" => String::from("1"),
+            "qC" => String::from("QC1"),
+            "qfThreadInfo" => String::from("m1"),
+            "qsThreadInfo" => String::from("l"),
+            "qSymbol::" => String::from("OK"),
+            _ => String::new(),
+        }
+    }
+
+    fn resume(&mut self, session: &mut Session, reverse: bool) -> Stop {
+        let connection = &mut *self.connection;
+        let mut steps = 0;
+        let mut interrupted = || {
+            steps += 1;
+            steps % INTERRUPT_CHECK_INTERVAL == 0 && poll_interrupt(connection)
+        };
+        match reverse {
+            true => session.reverse_resume(&mut interrupted),
+            false => session.resume(&mut interrupted),
+        }
+    }
+
+    fn stop_reply(&mut self, stop: &Stop) -> String {
+        self.signal = signal(stop);
+        match stop {
+            Stop::Watchpoint(address) => format!("T{:02x}watch:{:x};", self.signal, address),
+            Stop::HistoryStart => format!("T{:02x}replaylog:begin;", self.signal),
+            _ => format!("S{:02x}", self.signal),
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0u8];
+        loop {
+            match self.connection.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // skip acks, stray interrupts, and noise until a packet starts
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            let mut checksum = 0u8;
+            loop {
+                let Some(byte) = self.read_byte()? else {
+                    return Ok(None);
+                };
+                match byte {
+                    b'#' => break,
+                    b'}' => {
+                        let Some(escaped) = self.read_byte()? else {
+                            return Ok(None);
+                        };
+                        checksum = checksum.wrapping_add(byte).wrapping_add(escaped);
+                        data.push(escaped ^ 0x20);
+                    }
+                    _ => {
+                        checksum = checksum.wrapping_add(byte);
+                        data.push(byte);
+                    }
+                }
+            }
+
+            let (Some(high), Some(low)) = (self.read_byte()?, self.read_byte()?) else {
+                return Ok(None);
+            };
+            let expected = decode_hex(&[high, low]).and_then(|bytes| bytes.first().copied());
+
+            if self.acknowledge {
+                match expected == Some(checksum) {
+                    true => self.connection.write_all(b"+")?,
+                    false => {
+                        self.connection.write_all(b"-")?;
+                        continue;
+                    }
+                }
+            }
+
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let mut packet = vec![b'$'];
+        let mut checksum = 0u8;
+        for byte in data.bytes() {
+            match byte {
+                b'$' | b'#' | b'}' | b'*' => {
+                    packet.push(b'}');
+                    packet.push(byte ^ 0x20);
+                    checksum = checksum.wrapping_add(b'}').wrapping_add(byte ^ 0x20);
+                }
+                _ => {
+                    packet.push(byte);
+                    checksum = checksum.wrapping_add(byte);
+                }
+            }
+        }
+        packet.extend_from_slice(format!("#{:02x}", checksum).as_bytes());
+
+        loop {
+            self.connection.write_all(&packet)?;
+            self.connection.flush()?;
+            if !self.acknowledge {
+                return Ok(());
+            }
+            match self.read_byte()? {
+                Some(b'-') => continue, // client wants it again
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn poll_interrupt(connection: &mut dyn Connection) -> bool {
+    if connection.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let mut byte = [0u8];
+    let interrupted = match connection.read(&mut byte) {
+        Ok(0) => true, // client hung up
+        Ok(_) => byte[0] == INTERRUPT,
+        Err(_) => false,
+    };
+    let _ = connection.set_nonblocking(false);
+    interrupted
+}
+
+fn signal(stop: &Stop) -> u8 {
+    match stop {
+        Stop::Step | Stop::Breakpoint | Stop::Watchpoint(_) | Stop::HistoryStart => SIGTRAP,
+        Stop::Interrupt => SIGINT,
+        Stop::Fault(fault) => match fault {
+            Fault::StackUnderflow
+            | Fault::StackOverflow
+            | Fault::RegisterOverflow
+            | Fault::MemoryOverflow { .. }
+            | Fault::DeviceBufferOverflow { .. }
+            | Fault::ProtectedMemory { .. }
+            | Fault::UninitializedRead { .. } => SIGSEGV,
+            Fault::DivideByZero => SIGFPE,
+            Fault::InvalidDevice { .. }
+            | Fault::InvalidDMA { .. }
+            | Fault::InvalidRegion { .. }
+            | Fault::InvalidStack { .. }
+            | Fault::SpilledContext { .. } => SIGBUS,
+            Fault::PrivilegedInstruction { .. } => SIGILL,
+            Fault::UncaughtThrow { .. } | Fault::CatchMismatch => SIGABRT,
+            Fault::ReturnMismatch { .. } => SIGSEGV,
+            Fault::UnknownRoutine { .. } | Fault::LinkerFull { .. } => SIGBUS,
+            Fault::WidthMismatch(_) => SIGTRAP,
+        },
+    }
+}
+
+fn read_register(cpu: &CPU, register: usize) -> Vec<u8> {
+    match register {
+        0 => cpu.program_counter.to_le_bytes().to_vec(),
+        1 => cpu.memory_address.to_le_bytes().to_vec(),
+        2 => (cpu.data_depth() as u16).to_le_bytes().to_vec(),
+        3 => (cpu.swap_depth() as u16).to_le_bytes().to_vec(),
+        4 => (cpu.return_depth() as u16).to_le_bytes().to_vec(),
+        5 => cpu.hold_reg.get().to_le_bytes().to_vec(),
+        _ => vec![],
+    }
+}
+
+fn write_register(cpu: &mut CPU, register: usize, hex: &str) -> String {
+    let Some(&width) = REGISTER_WIDTHS.get(register) else {
+        return String::from("E01");
+    };
+    let bytes = match decode_hex(hex.as_bytes()) {
+        Some(bytes) if bytes.len() == width => bytes,
+        _ => return String::from("E01"),
+    };
+
+    let mut value = [0u8; 8];
+    value[..width].copy_from_slice(&bytes);
+    let value = u64::from_le_bytes(value);
+
+    let result = match register {
+        0 => {
+            cpu.program_counter = value as u16;
+            Ok(())
+        }
+        1 => {
+            cpu.memory_address = value;
+            Ok(())
+        }
+        2..=4 => cpu.set_stack_depth(register as u8 - 2, value as usize),
+        _ => {
+            cpu.hold_reg.set(value);
+            Ok(())
+        }
+    };
+    match result {
+        Ok(()) => String::from("OK"),
+        Err(_) => String::from("E01"),
+    }
+}
+
+fn write_registers(cpu: &mut CPU, hex: &str) -> String {
+    let mut cursor = 0;
+    for (register, width) in REGISTER_WIDTHS.iter().enumerate() {
+        let end = cursor + width * 2;
+        let Some(value) = hex.get(cursor..end) else {
+            return String::from("E01");
+        };
+        let reply = write_register(cpu, register, value);
+        if reply != "OK" {
+            return reply;
+        }
+        cursor = end;
+    }
+    String::from("OK")
+}
+
+fn read_memory(cpu: &CPU, args: &str) -> String {
+    let Some((address, length)) = parse_address_length(args) else {
+        return String::from("E01");
+    };
+    if address >= cpu.memory.len() {
+        return String::from("E01");
+    }
+
+    // reads running off the end of memory are truncated, which gdb accepts
+    let end = usize::min(address.saturating_add(length), cpu.memory.len());
+    encode_hex(&cpu.memory[address..end])
+}
+
+fn write_memory(cpu: &mut CPU, args: &str) -> String {
+    let Some((range, data)) = args.split_once(':') else {
+        return String::from("E01");
+    };
+    let Some((address, length)) = parse_address_length(range) else {
+        return String::from("E01");
+    };
+    let bytes = match decode_hex(data.as_bytes()) {
+        Some(bytes) if bytes.len() == length => bytes,
+        _ => return String::from("E01"),
+    };
+    match cpu.write_memory(address, &bytes) {
+        Ok(()) => String::from("OK"),
+        Err(_) => String::from("E01"),
+    }
+}
+
+fn transfer_chunk(document: &str, offset: usize, length: usize) -> String {
+    if offset >= document.len() {
+        return String::from("l");
+    }
+    let end = usize::min(offset + length, document.len());
+    let marker = match end == document.len() {
+        true => 'l',
+        false => 'm',
+    };
+    format!("{}{}", marker, &document[offset..end])
+}
+
+fn parse_address_length(args: &str) -> Option<(usize, usize)> {
+    let (address, length) = args.split_once(',')?;
+    let address = usize::from_str_radix(address, 16).ok()?;
+    let length = usize::from_str_radix(length, 16).ok()?;
+    Some((address, length))
+}
+
+enum Breakpoint {
+    Software,
+    Write,
+}
+
+// `type,addr,kind` -- software breakpoints, and write watchpoints of `kind` bytes
+fn parse_breakpoint(args: &str) -> Option<(Breakpoint, u16, u16)> {
+    let mut fields = args.split(',');
+    let kind = match fields.next()? {
+        "0" => Breakpoint::Software,
+        "2" => Breakpoint::Write,
+        _ => return None,
+    };
+    let address = u16::from_str_radix(fields.next()?, 16).ok()?;
+    let len = u16::from_str_radix(fields.next()?, 16).ok()?;
+    Some((kind, address, len))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
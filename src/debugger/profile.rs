@@ -0,0 +1,163 @@
+/*
+    Counts executed instructions against the stack of routines that ran
+    them. A `Call`, or a call by hash, pushes the routine holding its
+    target, named from the source map or by address when the map has no
+    routine there. A frame is popped once the return stack drops below the
+    depth its call left it at, which covers `Return` as well as a `Throw`
+    unwinding several calls.
+
+    The counts are written as folded stacks, one line per distinct stack:
+
+        main;draw;clear 1520
+
+    which flame graph tools read as is.
+*/
+use super::SourceMap;
+use crate::core::{Fault, Instruction, CPU};
+use std::collections::HashMap;
+use std::io::Write;
+
+pub struct Profile {
+    map: SourceMap,
+    names: Vec<String>,
+    frames: Vec<Frame>,
+    samples: HashMap<Vec<usize>, u64>,
+    pub instructions: u64,
+}
+#[derive(Clone, Copy)]
+struct Frame {
+    depth: usize,
+    name: usize,
+}
+
+// instruction counts for one routine, across every stack it appears in
+pub struct RoutineCount {
+    pub name: String,
+    pub own: u64,
+    pub total: u64,
+}
+
+impl Profile {
+    pub fn new(map: SourceMap) -> Profile {
+        Profile {
+            map,
+            names: Vec::new(),
+            frames: Vec::new(),
+            samples: HashMap::new(),
+            instructions: 0,
+        }
+    }
+
+    // executes one instruction, counting it against the current stack
+    pub fn step(&mut self, cpu: &mut CPU) -> Result<(), Fault> {
+        let address = cpu.program_counter;
+        let instruction = Instruction::from(cpu.memory[address as usize]);
+        if self.frames.is_empty() {
+            // the routine profiling started in is never popped
+            let name = self.name_at(address);
+            self.frames.push(Frame { depth: 0, name });
+        }
+
+        let stack: Vec<usize> = self.frames.iter().map(|frame| frame.name).collect();
+        *self.samples.entry(stack).or_insert(0) += 1;
+        self.instructions += 1;
+
+        cpu.execute()?;
+
+        let depth = cpu.return_depth();
+        while self.frames.len() > 1 && self.frames.last().is_some_and(|f| f.depth > depth) {
+            self.frames.pop();
+        }
+        if let Instruction::Call { .. } | Instruction::CallHash = instruction {
+            let name = self.name_at(cpu.program_counter);
+            self.frames.push(Frame { depth, name });
+        }
+        Ok(())
+    }
+
+    pub fn write_folded(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        let mut lines: Vec<(String, u64)> = self
+            .samples
+            .iter()
+            .map(|(stack, &count)| (self.fold(stack), count))
+            .collect();
+        lines.sort();
+        for (stack, count) in lines {
+            writeln!(out, "{} {}", stack, count)?;
+        }
+        Ok(())
+    }
+
+    // routines by instructions run in their own body, most first
+    pub fn routines(&self) -> Vec<RoutineCount> {
+        let mut counts: Vec<RoutineCount> = self
+            .names
+            .iter()
+            .map(|name| RoutineCount {
+                name: name.clone(),
+                own: 0,
+                total: 0,
+            })
+            .collect();
+        for (stack, &count) in &self.samples {
+            if let Some(&leaf) = stack.last() {
+                counts[leaf].own += count;
+            }
+            // recursion counts once towards a routine's total
+            let mut seen = stack.clone();
+            seen.sort_unstable();
+            seen.dedup();
+            for name in seen {
+                counts[name].total += count;
+            }
+        }
+        counts.sort_by(|a, b| (b.own, b.total, &a.name).cmp(&(a.own, a.total, &b.name)));
+        counts
+    }
+
+    pub fn summary(&self, top: usize) -> String {
+        let percent = |count: u64| match self.instructions {
+            0 => 0.0,
+            all => count as f64 * 100.0 / all as f64,
+        };
+
+        let mut table = format!(
+            "{:>12} {:>6} {:>12} {:>6}  routine\n",
+            "self", "%", "total", "%"
+        );
+        for routine in self.routines().into_iter().take(top) {
+            table += &format!(
+                "{:>12} {:>6.2} {:>12} {:>6.2}  {}\n",
+                routine.own,
+                percent(routine.own),
+                routine.total,
+                percent(routine.total),
+                routine.name
+            );
+        }
+        table += &format!("{} instructions", self.instructions);
+        table
+    }
+
+    fn name_at(&mut self, address: u16) -> usize {
+        let name = match self.map.routine_at(address) {
+            Some(routine) => routine.name.clone(),
+            None => format!("{:#06x}", address),
+        };
+        match self.names.iter().position(|known| *known == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name);
+                self.names.len() - 1
+            }
+        }
+    }
+
+    fn fold(&self, stack: &[usize]) -> String {
+        let names: Vec<&str> = stack
+            .iter()
+            .map(|&name| self.names[name].as_str())
+            .collect();
+        names.join(";")
+    }
+}
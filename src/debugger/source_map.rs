@@ -0,0 +1,185 @@
+/*
+    Source maps are plain text, one record per line, addresses in hex:
+
+        source <index> <path>
+        routine <start> <end> <name>
+        line <address> <source index> <line number>
+
+    Routine ranges are half open, so a routine at the top of memory ends
+    at 10000. A line record covers every address up to
+    the next line record. Source paths are relative to the map file.
+*/
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Clone)]
+pub struct RoutineSymbol {
+    pub name: String,
+    pub start: u16,
+    pub end: u32, // one past the last byte, which can be past 0xFFFF
+}
+
+#[derive(Clone, Copy)]
+pub struct LineEntry {
+    pub address: u16,
+    pub source: usize,
+    pub line: u32,
+}
+
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    pub sources: Vec<PathBuf>,
+    pub routines: Vec<RoutineSymbol>,
+    pub lines: Vec<LineEntry>,
+}
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    pub fn load(path: &Path) -> Result<SourceMap, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read source map {}: {}", path.display(), e))?;
+        let mut map: SourceMap = text.parse()?;
+        if let Some(directory) = path.parent() {
+            for source in &mut map.sources {
+                if source.is_relative() {
+                    *source = directory.join(&source);
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    pub fn routine_at(&self, address: u16) -> Option<&RoutineSymbol> {
+        self.routines
+            .iter()
+            .find(|routine| routine.start <= address && (address as u32) < routine.end)
+    }
+
+    // the line `address` is on, if it's in the routine that has the address
+    pub fn line_at(&self, address: u16) -> Option<&LineEntry> {
+        let routine = self.routine_at(address)?;
+        let index = self.lines.partition_point(|entry| entry.address <= address);
+        let entry = self.lines.get(index.checked_sub(1)?)?;
+        (entry.address >= routine.start).then_some(entry)
+    }
+
+    pub fn source_index(&self, path: &Path) -> Option<usize> {
+        let canonical = std::fs::canonicalize(path).ok();
+        self.sources
+            .iter()
+            .position(|source| match (&canonical, std::fs::canonicalize(source)) {
+                (Some(canonical), Ok(source)) => *canonical == source,
+                _ => source.ends_with(path) || path.ends_with(source),
+            })
+    }
+
+    // the lowest address of the first line at or after `line` with any code
+    pub fn address_of_line(&self, source: usize, line: u32) -> Option<LineEntry> {
+        self.lines
+            .iter()
+            .filter(|entry| entry.source == source && entry.line >= line)
+            .min_by_key(|entry| (entry.line, entry.address))
+            .copied()
+    }
+}
+impl FromStr for SourceMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn parse_address(field: Option<&str>, n: usize) -> Result<u16, String> {
+            let field = field.ok_or(format!("Source map line {}: missing address", n))?;
+            u16::from_str_radix(field, 16)
+                .map_err(|_| format!("Source map line {}: invalid address '{}'", n, field))
+        }
+        fn parse_end(field: Option<&str>, n: usize) -> Result<u32, String> {
+            let field = field.ok_or(format!("Source map line {}: missing address", n))?;
+            match u32::from_str_radix(field, 16) {
+                Ok(end) if end <= 0x1_0000 => Ok(end),
+                _ => Err(format!(
+                    "Source map line {}: invalid address '{}'",
+                    n, field
+                )),
+            }
+        }
+        fn parse_number<T: FromStr>(field: Option<&str>, n: usize) -> Result<T, String> {
+            let field = field.ok_or(format!("Source map line {}: missing number", n))?;
+            field
+                .parse()
+                .map_err(|_| format!("Source map line {}: invalid number '{}'", n, field))
+        }
+
+        let mut map = SourceMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let n = i + 1;
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                None => continue,
+                Some(comment) if comment.starts_with('#') => continue,
+                Some("source") => {
+                    let index: usize = parse_number(fields.next(), n)?;
+                    if index != map.sources.len() {
+                        return Err(format!("Source map line {}: sources out of order", n));
+                    }
+                    let path = fields.collect::<Vec<_>>().join(" ");
+                    map.sources.push(PathBuf::from(path));
+                }
+                Some("routine") => {
+                    let start = parse_address(fields.next(), n)?;
+                    let end = parse_end(fields.next(), n)?;
+                    // the rest of the line, since quoted names can have spaces
+                    let name = fields.collect::<Vec<_>>().join(" ");
+                    if name.is_empty() {
+                        return Err(format!("Source map line {}: missing routine name", n));
+                    }
+                    map.routines.push(RoutineSymbol { name, start, end });
+                }
+                Some("line") => {
+                    let address = parse_address(fields.next(), n)?;
+                    let source = parse_number(fields.next(), n)?;
+                    let line = parse_number(fields.next(), n)?;
+                    if source >= map.sources.len() {
+                        return Err(format!("Source map line {}: unknown source {}", n, source));
+                    }
+                    map.lines.push(LineEntry {
+                        address,
+                        source,
+                        line,
+                    });
+                }
+                Some(record) => {
+                    return Err(format!(
+                        "Source map line {}: unknown record '{}'",
+                        n, record
+                    ))
+                }
+            }
+        }
+        map.lines.sort_by_key(|entry| entry.address);
+        Ok(map)
+    }
+}
+impl Display for SourceMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, source) in self.sources.iter().enumerate() {
+            writeln!(f, "source {} {}", index, source.display())?;
+        }
+        for routine in &self.routines {
+            writeln!(
+                f,
+                "routine {:04x} {:04x} {}",
+                routine.start, routine.end, routine.name
+            )?;
+        }
+        for entry in &self.lines {
+            writeln!(
+                f,
+                "line {:04x} {} {}",
+                entry.address, entry.source, entry.line
+            )?;
+        }
+        Ok(())
+    }
+}
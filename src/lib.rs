@@ -1,2 +1,4 @@
 pub mod assembler;
 pub mod core;
+pub mod debugger;
+pub mod system;
@@ -0,0 +1,122 @@
+/*
+    Several CPUs stepped in a fixed round-robin order. Each round, every
+    running CPU executes `quantum` instructions in turn and then has its
+    devices serviced, so the same ROMs and wiring always produce the same run.
+*/
+use crate::core::device::{self, Device, DeviceMap, Devices, Mailbox};
+use crate::core::{Fault, CPU};
+use std::collections::HashMap;
+
+pub struct Machine {
+    pub cpu: CPU,
+    pub devices: DeviceMap,
+    pub fault: Option<Fault>,
+}
+impl Machine {
+    pub fn new(rom: Vec<u8>) -> Machine {
+        let mut cpu = CPU::new();
+        cpu.load_rom(rom);
+        Machine {
+            cpu,
+            devices: HashMap::new(),
+            fault: None,
+        }
+    }
+
+    // connects a device to a free slot, returning the slot index
+    pub fn attach(&mut self, device_type: Devices, device: Box<dyn Device>) -> Option<usize> {
+        if self.devices.contains_key(&device_type) {
+            return None;
+        }
+        let slot = self.cpu.connect_device(device_type.clone().into())?;
+        self.devices.insert(device_type, device);
+        Some(slot)
+    }
+
+    // undoes `attach`, freeing the slot
+    pub fn detach(&mut self, device_type: &Devices, slot: usize) {
+        self.devices.remove(device_type);
+        self.cpu.disconnect_device(slot);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.fault.is_none()
+    }
+
+    fn run(&mut self, quantum: usize) {
+        for _ in 0..quantum {
+            if let Err(fault) = self.cpu.execute() {
+                self.fault = Some(fault);
+                return;
+            }
+        }
+        if let Err(fault) = device::service(&mut self.cpu, &mut self.devices) {
+            self.fault = Some(fault);
+        }
+    }
+}
+
+pub struct System {
+    pub machines: Vec<Machine>,
+    pub quantum: usize,
+    pub rounds: u64,
+}
+impl System {
+    pub const DEFAULT_QUANTUM: usize = 64;
+
+    pub fn new(quantum: usize) -> System {
+        System {
+            machines: Vec::new(),
+            quantum,
+            rounds: 0,
+        }
+    }
+
+    pub fn add(&mut self, machine: Machine) -> usize {
+        self.machines.push(machine);
+        self.machines.len() - 1
+    }
+
+    // wires a mailbox on `channel` between two machines, returning each side's slot
+    pub fn connect(&mut self, a: usize, b: usize, channel: u8) -> Result<(usize, usize), String> {
+        if a == b {
+            return Err(format!("Can't connect machine {} to itself", a));
+        }
+        if a.max(b) >= self.machines.len() {
+            return Err(format!("No machine {}", a.max(b)));
+        }
+
+        let (end_a, end_b) = Mailbox::pair();
+        let slot_a = self.machines[a]
+            .attach(Devices::Mailbox(channel), Box::new(end_a))
+            .ok_or(format!("Machine {} can't take mailbox {}", a, channel))?;
+        let Some(slot_b) = self.machines[b].attach(Devices::Mailbox(channel), Box::new(end_b))
+        else {
+            // leave `a` as it was, without a mailbox to nowhere
+            self.machines[a].detach(&Devices::Mailbox(channel), slot_a);
+            return Err(format!("Machine {} can't take mailbox {}", b, channel));
+        };
+        Ok((slot_a, slot_b))
+    }
+
+    // runs one round, returning false once every machine has stopped
+    pub fn step(&mut self) -> bool {
+        let mut running = false;
+        for machine in self.machines.iter_mut().filter(|m| m.is_running()) {
+            machine.run(self.quantum);
+            running |= machine.is_running();
+        }
+        self.rounds += 1;
+        running
+    }
+
+    // runs up to `rounds` rounds, returning how many ran
+    pub fn run(&mut self, rounds: u64) -> u64 {
+        for round in 0..rounds {
+            if !self.step() {
+                return round + 1;
+            }
+        }
+        rounds
+    }
+}
@@ -0,0 +1,174 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{Context, Library, Resolver},
+};
+use cohost::core::CPU;
+use std::path::PathBuf;
+
+fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    assemble_with(Vec::new(), source)
+}
+
+// assembles with modules imported from the given library roots
+fn assemble_with(roots: Vec<PathBuf>, source: &str) -> Result<Vec<u8>, String> {
+    let mut diagnostics = Diagnostics::default();
+    let module = parse_module(source, &mut diagnostics).expect("source has errors");
+    let module = Resolver::new(roots).resolve(module)?;
+    let library = Library::new();
+    Context::new(&library, module)?.export()
+}
+
+#[test]
+fn emits_calls_and_returns() {
+    // LIT8 1, LIT16 0x0007 CAL16, RTN16; then the helper at 0x0007
+    let rom = assemble(": main LIT8 1 >helper ;\n: helper LIT8 2 ;").unwrap();
+    assert_eq!(
+        rom,
+        [0xB0, 0x01, 0xB1, 0x07, 0x00, 0x05, 0x07, 0xB0, 0x02, 0x07]
+    );
+
+    // the call returns to the instruction after it
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom);
+    for _ in 0..3 {
+        cpu.execute().unwrap();
+    }
+    assert_eq!(cpu.program_counter, 0x07);
+    assert_eq!(cpu.return_st.as_slice(), [0x06, 0x00]);
+    cpu.execute().unwrap();
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 0x06);
+    assert!(cpu.return_st.as_slice().is_empty());
+}
+
+#[test]
+fn emits_anchor_and_routine_addresses() {
+    // a relative anchor is an offset from the jump that takes it
+    let rom = assemble(": main #top LIT8 1 &top JPR8 ;").unwrap();
+    assert_eq!(rom, [0xB0, 0x01, 0xB0, 0xFC, 0x0A, 0x07]);
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom);
+    for _ in 0..3 {
+        cpu.execute().unwrap();
+    }
+    assert_eq!(cpu.program_counter, 0x00);
+
+    // absolute anchors and routine addresses are little endian
+    let rom = assemble(": main *end JMP16 #end $helper ;\n: helper ;").unwrap();
+    assert_eq!(rom, [0xB1, 0x04, 0x00, 0x09, 0xB1, 0x08, 0x00, 0x07, 0x07]);
+}
+
+#[test]
+fn layout_rejects_bad_anchors() {
+    assert_eq!(
+        assemble(": main *nowhere JMP16 ;").unwrap_err(),
+        "Using undefined anchor `nowhere` in routine `main`"
+    );
+    assert_eq!(
+        assemble(": main >nobody ;").unwrap_err(),
+        "Using undefined routine `nobody` in routine `main`"
+    );
+    assert_eq!(
+        assemble(": main #top #top ;").unwrap_err(),
+        "Duplicate anchor `top` in routine `main`"
+    );
+    // anchors belong to their routine
+    assert_eq!(
+        assemble(": main #top ;\n: other *top JMP16 ;").unwrap_err(),
+        "Using undefined anchor `top` in routine `other`"
+    );
+    assert!(assemble(": main #top *top JMP16 ;\n: other #top *top JMP16 ;").is_ok());
+
+    // an 8 bit relative jump reaches back 128 bytes at most
+    let back = |literals: usize| format!(": main #top {} &top JPR8 ;", "LIT8 0 ".repeat(literals));
+    assert_eq!(assemble(&back(63)).unwrap()[127], 0x80);
+    assert_eq!(
+        assemble(&back(64)).unwrap_err(),
+        "Relative jump to anchor `top` in routine `main` is -130 bytes, too far for 8 bits"
+    );
+}
+
+#[test]
+fn macros_take_their_arguments() {
+    assert_eq!(
+        assemble("% m [ x ] LIT8 1 ;\n: main ~m ;").unwrap_err(),
+        "Macro `m` takes 1 parameters, but was given 0"
+    );
+    assert_eq!(
+        assemble("% m [ x ] LIT8 1 ;\n: main ~m 'a 'b ;").unwrap_err(),
+        "Macro `m` takes 1 parameters, but was given 2"
+    );
+    assert_eq!(
+        assemble(": main ~nope ;").unwrap_err(),
+        "Using undefined macro: nope"
+    );
+    assert_eq!(
+        assemble("% m ~m ;\n: main ~m ;").unwrap_err(),
+        "Macro `m` expands itself"
+    );
+
+    // anchors named from a parameter are shared between macros
+    let rom =
+        assemble("% mark [ x ] #{x} ;\n% go [ x ] *{x} JMP16 ;\n: main ~mark 'a LIT8 1 ~go 'a ;");
+    assert_eq!(rom.unwrap(), [0xB0, 0x01, 0xB1, 0x00, 0x00, 0x09, 0x07]);
+}
+
+#[test]
+fn each_macro_use_gets_its_own_anchors() {
+    // LIT16 0x0000 JMP16, then LIT16 0x0004 JMP16
+    let spin = [0xB1, 0x00, 0x00, 0x09, 0xB1, 0x04, 0x00, 0x09, 0x07];
+    let rom = assemble("% spin #top *top JMP16 ;\n: main ~spin ~spin ;");
+    assert_eq!(rom.unwrap(), spin);
+
+    // including uses of a macro inside another
+    let rom = assemble("% spin #top *top JMP16 ;\n% outer ~spin ;\n: main ~outer ~outer ;");
+    assert_eq!(rom.unwrap(), spin);
+}
+
+// a scratch library root holding the given `.co` files
+fn library(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("cohost-{}-{}", name, std::process::id()));
+    for (path, source) in files {
+        let file = root.join(path);
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(file, source).unwrap();
+    }
+    root
+}
+
+#[test]
+fn imports_come_from_library_roots() {
+    let root = library(
+        "imports",
+        &[
+            ("lib/numbers.co", "% one LIT8 1 ;\n% two ~one ~one ;"),
+            ("lib/a.co", "+ .lib.b\n\t%one\n;\n% two ~one ~one ;"),
+            ("lib/b.co", "+ .lib.a\n\t%two\n;\n% one LIT8 1 ;"),
+        ],
+    );
+    let roots = vec![root.clone()];
+
+    // an imported macro brings along the macros it uses
+    let rom = assemble_with(roots.clone(), "+ .lib.numbers\n\t%two\n;\n: main ~two ;");
+    assert_eq!(rom.unwrap(), [0xB0, 0x01, 0xB0, 0x01, 0x07]);
+
+    assert_eq!(
+        assemble_with(roots.clone(), "+ .lib.numbers\n\t%three\n;\n: main ;").unwrap_err(),
+        "Module `.lib.numbers` has no macro `three`"
+    );
+    assert_eq!(
+        assemble_with(roots.clone(), "+ .lib.missing\n\t%one\n;\n: main ;").unwrap_err(),
+        format!("Couldn't find module `.lib.missing` in {}", root.display())
+    );
+    assert_eq!(
+        assemble("+ .lib.missing\n\t%one\n;\n: main ;").unwrap_err(),
+        "Couldn't find module `.lib.missing`: no library paths (use --lib-path or COHOST_LIB_PATH)"
+    );
+    assert_eq!(
+        assemble_with(roots, "+ .lib.a\n\t%two\n;\n: main ~two ;").unwrap_err(),
+        "Import cycle: .lib.a -> .lib.b -> .lib.a"
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
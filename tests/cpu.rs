@@ -0,0 +1,481 @@
+use cohost::core::{
+    str_to_opcode, Access, Fault, InitCheck, Journal, Mismatch, ShadowStack, Uninitialized,
+    WidthCheck, CPU,
+};
+
+// the bytes for a line of mnemonics and byte literals, like `LIT8 0x2A ADD8`;
+// `@0x20` pads with zeroes up to that address
+fn rom(source: &str) -> Vec<u8> {
+    let mut rom = Vec::new();
+    for word in source.split_whitespace() {
+        if let Some(address) = word.strip_prefix("@0x") {
+            rom.resize(usize::from_str_radix(address, 16).unwrap(), 0);
+            continue;
+        }
+        rom.push(match (str_to_opcode(word), word.strip_prefix("0x")) {
+            (Some(opcode), _) => opcode,
+            (None, Some(hex)) => u8::from_str_radix(hex, 16).unwrap(),
+            (None, None) => word.parse().unwrap(),
+        });
+    }
+    rom
+}
+
+fn cpu(source: &str) -> CPU {
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom(source));
+    cpu
+}
+
+// runs `steps` instructions, none of which may fault
+fn run(source: &str, steps: usize) -> CPU {
+    let mut cpu = cpu(source);
+    for _ in 0..steps {
+        cpu.execute().unwrap();
+    }
+    cpu
+}
+
+// runs until an instruction faults, returning the fault
+fn fault(cpu: &mut CPU) -> Fault {
+    for _ in 0..1_000 {
+        if let Err(fault) = cpu.execute() {
+            return fault;
+        }
+    }
+    panic!("no fault by {:#06X}", cpu.program_counter);
+}
+
+#[test]
+fn false_conditional_jump_falls_through() {
+    // a false jump used to leave the program counter on the jump itself
+    let cpu = run("LIT8 0x00 LIT8 0x20 JPC8 LIT8 0x07", 4);
+    assert_eq!(cpu.data_st.as_slice(), [0x07]);
+    assert_eq!(cpu.program_counter, 7);
+
+    let cpu = run("LIT8 0xFF LIT8 0x20 JPC8 LIT8 0x07", 3);
+    assert!(cpu.data_st.is_empty());
+    assert_eq!(cpu.program_counter, 0x20);
+}
+
+#[test]
+fn calls_return_to_the_next_instruction() {
+    // a call used to push its own address, so returning ran the call again
+    let mut cpu = run("LIT16 0x08 0x00 CAL16 LIT8 0x07 @0x08 RTN16", 2);
+    assert_eq!(cpu.return_st.as_slice(), [0x04, 0x00]);
+    assert_eq!(cpu.program_counter, 0x08);
+
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 4);
+    cpu.execute().unwrap();
+    assert_eq!(cpu.data_st.as_slice(), [0x07]);
+    assert!(cpu.return_st.is_empty());
+}
+
+#[test]
+fn arithmetic_wraps() {
+    // these used to panic on overflow in debug builds
+    let cpu = run("LIT8 0xFF LIT8 0x01 ADD8 LIT8 0x01 LIT8 0x00 SUB8", 6);
+    assert_eq!(cpu.data_st.as_slice(), [0x00, 0xFF]);
+
+    let cpu = run("LIT16 0x00 0x01 DPD16 MUL16", 3);
+    assert_eq!(cpu.data_st.as_slice(), [0x00, 0x00]);
+
+    // and so does the program counter, past the top of memory
+    let mut top = CPU::new();
+    top.load_rom(rom("@0xFFFE LIT8 0x07"));
+    top.program_counter = 0xFFFE;
+    top.execute().unwrap();
+    assert_eq!(top.data_st.as_slice(), [0x07]);
+    assert_eq!(top.program_counter, 0);
+}
+
+#[test]
+fn errors_fault_instead_of_panicking() {
+    assert_eq!(fault(&mut cpu("DRD")), Fault::StackUnderflow);
+    assert_eq!(
+        fault(&mut cpu("LIT8 0x00 LIT8 0x05 DIV8")),
+        Fault::DivideByZero
+    );
+    assert_eq!(
+        fault(&mut cpu("LIT16 0xFF 0xFF ADR16 LOD16")),
+        Fault::MemoryOverflow { address: 0x1_0001 }
+    );
+    assert_eq!(
+        fault(&mut cpu("LIT8 0x00 LIT8 0x20 DEVICE_TEST_0")),
+        Fault::InvalidDevice { index: 0x20 }
+    );
+
+    // the faulting instruction doesn't move on, and the CPU keeps working
+    let mut cpu = cpu("LIT8 0x00 LIT8 0x05 DIV8");
+    fault(&mut cpu);
+    assert_eq!(cpu.program_counter, 4);
+    cpu.program_counter = 0;
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 2);
+}
+
+#[test]
+fn stepping_back_undoes_init_and_events() {
+    // LOD8 reads 0x80 before STR8 writes it; then a device write
+    let mut cpu = CPU::new();
+    cpu.init = Some(InitCheck::new(false));
+    cpu.journal = Some(Journal::new(Journal::DEFAULT_CAPACITY));
+    cpu.load_rom(rom(
+        "LIT8 0x80 ADR8 LOD8 LIT8 0x2A STR8 LIT8 0x07 LIT8 0x00 LIT8 0x01 LIT8 0x00 DEVICE_TEST_4",
+    ));
+    let init = |cpu: &CPU| {
+        let init = cpu.init.as_ref().unwrap();
+        (init.is_initialized(0x80), init.reports.len())
+    };
+    for _ in 0..10 {
+        cpu.execute().unwrap();
+    }
+    assert_eq!(init(&cpu), (true, 1));
+
+    // the device event is raised again by replaying the write
+    assert!(cpu.step_back());
+    assert_eq!(cpu.take_device_events(), 0);
+    cpu.execute().unwrap();
+    assert_eq!(cpu.take_device_events(), 1);
+    assert!(cpu.step_back());
+    cpu.execute().unwrap();
+
+    // undoing the store clears its bit, and undoing the load its report
+    for _ in 0..6 {
+        assert!(cpu.step_back());
+    }
+    assert_eq!(cpu.program_counter, 6);
+    assert_eq!(init(&cpu), (false, 1));
+    assert_eq!(cpu.memory[0x80], 0x00);
+    assert!(cpu.step_back());
+    assert!(cpu.step_back());
+    assert_eq!(cpu.program_counter, 3);
+    assert_eq!(init(&cpu), (false, 0));
+
+    // and running forward again gives the same result
+    for _ in 0..8 {
+        cpu.execute().unwrap();
+    }
+    assert_eq!(init(&cpu), (true, 1));
+    assert_eq!(cpu.memory[0x80], 0x2A);
+    assert_eq!(cpu.take_device_events(), 1);
+}
+
+#[test]
+fn blocks_end_at_interrupt_switches_and_count_up_to_a_fault() {
+    let mut switches = cpu("LIT8 0x01 INE LIT8 0x02 IND LIT8 0x03 DRD");
+    assert_eq!(switches.execute_block(), Ok(2));
+    assert!(switches.interrupts_enabled());
+    assert_eq!(switches.execute_block(), Ok(2));
+    assert!(!switches.interrupts_enabled());
+
+    let mut cpu = cpu("LIT8 0x01 LIT8 0x00 LIT8 0x05 DIV8");
+    assert_eq!(cpu.execute_block(), Err((3, Fault::DivideByZero)));
+}
+
+#[test]
+fn user_mode_traps_into_the_supervisor() {
+    // set the syscall vector, then drop to user mode at 0x10
+    let mut cpu = cpu("LIT16 0x20 0x00 SVC LIT16 0x10 0x00 CDR16 SRT
+        @0x10 SYS INE
+        @0x20 SRT");
+    for _ in 0..5 {
+        cpu.execute().unwrap();
+    }
+    assert_eq!(cpu.program_counter, 0x10);
+    assert!(!cpu.is_supervisor());
+
+    // SYSCALL enters supervisor mode at the vector, with interrupts off
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 0x20);
+    assert!(cpu.is_supervisor());
+    assert!(!cpu.interrupts_enabled());
+    assert_eq!(cpu.return_st.as_slice(), [0x11, 0x00]);
+
+    // and SRT goes back to user mode after it, where INE is privileged
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 0x11);
+    assert!(!cpu.is_supervisor());
+    assert!(cpu.interrupts_enabled());
+    assert_eq!(
+        fault(&mut cpu),
+        Fault::PrivilegedInstruction { opcode: 0x22 }
+    );
+    assert_eq!(cpu.program_counter, 0x11);
+}
+
+#[test]
+fn regions_guard_memory() {
+    // region 0 makes 0x0100-0x01FF read-only, then a store lands in it
+    let mut read_only = cpu("LIT16 0xFF 0x01 LIT16 0x00 0x01 LIT8 0x40 LIT8 0x00 RGN
+        LIT16 0x80 0x01 ADR16 LIT8 0x2A STR8");
+    assert_eq!(
+        fault(&mut read_only),
+        Fault::ProtectedMemory {
+            address: 0x0180,
+            access: Access::Write
+        }
+    );
+    assert_eq!(read_only.memory[0x0180], 0x00);
+
+    // region 1 makes 0x0040 on no-execute, and the jump there can't fetch
+    let mut no_execute = cpu("LIT16 0xFF 0x00 LIT16 0x40 0x00 LIT8 0x20 LIT8 0x01 RGN
+        LIT8 0x40 JMP8
+        @0x40 0x00");
+    assert_eq!(
+        fault(&mut no_execute),
+        Fault::ProtectedMemory {
+            address: 0x0040,
+            access: Access::Execute
+        }
+    );
+
+    // a supervisor region can't be read from user mode
+    let mut user = cpu("LIT16 0xFF 0x01 LIT16 0x00 0x01 LIT8 0x80 LIT8 0x00 RGN
+        LIT16 0x20 0x00 CDR16 SRT
+        @0x20 LIT16 0x00 0x01 ADR16 LOD8");
+    assert_eq!(
+        fault(&mut user),
+        Fault::ProtectedMemory {
+            address: 0x0100,
+            access: Access::Read
+        }
+    );
+    assert!(!user.is_supervisor());
+}
+
+#[test]
+fn backed_stacks_spill_instead_of_overflowing() {
+    // pushes 0x55 forever
+    let mut unbacked = cpu("LIT8 0x55 LIT8 0x00 JMP8");
+    assert_eq!(fault(&mut unbacked), Fault::StackOverflow);
+    assert_eq!(unbacked.data_st.len(), 256);
+
+    // the same loop, with the data stack backed by 0x1000-0x2000
+    let mut cpu = cpu("LIT16 0x00 0x20 LIT16 0x00 0x10 LIT8 0x00 SKS
+        LIT8 0x55 LIT8 0x09 JMP8
+        @0x40 LIT8 0x00 SKR");
+    for _ in 0..4 + 3 * 400 {
+        cpu.execute().unwrap();
+    }
+    assert!(cpu.data_st.len() < 256);
+    assert!(cpu.memory[0x1000..0x1100].iter().all(|byte| *byte == 0x55));
+
+    // SKR reads back the base, limit and top of the spilled bytes
+    cpu.program_counter = 0x40;
+    cpu.execute().unwrap();
+    cpu.execute().unwrap();
+    let top = &cpu.data_st.as_slice()[cpu.data_st.len() - 6..];
+    assert_eq!(top, [0x00, 0x10, 0x00, 0x20, 0x00, 0x11]);
+}
+
+#[test]
+fn contexts_switch_between_coroutines() {
+    // main swaps to a coroutine, which loads main back, which saves itself
+    let mut main = cpu("LIT8 0xAA LIT16 0x00 0x10 LIT16 0x00 0x20 CXW
+        LIT16 0x00 0x30 CXS 0x00
+        @0x40 LIT8 0xBB LIT16 0x00 0x20 CXL");
+    main.memory[0x1000] = 0x40; // the coroutine starts at 0x40, with empty stacks
+    for _ in 0..4 {
+        main.execute().unwrap();
+    }
+    assert_eq!(main.program_counter, 0x40);
+    assert!(main.data_st.as_slice().is_empty());
+    assert_eq!(main.memory[0x2000..0x2002], [0x09, 0x00]);
+    assert_eq!(main.memory[0x200A..0x200C], [0x01, 0x00]);
+    assert_eq!(main.memory[0x2010], 0xAA);
+
+    for _ in 0..3 {
+        main.execute().unwrap();
+    }
+    assert_eq!(main.program_counter, 0x09);
+    assert_eq!(main.data_st.as_slice(), [0xAA]);
+
+    main.execute().unwrap();
+    main.execute().unwrap();
+    assert_eq!(main.program_counter, 0x0D);
+    assert_eq!(main.memory[0x3000..0x3002], [0x0D, 0x00]);
+    assert_eq!(main.memory[0x3010], 0xAA);
+
+    // a saved stack can't be longer than the stack itself
+    let mut bad = cpu("LIT16 0x00 0x10 CXL");
+    bad.memory[0x100A..0x100C].copy_from_slice(&[0x01, 0x01]);
+    assert_eq!(fault(&mut bad), Fault::StackOverflow);
+}
+
+#[test]
+fn contexts_only_hold_resident_stacks() {
+    // pushes a byte each time around 0, spilling into 0x1000
+    let mut spilled = cpu("LIT8 0x55 LIT16 0x00 0x00 JMP16
+        @0x10 LIT16 0x00 0x30 CXS
+        @0x20 LIT16 0x00 0x40 CXL");
+    spilled.back_stack(0, 0x1000, 0x2000).unwrap();
+    for _ in 0..300 * 3 {
+        spilled.execute().unwrap();
+    }
+    assert_eq!(spilled.data_depth(), 300);
+
+    // so saving refuses to leave the spilled bytes behind
+    spilled.program_counter = 0x10;
+    assert_eq!(fault(&mut spilled), Fault::SpilledContext { index: 0 });
+    assert_eq!(spilled.memory[0x3000..0x3010], [0; 16]);
+
+    // and loading replaces them along with the rest of the stack
+    spilled.program_counter = 0x20;
+    spilled.execute().unwrap();
+    spilled.execute().unwrap();
+    assert_eq!(spilled.program_counter, 0);
+    assert_eq!(spilled.data_depth(), 0);
+    let backing = spilled.stack_backing(0).unwrap();
+    assert_eq!((backing.base, backing.top), (0x1000, 0x1000));
+}
+
+#[test]
+fn throws_unwind_to_the_innermost_catch() {
+    // throws from inside a call, past a byte pushed inside the catch
+    let thrown = run(
+        "LIT8 0x11 LIT16 0x40 0x00 CTH LIT8 0x22 LIT16 0x20 0x00 CAL16
+        @0x20 LIT8 0x07 THR",
+        8,
+    );
+    assert_eq!(thrown.program_counter, 0x40);
+    assert_eq!(thrown.data_st.as_slice(), [0x11, 0x07]);
+    assert!(thrown.return_st.as_slice().is_empty());
+    assert_eq!(thrown.catch_depth, 0);
+
+    // ending a catch drops its frame
+    let ended = run("LIT16 0x40 0x00 CTH CTE", 3);
+    assert!(ended.return_st.as_slice().is_empty());
+    assert_eq!(ended.catch_depth, 0);
+
+    assert_eq!(
+        fault(&mut cpu("LIT8 0x05 THR")),
+        Fault::UncaughtThrow { code: 0x05 }
+    );
+    assert_eq!(fault(&mut cpu("CTE")), Fault::CatchMismatch);
+    // a catch can only be ended at the depth it was made
+    assert_eq!(
+        fault(&mut cpu(
+            "LIT16 0x40 0x00 CTH LIT16 0x20 0x00 CAL16 @0x20 CTE"
+        )),
+        Fault::CatchMismatch
+    );
+}
+
+#[test]
+fn shadow_stack_catches_overwritten_returns() {
+    let mut returned = cpu("LIT16 0x20 0x00 CAL16 @0x20 RTN16");
+    returned.shadow = Some(ShadowStack::new());
+    for _ in 0..3 {
+        returned.execute().unwrap();
+    }
+    assert_eq!(returned.program_counter, 0x04);
+
+    // the routine swaps its return address for 0x0040
+    let mut overwritten = cpu("LIT16 0x20 0x00 CAL16 @0x20 DRR DRR LIT16 0x40 0x00 CDR16 RTN16");
+    overwritten.shadow = Some(ShadowStack::new());
+    assert_eq!(
+        fault(&mut overwritten),
+        Fault::ReturnMismatch {
+            expected: 0x04,
+            found: 0x40
+        }
+    );
+}
+
+#[test]
+fn width_check_catches_mismatched_operands() {
+    // the top operand of the ADD16 was pushed a byte at a time
+    let mismatch = Mismatch {
+        address: 0x07,
+        producer: 0x05,
+        stack: 0,
+        pushed: 1,
+        taken: 2,
+    };
+    let mut faulted = cpu("LIT16 0x01 0x00 LIT8 2 LIT8 3 ADD16");
+    faulted.widths = Some(WidthCheck::new(true));
+    assert_eq!(fault(&mut faulted), Fault::WidthMismatch(mismatch));
+
+    // or reports it and carries on
+    let mut reported = cpu("LIT16 0x01 0x00 LIT8 2 LIT8 3 ADD16");
+    reported.widths = Some(WidthCheck::new(false));
+    for _ in 0..4 {
+        reported.execute().unwrap();
+    }
+    assert_eq!(reported.widths.unwrap().reports, [mismatch]);
+}
+
+#[test]
+fn init_check_catches_reads_of_unwritten_memory() {
+    // init checks are set before loading, so the rom's bytes count as written
+    let checked = |source: &str, fault: bool| {
+        let mut cpu = CPU::new();
+        cpu.init = Some(InitCheck::new(fault));
+        cpu.load_rom(rom(source));
+        cpu
+    };
+
+    let mut loaded = checked("LIT8 0x80 ADR8 LOD8", true);
+    assert_eq!(
+        fault(&mut loaded),
+        Fault::UninitializedRead {
+            pc: 0x03,
+            address: 0x80
+        }
+    );
+    // a literal's operand past the end of the rom
+    assert_eq!(
+        fault(&mut checked("LIT8", true)),
+        Fault::UninitializedRead {
+            pc: 0x00,
+            address: 0x01
+        }
+    );
+
+    // once written, the byte reads without a fault
+    let mut stored = checked("LIT8 0x80 ADR8 LIT8 0x2A STR8 LOD8", true);
+    for _ in 0..5 {
+        stored.execute().unwrap();
+    }
+    assert_eq!(stored.data_st.as_slice(), [0x2A, 0x2A]);
+
+    // or the read is reported, and carries on with what's there
+    let mut reported = checked("LIT8 0x80 ADR8 LOD8", false);
+    for _ in 0..3 {
+        reported.execute().unwrap();
+    }
+    assert_eq!(reported.data_st.as_slice(), [0x00]);
+    assert_eq!(
+        reported.init.unwrap().reports,
+        [Uninitialized {
+            pc: 0x03,
+            address: 0x80
+        }]
+    );
+
+    // a DMA buffer counts as written once a device fills it, not when the
+    // request for it is made
+    let source = "LIT8 0x04 LIT8 0x80 LIT8 0x80 LIT8 0x00 DMA_TEST_2 LIT8 0x80 ADR8 LOD8";
+    assert_eq!(
+        fault(&mut checked(source, true)),
+        Fault::UninitializedRead {
+            pc: 0x0C,
+            address: 0x80
+        }
+    );
+    let mut filled = checked(source, true);
+    for _ in 0..5 {
+        filled.execute().unwrap();
+    }
+    assert_eq!(
+        filled.fill_dma(0, &[0; 5]),
+        Err(Fault::MemoryOverflow { address: 0x84 })
+    );
+    filled.fill_dma(0, &[0x2A, 0x2B]).unwrap();
+    for _ in 0..3 {
+        filled.execute().unwrap();
+    }
+    assert_eq!(filled.data_st.as_slice(), [0x2A]);
+}
@@ -0,0 +1,200 @@
+#![cfg(unix)]
+
+use cohost::debugger::dap;
+use cohost::debugger::json::Json;
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+
+const SOURCE: &str = "% two [ x ] LIT8 2 ;
+: main
+    LIT8 1
+    ~two
+        'a
+    >helper ;
+: helper
+    LIT8 3 ADD8
+;
+";
+
+struct Client {
+    requests: Sender<Json>,
+    replies: BufReader<UnixStream>,
+    sequence: i64,
+}
+impl Client {
+    fn read(&mut self) -> Json {
+        dap::read_message(&mut self.replies).unwrap().unwrap()
+    }
+
+    // the response to a request, skipping any events sent before it
+    fn request(&mut self, command: &str, arguments: Json) -> Json {
+        self.sequence += 1;
+        let request = Json::object(vec![
+            ("seq", self.sequence.into()),
+            ("type", Json::string("request")),
+            ("command", Json::string(command)),
+            ("arguments", arguments),
+        ]);
+        self.requests.send(request).unwrap();
+        loop {
+            let message = self.read();
+            if message.get("type").and_then(Json::as_str) == Some("response") {
+                assert_eq!(
+                    message.get("request_seq").and_then(Json::as_i64),
+                    Some(self.sequence)
+                );
+                assert_eq!(
+                    message.get("success").and_then(Json::as_bool),
+                    Some(true),
+                    "{}",
+                    message
+                );
+                return message.get("body").cloned().unwrap_or(Json::Null);
+            }
+        }
+    }
+
+    // the body of the next event, which must be `event`
+    fn event(&mut self, event: &str) -> Json {
+        let message = self.read();
+        assert_eq!(message.get("event").and_then(Json::as_str), Some(event));
+        message.get("body").cloned().unwrap()
+    }
+
+    // the name and line of every frame, innermost first
+    fn stack(&mut self) -> Vec<(String, i64)> {
+        let trace = self.request("stackTrace", Json::object(vec![]));
+        let frames = trace.get("stackFrames").and_then(Json::as_array).unwrap();
+        frames
+            .iter()
+            .map(|frame| {
+                let name = frame.get("name").and_then(Json::as_str).unwrap();
+                let line = frame.get("line").and_then(Json::as_i64).unwrap();
+                (name.to_string(), line)
+            })
+            .collect()
+    }
+}
+
+fn stack(frames: &[(&str, i64)]) -> Vec<(String, i64)> {
+    frames
+        .iter()
+        .map(|(name, line)| (name.to_string(), *line))
+        .collect()
+}
+
+#[test]
+fn scripted_session() {
+    let directory = std::env::temp_dir().join(format!("cohost-dap-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("main.co");
+    let rom = directory.join("main.rom");
+    std::fs::write(&source, SOURCE).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_assemble"))
+        .arg("--source")
+        .arg(&source)
+        .arg("--output")
+        .arg(&rom)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let (requests, messages) = mpsc::channel();
+    let (server, replies) = UnixStream::pair().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut server = server;
+        dap::serve(messages, &mut server).unwrap();
+    });
+    let mut client = Client {
+        requests,
+        replies: BufReader::new(replies),
+        sequence: 0,
+    };
+
+    let capabilities = client.request("initialize", Json::object(vec![]));
+    assert_eq!(
+        capabilities.get("supportsStepBack").and_then(Json::as_bool),
+        Some(true)
+    );
+    client.event("initialized");
+    let program = rom.display().to_string();
+    client.request("launch", Json::object(vec![("program", program.into())]));
+
+    // a breakpoint on a line without code moves to the next line with some
+    let path = source.display().to_string();
+    let breakpoints = client.request(
+        "setBreakpoints",
+        Json::object(vec![
+            ("source", Json::object(vec![("path", path.into())])),
+            (
+                "breakpoints",
+                vec![Json::object(vec![("line", 7.into())])].into(),
+            ),
+        ]),
+    );
+    let breakpoint = &breakpoints
+        .get("breakpoints")
+        .and_then(Json::as_array)
+        .unwrap()[0];
+    assert_eq!(
+        breakpoint.get("verified").and_then(Json::as_bool),
+        Some(true)
+    );
+    assert_eq!(breakpoint.get("line").and_then(Json::as_i64), Some(8));
+
+    // run into the call
+    client.request("configurationDone", Json::object(vec![]));
+    let stopped = client.event("stopped");
+    assert_eq!(
+        stopped.get("reason").and_then(Json::as_str),
+        Some("breakpoint")
+    );
+    assert_eq!(client.stack(), stack(&[("helper", 8), ("main", 6)]));
+
+    // step over the line, then look at the data stack
+    client.request("next", Json::object(vec![]));
+    client.event("stopped");
+    assert_eq!(client.stack(), stack(&[("helper", 9), ("main", 6)]));
+    let stacks = client.request(
+        "variables",
+        Json::object(vec![("variablesReference", 1.into())]),
+    );
+    let data = &stacks.get("variables").and_then(Json::as_array).unwrap()[0];
+    let reference = data.get("variablesReference").unwrap().clone();
+    let items = client.request(
+        "variables",
+        Json::object(vec![("variablesReference", reference)]),
+    );
+    let values: Vec<&str> = items
+        .get("variables")
+        .and_then(Json::as_array)
+        .unwrap()
+        .iter()
+        .map(|variable| variable.get("value").and_then(Json::as_str).unwrap())
+        .collect();
+    assert_eq!(values, ["0x05", "0x01"]); // from the top down
+
+    // step back onto the add, and back out of the call
+    client.request("stepBack", Json::object(vec![]));
+    client.event("stopped");
+    assert_eq!(client.stack(), stack(&[("helper", 8), ("main", 6)]));
+    client.request("reverseContinue", Json::object(vec![]));
+    let stopped = client.event("stopped");
+    assert_eq!(
+        stopped.get("reason").and_then(Json::as_str),
+        Some("breakpoint")
+    );
+    client.request("reverseContinue", Json::object(vec![]));
+    let stopped = client.event("stopped");
+    assert_eq!(
+        stopped.get("text").and_then(Json::as_str),
+        Some("Start of history")
+    );
+    assert_eq!(client.stack(), stack(&[("main", 3)]));
+
+    client.request("disconnect", Json::object(vec![]));
+    server.join().unwrap();
+    std::fs::remove_dir_all(directory).unwrap();
+}
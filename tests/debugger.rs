@@ -0,0 +1,90 @@
+use cohost::core::{str_to_opcode, Journal, Linker, CPU};
+use cohost::debugger::{Session, Stop};
+
+// the bytes for a line of mnemonics and byte literals, like `LIT8 0x2A ADD8`;
+// `@0x20` pads with zeroes up to that address
+fn rom(source: &str) -> Vec<u8> {
+    let mut rom = Vec::new();
+    for word in source.split_whitespace() {
+        if let Some(address) = word.strip_prefix("@0x") {
+            rom.resize(usize::from_str_radix(address, 16).unwrap(), 0);
+            continue;
+        }
+        rom.push(match (str_to_opcode(word), word.strip_prefix("0x")) {
+            (Some(opcode), _) => opcode,
+            (None, Some(hex)) => u8::from_str_radix(hex, 16).unwrap(),
+            (None, None) => word.parse().unwrap(),
+        });
+    }
+    rom
+}
+
+// the entry of every frame, outermost first
+fn entries(session: &Session) -> Vec<u16> {
+    session.frames.iter().map(|frame| frame.entry).collect()
+}
+
+fn step(session: &mut Session, steps: usize) {
+    for _ in 0..steps {
+        assert!(matches!(session.step(), Stop::Step));
+    }
+}
+
+fn step_back(session: &mut Session, steps: usize) {
+    for _ in 0..steps {
+        assert!(matches!(session.step_back(), Stop::Step));
+    }
+}
+
+#[test]
+fn throws_pop_every_frame_they_unwind() {
+    // catches at 0x20, then calls 0x10, which calls 0x18, which throws
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom("LIT16 0x20 0x00 CTH LIT16 0x10 0x00 CAL16
+        @0x10 LIT16 0x18 0x00 CAL16
+        @0x18 LIT8 0x01 THR"));
+    cpu.journal = Some(Journal::new(Journal::DEFAULT_CAPACITY));
+    let mut session = Session::new(&mut cpu, Box::new(|_| Ok(())));
+
+    step(&mut session, 6);
+    assert_eq!(entries(&session), [0x10, 0x18]);
+    assert_eq!(session.frames[1].call_site, 0x13);
+    step(&mut session, 2);
+    assert_eq!(session.cpu.program_counter, 0x20);
+    assert!(session.frames.is_empty());
+
+    // stepping back over the throw brings both frames back
+    step_back(&mut session, 1);
+    assert_eq!(entries(&session), [0x10, 0x18]);
+    step_back(&mut session, 2);
+    assert_eq!(entries(&session), [0x10]);
+    step(&mut session, 3);
+    assert!(session.frames.is_empty());
+}
+
+#[test]
+fn calls_by_hash_push_frames() {
+    // CLH loads `LIT8 0x07 RTN16` into 0x80, and calls it
+    let hash = [0x11; 32];
+    let mut linker = Linker::new(0x80..0x100);
+    linker.add(hash, rom("LIT8 0x07 RTN16"), Vec::new());
+    let mut cpu = CPU::new();
+    let mut call = rom("CLH");
+    call.extend(hash);
+    cpu.load_rom(call);
+    cpu.linker = Some(linker);
+    cpu.journal = Some(Journal::new(Journal::DEFAULT_CAPACITY));
+    let mut session = Session::new(&mut cpu, Box::new(|_| Ok(())));
+
+    step(&mut session, 1);
+    assert_eq!(entries(&session), [0x80]);
+    assert_eq!(session.frames[0].call_site, 0x00);
+    step(&mut session, 2);
+    assert_eq!(session.cpu.program_counter, 0x21);
+    assert!(session.frames.is_empty());
+
+    step_back(&mut session, 1);
+    assert_eq!(entries(&session), [0x80]);
+    step_back(&mut session, 2);
+    assert!(session.frames.is_empty());
+}
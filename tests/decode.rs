@@ -0,0 +1,107 @@
+use cohost::core::{Instruction, CPU};
+
+/*
+    Opcodes are decoded from a table, and operands read at a fixed width.
+    The corpus files were written by the byte-at-a-time implementation the
+    table replaced: every opcode's decoding, and a hash of the trace of each
+    of a few thousand random roms. The roms leave out opcodes 0x20 to 0x3F,
+    which were added later, and those whose behaviour changed since.
+
+    That implementation already had the fault changes made alongside the
+    gdb stub, so the traces differ from the original code on purpose:
+
+    - a stack, memory, device or DMA error ends the trace with a fault,
+      where the original code panicked
+    - arithmetic and the program counter wrap, where the original code
+      panicked on overflow in debug builds
+    - a false conditional jump (0x0C to 0x0F) moves on to the next
+      instruction, where the original code left the program counter on the
+      jump, so it ran again with whatever was left on the stack
+*/
+const DECODE: &str = include_str!("corpus/decode.txt");
+const TRACES: &str = include_str!("corpus/traces.txt");
+const ROMS: usize = 3_000;
+const ROM_LEN: usize = 48;
+const STEPS: usize = 200;
+// calls, which now push the address after the call, and relative jumps,
+// whose offsets are now signed
+const CHANGED: [u8; 6] = [0x04, 0x05, 0x0A, 0x0B, 0x0E, 0x0F];
+
+// xorshift, so the roms are the same every run
+struct Random(u64);
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn rom(&mut self) -> Vec<u8> {
+        let mut rom = Vec::with_capacity(ROM_LEN);
+        while rom.len() < ROM_LEN {
+            let byte = self.next() as u8;
+            if !CHANGED.contains(&byte) && !(0x20..=0x3F).contains(&byte) {
+                rom.push(byte);
+            }
+        }
+        rom
+    }
+}
+
+// FNV-1a over everything an instruction can change, after every step
+struct Trace(u64);
+impl Trace {
+    fn add(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100_0000_01B3);
+        }
+    }
+    fn step(&mut self, cpu: &CPU) {
+        self.add(&cpu.program_counter.to_le_bytes());
+        self.add(&cpu.memory_address.to_le_bytes());
+        self.add(&cpu.hold_reg.get().to_le_bytes());
+        for stack in [&cpu.data_st, &cpu.swap_st, &cpu.return_st] {
+            self.add(&stack.len().to_le_bytes());
+            self.add(stack.as_slice());
+        }
+    }
+}
+
+fn trace(rom: Vec<u8>) -> u64 {
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom);
+    let mut trace = Trace(0xCBF2_9CE4_8422_2325);
+    for _ in 0..STEPS {
+        let faulted = cpu.execute().is_err();
+        trace.step(&cpu);
+        if faulted {
+            break;
+        }
+    }
+    trace.add(&cpu.memory);
+    trace.0
+}
+
+#[test]
+fn table_decodes_like_the_old_decoder() {
+    let decoded = (0..=255u8).map(|byte| format!("{:02X} {:?}", byte, Instruction::from(byte)));
+    for (byte, (decoded, expected)) in decoded.zip(DECODE.lines()).enumerate() {
+        if !(0x20..=0x3F).contains(&byte) {
+            assert_eq!(decoded, expected);
+        }
+    }
+    assert_eq!(DECODE.lines().count(), 256);
+}
+
+#[test]
+fn random_roms_trace_like_the_old_implementation() {
+    let mut random = Random(0x5EED_C0DE);
+    let traces: Vec<String> = (0..ROMS)
+        .map(|_| format!("{:016x}", trace(random.rom())))
+        .collect();
+    let expected: Vec<&str> = TRACES.lines().collect();
+    assert_eq!(traces.len(), expected.len());
+    for (i, (trace, expected)) in traces.iter().zip(expected).enumerate() {
+        assert_eq!(trace, expected, "rom {} traced differently", i);
+    }
+}
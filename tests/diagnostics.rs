@@ -0,0 +1,111 @@
+use cohost::assembler::{
+    diagnostic::{suggest_opcode, Diagnostic, Diagnostics, Span},
+    parsing::parse_module,
+};
+use std::process::Command;
+
+fn diagnose(source: &str) -> Diagnostics {
+    let mut diagnostics = Diagnostics::default();
+    parse_module(source, &mut diagnostics);
+    diagnostics
+}
+
+#[test]
+fn renders_the_lines_it_points_at() {
+    let source = "( adds )\n: main\n    LIT8 1 ADD9\n;\n";
+    assert_eq!(
+        diagnose(source).render(source, "main.co"),
+        "error: Dangling string literal
+ --> main.co:3:12
+  |
+2 | : main
+  | - in routine `main`
+3 |     LIT8 1 ADD9
+  |            ^^^^ not an opcode or label
+  |
+  = note: did you mean `ADD8`?
+
+1 error, 0 warnings
+"
+    );
+
+    // underlines line up under tabs, and the gutter fits the widest line number
+    let source = format!("{}: main\n\tLIT8 1 XYZZY\n;\n", "\n".repeat(9));
+    let rendered = diagnose(&source).render(&source, "main.co");
+    assert!(rendered.contains("11 | \tLIT8 1 XYZZY\n   | \t       ^^^^^ "));
+    assert!(!rendered.contains("note"));
+}
+
+#[test]
+fn renders_without_a_span() {
+    let diagnostic = Diagnostic::error("Nothing to assemble").note("add a routine");
+    assert_eq!(
+        diagnostic.render("", "main.co"),
+        "error: Nothing to assemble\n  = note: add a routine\n"
+    );
+    assert_eq!(diagnostic.to_string(), "error: Nothing to assemble");
+
+    let span = Span {
+        start: 0,
+        end: 1,
+        line: 2,
+        column: 3,
+    };
+    let diagnostic = Diagnostic::warning("Odd").primary(span, "here");
+    assert_eq!(diagnostic.to_string(), "2:3: warning: Odd");
+}
+
+#[test]
+fn suggests_opcodes_one_typo_away() {
+    assert_eq!(suggest_opcode("ADD9"), Some("ADD8"));
+    assert_eq!(suggest_opcode("DAD8"), Some("ADD8")); // swapped
+    assert_eq!(suggest_opcode("lit8"), Some("LIT8"));
+    assert_eq!(suggest_opcode("LT8"), Some("LIT8")); // missing a letter
+    assert_eq!(suggest_opcode("XYZZY"), None);
+}
+
+const ERRORS: &str = ": one ADD9 ;
+: two LIT8 1 ;
+: three SUB9 ;
+: four LT8 ;
+";
+
+#[test]
+fn reports_every_definition_with_an_error() {
+    let diagnostics = diagnose(ERRORS);
+    assert_eq!((diagnostics.errors, diagnostics.warnings), (3, 0));
+    let lines: Vec<usize> = diagnostics
+        .list
+        .iter()
+        .map(|diagnostic| diagnostic.span().unwrap().line)
+        .collect();
+    assert_eq!(lines, [1, 3, 4]);
+}
+
+#[test]
+fn assembler_prints_every_error_and_fails() {
+    let directory = std::env::temp_dir().join(format!("cohost-errors-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let source = directory.join("main.co");
+    let rom = directory.join("main.rom");
+    std::fs::write(&source, ERRORS).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_assemble"))
+        .arg("--source")
+        .arg(&source)
+        .arg("--output")
+        .arg(&rom)
+        .arg("--max-errors")
+        .arg("2")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(":1:7\n"), "{}", stderr);
+    assert!(stderr.contains(":3:9\n"), "{}", stderr);
+    assert!(!stderr.contains(":4:8\n"), "{}", stderr);
+    assert!(stderr.ends_with("1 more not shown (limit 2)\n\n3 errors, 0 warnings\n\n"));
+    assert!(!rom.exists());
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,173 @@
+#![cfg(unix)]
+
+use cohost::core::{Journal, CPU};
+use cohost::debugger::{gdb, Session};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+
+struct Client {
+    stream: UnixStream,
+}
+impl Client {
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0u8];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", packet, checksum).unwrap();
+        assert_eq!(self.read_byte(), b'+');
+
+        assert_eq!(self.read_byte(), b'$');
+        let mut reply = Vec::new();
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => reply.push(byte),
+            }
+        }
+        self.read_byte();
+        self.read_byte();
+        self.stream.write_all(b"+").unwrap();
+
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+#[test]
+fn scripted_session() {
+    let (server, client) = UnixStream::pair().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut server = server;
+        let mut cpu = CPU::new();
+        // LIT8 2, LIT8 3, ADD8, DRD, DRD
+        cpu.load_rom(vec![0xB0, 0x02, 0xB0, 0x03, 0x60, 0xFC, 0xFC]);
+        let mut session = Session::new(&mut cpu, Box::new(|_| Ok(())));
+        gdb::serve(&mut session, &mut server).unwrap();
+    });
+    let mut client = Client { stream: client };
+
+    assert!(client
+        .request("qSupported:swbreak+")
+        .contains("qXfer:features:read+"));
+    assert!(client
+        .request("qXfer:features:read:target.xml:0,1000")
+        .starts_with("l<?xml"));
+    assert_eq!(client.request("?"), "S05");
+    assert_eq!(client.request("\u{e9}1"), ""); // unknown, and not one byte wide
+
+    // run to a breakpoint on ADD8
+    assert_eq!(client.request("Z0,4,1"), "OK");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(client.request("p0"), "0400");
+    assert_eq!(client.request("p2"), "0200");
+    assert_eq!(client.request("z0,4,1"), "OK");
+
+    // step over the add and inspect all registers
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(
+        client.request("g"),
+        "050000000000000000000100000000000000000000000000"
+    );
+
+    // memory access
+    assert_eq!(client.request("m0,5"), "b002b00360");
+    assert_eq!(client.request("M100,2:abcd"), "OK");
+    assert_eq!(client.request("m100,2"), "abcd");
+    assert_eq!(client.request("mffff,4"), "00");
+
+    // register writes, then drop from an empty stack to fault
+    assert_eq!(client.request("P1=0001000000000000"), "OK");
+    assert_eq!(client.request("p1"), "0001000000000000");
+    assert_eq!(client.request("P2=0000"), "OK");
+    assert_eq!(client.request("s"), "S0b");
+
+    assert_eq!(client.request("D"), "OK");
+    server.join().unwrap();
+}
+
+#[test]
+fn reverse_session() {
+    let (server, client) = UnixStream::pair().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut server = server;
+        let mut cpu = CPU::new();
+        // LIT8 0x80, ADR8, LIT8 0x2a, STR8, DRD, DRD
+        cpu.load_rom(vec![0xB0, 0x80, 0xB4, 0xB0, 0x2A, 0xB8, 0xFC, 0xFC]);
+        cpu.journal = Some(Journal::new(Journal::DEFAULT_CAPACITY));
+        let mut session = Session::new(&mut cpu, Box::new(|_| Ok(())));
+        gdb::serve(&mut session, &mut server).unwrap();
+    });
+    let mut client = Client { stream: client };
+
+    assert!(client
+        .request("qSupported:swbreak+")
+        .contains("ReverseStep+;ReverseContinue+"));
+
+    // run forward until the store hits the watchpoint
+    assert_eq!(client.request("Z2,80,1"), "OK");
+    assert_eq!(client.request("c"), "T05watch:80;");
+    assert_eq!(client.request("p0"), "0600");
+    assert_eq!(client.request("m80,1"), "2a");
+
+    // undo the store
+    assert_eq!(client.request("bs"), "T05watch:80;");
+    assert_eq!(client.request("p0"), "0500");
+    assert_eq!(client.request("p2"), "0100");
+    assert_eq!(client.request("m80,1"), "00");
+
+    // back to a breakpoint, then to the start of history
+    assert_eq!(client.request("z2,80,1"), "OK");
+    assert_eq!(client.request("Z0,3,1"), "OK");
+    assert_eq!(client.request("bc"), "S05");
+    assert_eq!(client.request("p0"), "0300");
+    assert_eq!(client.request("z0,3,1"), "OK");
+    assert_eq!(client.request("bc"), "T05replaylog:begin;");
+    assert_eq!(
+        client.request("g"),
+        "000000000000000000000000000000000000000000000000"
+    );
+
+    // replaying forward reaches the same fault
+    assert_eq!(client.request("c"), "S0b");
+    assert_eq!(client.request("m80,1"), "2a");
+
+    assert_eq!(client.request("D"), "OK");
+    server.join().unwrap();
+}
+
+#[test]
+fn spilled_stack_session() {
+    let (server, client) = UnixStream::pair().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut server = server;
+        let mut cpu = CPU::new();
+        // LIT8 0x55, LIT16 0x0000, JMP16, pushing a byte each time around
+        cpu.load_rom(vec![0xB0, 0x55, 0xB1, 0x00, 0x00, 0x09]);
+        cpu.back_stack(0, 0x1000, 0x2000).unwrap();
+        for _ in 0..300 * 3 {
+            cpu.execute().unwrap();
+        }
+        let mut session = Session::new(&mut cpu, Box::new(|_| Ok(())));
+        gdb::serve(&mut session, &mut server).unwrap();
+    });
+    let mut client = Client { stream: client };
+
+    // the data stack pointer counts the bytes spilled to memory
+    assert_eq!(client.request("p2"), "2c01");
+    assert_eq!(client.request("m1000,2"), "5555");
+
+    // dropping below the resident part reaches into the spilled bytes
+    assert_eq!(client.request("P2=2c00"), "OK");
+    assert_eq!(client.request("p2"), "2c00");
+
+    // growing is limited by what the resident part can hold
+    assert_eq!(client.request("P2=0002"), "E01");
+    assert_eq!(client.request("P2=0001"), "OK");
+    assert_eq!(client.request("p2"), "0001");
+
+    assert_eq!(client.request("D"), "OK");
+    server.join().unwrap();
+}
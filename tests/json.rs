@@ -0,0 +1,64 @@
+use cohost::debugger::json::Json;
+
+fn parse(text: &str) -> Json {
+    text.parse().unwrap()
+}
+
+#[test]
+fn string_escapes() {
+    assert_eq!(
+        parse(r#""quote \" slash \\ \/ \n\r\t\b\f""#),
+        Json::string("quote \" slash \\ / \n\r\t\u{8}\u{c}")
+    );
+    assert_eq!(parse(r#""\u00e9\u0041""#), Json::string("\u{e9}A"));
+    assert_eq!(parse(r#""\ud83d\ude00""#), Json::string("\u{1F600}"));
+
+    // written back out, control characters are escaped
+    let text = Json::string("a\"b\\c\n\u{1}").to_string();
+    assert_eq!(text, r#""a\"b\\c\n\u0001""#);
+    assert_eq!(parse(&text), Json::string("a\"b\\c\n\u{1}"));
+}
+
+#[test]
+fn nesting() {
+    let json = parse(
+        r#" { "seq": 3, "arguments": { "lines": [ 1, [ ], { } ], "ok": true },
+              "none": null, "ratio": -1.5e1 } "#,
+    );
+    assert_eq!(json.get("seq").and_then(Json::as_i64), Some(3));
+    assert_eq!(json.get("none"), Some(&Json::Null));
+    assert_eq!(json.get("ratio"), Some(&Json::Number(-15.0)));
+
+    let arguments = json.get("arguments").unwrap();
+    assert_eq!(arguments.get("ok").and_then(Json::as_bool), Some(true));
+    let lines = arguments.get("lines").and_then(Json::as_array).unwrap();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1], Json::Array(vec![]));
+    assert_eq!(lines[2], Json::Object(vec![]));
+
+    // printing and parsing again gives the same value
+    assert_eq!(parse(&json.to_string()), json);
+}
+
+#[test]
+fn bad_input() {
+    for text in [
+        "",
+        "nul",
+        "tru",
+        "[1, 2",
+        "[1 2]",
+        "{\"a\" 1}",
+        "{\"a\": 1,}",
+        "{a: 1}",
+        "\"open",
+        "\"\\u12\"",
+        "\"\\ud83d\"",
+        "\"\\ud83d\\u0041\"",
+        "1 2",
+        "-",
+        "@",
+    ] {
+        assert!(text.parse::<Json>().is_err(), "parsed {:?}", text);
+    }
+}
@@ -0,0 +1,209 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::{parse_module, parse_text},
+    stdlib,
+    tokens::{validate_name, Spanned, TextToken},
+};
+
+fn corpus() -> Vec<&'static str> {
+    let mut corpus = vec![
+        include_str!("corpus/lexer.co"),
+        include_str!("../example/reverse.co"),
+    ];
+    corpus.extend(stdlib::modules().filter_map(stdlib::module));
+    corpus
+}
+
+fn lex(source: &str) -> Vec<Spanned> {
+    let mut diagnostics = Diagnostics::default();
+    let tokens = parse_text(source, &mut diagnostics);
+    assert!(
+        diagnostics.is_empty(),
+        "{}",
+        diagnostics.render(source, "corpus")
+    );
+    tokens
+}
+
+fn describe(tokens: &[Spanned]) -> Vec<String> {
+    tokens
+        .iter()
+        .map(|spanned| spanned.token.to_string())
+        .collect()
+}
+
+// the text of each token, with a space between any two on the same line
+fn unlex(source: &str, tokens: &[Spanned]) -> String {
+    let mut text = String::new();
+    let mut line_start = true;
+    for Spanned { token, span } in tokens {
+        let layout = matches!(token, TextToken::NewLine | TextToken::Tab(_));
+        if !line_start && !layout {
+            text.push(' ');
+        }
+        text.push_str(&source[span.start..span.end]);
+        line_start = layout;
+    }
+    text
+}
+
+#[test]
+fn corpus_round_trips() {
+    for source in corpus() {
+        let tokens = lex(source);
+        let text = unlex(source, &tokens);
+        assert_eq!(describe(&lex(&text)), describe(&tokens), "{}", text);
+    }
+}
+
+#[test]
+fn line_endings_and_indentation_dont_change_tokens() {
+    for source in corpus() {
+        let tokens = describe(&lex(source));
+        let crlf = source.replace('\n', "\r\n");
+        assert_eq!(describe(&lex(&crlf)), tokens, "{}", crlf);
+        let spaces = source.replace('\t', "    ");
+        assert_eq!(describe(&lex(&spaces)), tokens, "{}", spaces);
+    }
+}
+
+#[test]
+fn corpus_parses() {
+    for source in corpus() {
+        let mut diagnostics = Diagnostics::default();
+        let module = parse_module(source, &mut diagnostics);
+        assert!(module.is_some(), "{}", diagnostics.render(source, "corpus"));
+        assert!(
+            diagnostics.is_empty(),
+            "{}",
+            diagnostics.render(source, "corpus")
+        );
+    }
+}
+
+#[test]
+fn comments_against_tokens() {
+    let tokens = lex("%name(doc)LIT8 1;( a (b) c )[x]");
+    assert_eq!(
+        describe(&tokens),
+        [
+            "Import(Macro(name))",
+            "Comment(doc)",
+            "Assembly(LIT8)",
+            "Number(1)",
+            "Rune(CloseRoutine)",
+            "Comment(a (b) c)",
+            "Rune(OpenParameters)",
+            "String Literal(x)",
+            "Rune(CloseParameters)",
+        ]
+    );
+}
+
+#[test]
+fn strings_hold_spaces() {
+    let source = r#": "two words" "back\\slash" a.b"#;
+    let mut diagnostics = Diagnostics::default();
+    let tokens = parse_text(source, &mut diagnostics);
+    assert_eq!(
+        describe(&tokens),
+        [
+            "Rune(OpenRoutine)",
+            "String Literal(two words)",
+            r#"String Literal(back\slash)"#
+        ]
+    );
+    assert_eq!(diagnostics.errors, 1);
+}
+
+#[test]
+fn strings_are_names() {
+    for name in ["two words", "a-b?", r"back\slash"] {
+        assert_eq!(validate_name(name), Ok(()));
+    }
+    assert_eq!(
+        validate_name("top~1"),
+        Err("'top~1' contains reserved character '~'".to_string())
+    );
+    assert!(validate_name("say \"hi\"").is_err());
+
+    // a quoted name no label could refer to, or that could clash with the
+    // anchors a macro makes, is an error
+    for source in [r#": "~x" ;"#, r#": "say \"hi\"" ;"#, r#": "top~1" ;"#] {
+        let mut diagnostics = Diagnostics::default();
+        let tokens = parse_text(source, &mut diagnostics);
+        assert_eq!(
+            describe(&tokens),
+            ["Rune(OpenRoutine)", "Rune(CloseRoutine)"],
+            "{}",
+            source
+        );
+        assert_eq!(
+            diagnostics.list[0].message,
+            "String can't be used as a name"
+        );
+    }
+}
+
+#[test]
+fn labels_cant_name_macro_anchors() {
+    // `top~1` is what the first use of `m` renames its `#top` to
+    let source = "% m #top LIT8 1 *top ;\n: main ~m #top~1 LIT8 2 ;";
+    let mut diagnostics = Diagnostics::default();
+    assert!(parse_module(source, &mut diagnostics).is_none());
+    assert_eq!(diagnostics.list[0].message, "Couldn't parse token `#top~1`");
+}
+
+#[test]
+fn spans_follow_lines() {
+    let tokens = lex(": a\r\n        LIT8 1 (one\r\n two) ;");
+    let spans: Vec<_> = tokens
+        .iter()
+        .map(|spanned| (spanned.span.line, spanned.span.column))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            (1, 1),
+            (1, 3),
+            (1, 4),
+            (2, 1),
+            (2, 9),
+            (2, 14),
+            (2, 16),
+            (3, 7)
+        ]
+    );
+    assert!(matches!(tokens[3].token, TextToken::Tab(2)));
+    assert!(matches!(&tokens[6].token, TextToken::Comment(comment) if comment == "one\n two"));
+}
+
+#[test]
+fn lexing_recovers_from_errors() {
+    let source = "LIT8 ) 1 \"open\nLIT8 2 ( never closed";
+    let mut diagnostics = Diagnostics::default();
+    let tokens = parse_text(source, &mut diagnostics);
+    assert_eq!(
+        describe(&tokens),
+        [
+            "Assembly(LIT8)",
+            "Number(1)",
+            "New Line",
+            "Assembly(LIT8)",
+            "Number(2)"
+        ]
+    );
+    let messages: Vec<_> = diagnostics
+        .list
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Unmatched comment close",
+            "String is never closed",
+            "Comment is never closed"
+        ]
+    );
+}
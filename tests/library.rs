@@ -0,0 +1,121 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{hex, sha256, Library, Link, Resolver},
+};
+
+fn library(source: &str) -> Result<Library, String> {
+    let mut diagnostics = Diagnostics::default();
+    let module = parse_module(source, &mut diagnostics).expect("source has errors");
+    let module = Resolver::new(Vec::new()).resolve(module)?;
+    let mut library = Library::new();
+    library.add_module(module)?;
+    Ok(library)
+}
+
+const SOURCE: &str = "% two LIT8 2 ;
+^ double ~two MUL8 ;
+^ quadruple #again >double >double *again DRD DRD ;
+: helper ;
+";
+
+#[test]
+fn round_trips_through_bytes() {
+    let library = library(SOURCE).unwrap();
+    assert_eq!(library.routines.len(), 3);
+    assert_eq!(library.macros["two"], [0xB0, 0x02]);
+
+    // calls to other routines are linked by hash, and the rest locally
+    let double = library.resolve("double").unwrap();
+    let quadruple = &library.routines[&library.resolve("quadruple").unwrap()];
+    assert_eq!(
+        quadruple.links,
+        [
+            Link::Routine {
+                operand: 0x0001,
+                hash: double
+            },
+            Link::Routine {
+                operand: 0x0005,
+                hash: double
+            },
+            Link::Local { operand: 0x0009 },
+        ]
+    );
+    assert!(library.resolve("helper").is_none());
+
+    let bytes = library.to_bytes();
+    let read = Library::from_bytes(&bytes).unwrap();
+    assert_eq!(read.macros, library.macros);
+    assert_eq!(read.routine_names, library.routine_names);
+    assert_eq!(read.routines, library.routines);
+    assert_eq!(read.to_bytes(), bytes);
+}
+
+#[test]
+fn rejects_damaged_files() {
+    let library = library(SOURCE).unwrap();
+    let bytes = library.to_bytes();
+
+    // the header, the macro `two`, the routine count, a hash and the code's length
+    let code = 5 + 4 + (2 + 3 + 4 + 2) + 4 + 32 + 4;
+    let mut corrupted = bytes.clone();
+    corrupted[code] ^= 0xFF;
+    let hash = hex(&bytes[code - 36..code - 4].try_into().unwrap());
+    assert_eq!(
+        Library::from_bytes(&corrupted).err().unwrap(),
+        format!("Routine {} doesn't match its hash", hash)
+    );
+
+    // a name for a routine the library doesn't have
+    let mut mismatched = Library::from_bytes(&bytes).unwrap();
+    mismatched.routine_names.insert("missing".into(), [0; 32]);
+    assert_eq!(
+        Library::from_bytes(&mismatched.to_bytes()).err().unwrap(),
+        format!(
+            "Name `missing` is for {}, which isn't in the library",
+            "00".repeat(32)
+        )
+    );
+
+    assert_eq!(
+        Library::from_bytes(&bytes[..bytes.len() - 1])
+            .err()
+            .unwrap(),
+        format!("Library file ends early, at byte {}", bytes.len() - 32)
+    );
+    assert_eq!(
+        Library::from_bytes(b"CODE").err().unwrap(),
+        "Not a library file"
+    );
+}
+
+#[test]
+fn rejects_routines_calling_each_other() {
+    assert_eq!(
+        library(": a >b ;\n: b >c ;\n: c >a ;").err().unwrap(),
+        "Routines call each other in a cycle, which can't be hashed: a -> b -> c -> a"
+    );
+    // but a routine can call itself
+    assert!(library(": a >a ;").is_ok());
+}
+
+#[test]
+fn sha256_known_answers() {
+    for (message, digest) in [
+        (
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ] {
+        assert_eq!(hex(&sha256(message.as_bytes())), digest, "{:?}", message);
+    }
+}
@@ -0,0 +1,103 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{Context, Library, Module, Resolver},
+};
+use cohost::core::{Fault, InitCheck, Journal, Region, CPU};
+
+const LIBRARY: &str = "^ double LIT8 2 MUL8 ;
+^ quadruple #again >double >double *again DRD DRD ;
+";
+
+fn module(source: &str) -> Module {
+    let mut diagnostics = Diagnostics::default();
+    let module = parse_module(source, &mut diagnostics).expect("source has errors");
+    Resolver::new(Vec::new()).resolve(module).unwrap()
+}
+
+// a cpu running `source`, calling into LIBRARY loaded at `window`
+fn linked(source: &str, window: std::ops::Range<usize>) -> (CPU, Library) {
+    let mut library = Library::new();
+    library.add_module(module(LIBRARY)).unwrap();
+    let rom = Context::new(&library, module(source))
+        .unwrap()
+        .export()
+        .unwrap();
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom);
+    cpu.linker = Some(library.linker(window));
+    (cpu, library)
+}
+
+// runs until the first routine returns with nothing left to return to
+fn run(cpu: &mut CPU) -> Result<(), Fault> {
+    loop {
+        match cpu.execute() {
+            Ok(()) => continue,
+            Err(Fault::StackUnderflow) if cpu.return_st.is_empty() => return Ok(()),
+            Err(fault) => return Err(fault),
+        }
+    }
+}
+
+#[test]
+fn loads_routines_on_first_call() {
+    let (mut cpu, library) = linked(": main LIT8 3 <quadruple <quadruple ;", 0x8000..0x9000);
+    run(&mut cpu).unwrap();
+    assert_eq!(cpu.data_st.as_slice(), [48]);
+
+    // double is loaded first, and quadruple after it
+    let linker = cpu.linker.as_ref().unwrap();
+    let double = library.resolve("double").unwrap();
+    let quadruple = library.resolve("quadruple").unwrap();
+    assert_eq!(linker.address(&double), Some(0x8000));
+    assert_eq!(linker.address(&quadruple), Some(0x8004));
+
+    // with its calls linked to double, and its anchor moved with it; the
+    // second call used the same copy
+    assert_eq!(
+        cpu.memory[0x8004..0x8013],
+        [
+            0xB1, 0x00, 0x80, 0x05, 0xB1, 0x00, 0x80, 0x05, 0xB1, 0x04, 0x80, 0xFC, 0xFC, 0x07,
+            0x00
+        ]
+    );
+}
+
+#[test]
+fn faults_when_a_routine_cant_be_loaded() {
+    let (mut cpu, library) = linked(": main LIT8 3 <quadruple ;", 0x8000..0x8010);
+    let quadruple = library.resolve("quadruple").unwrap();
+    assert_eq!(run(&mut cpu), Err(Fault::LinkerFull { hash: quadruple }));
+
+    let (mut cpu, _) = linked(": main LIT8 3 <quadruple ;", 0x8000..0x9000);
+    cpu.linker = Some(Library::new().linker(0x8000..0x9000));
+    assert_eq!(
+        run(&mut cpu),
+        Err(Fault::UnknownRoutine { hash: quadruple })
+    );
+}
+
+#[test]
+fn loading_is_left_out_of_history_and_regions() {
+    let (mut cpu, _) = linked(": main LIT8 3 <quadruple ;", 0x8000..0x9000);
+    cpu.init = Some(InitCheck::new(true));
+    cpu.init.as_mut().unwrap().mark(0..0x100);
+    cpu.journal = Some(Journal::new(Journal::DEFAULT_CAPACITY));
+    let read_only = Region {
+        flags: Region::READ_ONLY_FLAG,
+        start: 0x8000,
+        last: 0x8FFF,
+    };
+    cpu.set_region(0, read_only).unwrap();
+
+    cpu.execute().unwrap();
+    cpu.execute().unwrap();
+    assert_eq!(cpu.program_counter, 0x8004);
+
+    // stepping back over the call leaves the routines loaded, and runnable
+    assert!(cpu.step_back());
+    assert_eq!(cpu.memory[0x8004], 0xB1);
+    run(&mut cpu).unwrap();
+    assert_eq!(cpu.data_st.as_slice(), [12]);
+}
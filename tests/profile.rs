@@ -0,0 +1,90 @@
+use cohost::core::CPU;
+use cohost::debugger::Profile;
+use std::process::Command;
+
+// main calls `a` twice, and `a` calls `b` each time:
+//   0x00 main: LIT16 0x0010 CAL16 LIT16 0x0010 CAL16 RTN16
+//   0x10 a:    LIT16 0x0020 CAL16 RTN16
+//   0x20 b:    LIT8 1 DRD RTN16
+fn rom() -> Vec<u8> {
+    let mut rom = vec![0xB1, 0x10, 0x00, 0x05, 0xB1, 0x10, 0x00, 0x05, 0x07];
+    rom.resize(0x10, 0);
+    rom.extend([0xB1, 0x20, 0x00, 0x05, 0x07]);
+    rom.resize(0x20, 0);
+    rom.extend([0xB0, 0x01, 0xFC, 0x07]);
+    rom
+}
+
+const MAP: &str = "
+routine 0000 0010 main
+routine 0010 0020 a
+routine 0020 0024 b
+";
+
+#[test]
+fn counts_instructions_by_stack_of_routines() {
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom());
+    let mut profile = Profile::new(MAP.parse().unwrap());
+    while cpu.program_counter != 0x08 {
+        profile.step(&mut cpu).unwrap();
+    }
+
+    let mut folded = Vec::new();
+    profile.write_folded(&mut folded).unwrap();
+    assert_eq!(
+        String::from_utf8(folded).unwrap(),
+        "main 4\nmain;a 6\nmain;a;b 6\n"
+    );
+
+    // ordered by instructions in the routine itself, then by total
+    let names: Vec<String> = profile.routines().into_iter().map(|r| r.name).collect();
+    assert_eq!(names, ["a", "b", "main"]);
+    let summary = profile.summary(2);
+    let rows: Vec<&str> = summary.lines().collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[1].ends_with("  a") && rows[1].contains(" 6 "));
+    assert!(rows[2].ends_with("  b"));
+    assert_eq!(rows[3], "16 instructions");
+}
+
+#[test]
+fn writes_the_profile_on_every_stop() {
+    let directory = std::env::temp_dir().join(format!("cohost-profile-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let rom_path = directory.join("main.rom");
+    let map = directory.join("main.map");
+    let folded = directory.join("main.folded");
+    std::fs::write(&rom_path, rom()).unwrap();
+    std::fs::write(&map, MAP).unwrap();
+
+    let profile = |steps: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cohost"));
+        command
+            .arg("--rom")
+            .arg(&rom_path)
+            .arg("--profile")
+            .arg(&folded);
+        if let Some(steps) = steps {
+            command.arg("--steps").arg(steps);
+        }
+        let output = command.output().unwrap();
+        let written = std::fs::read_to_string(&folded).unwrap();
+        std::fs::remove_file(&folded).unwrap();
+        (output, written)
+    };
+
+    // main returning with nothing on the return stack ends the program
+    let (output, written) = profile(None);
+    assert!(output.status.success());
+    assert_eq!(written, "main 4\nmain;a 6\nmain;a;b 6\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.ends_with("16 instructions\n"), "{}", stderr);
+
+    // and a step limit stops it early
+    let (output, written) = profile(Some("5"));
+    assert!(output.status.success());
+    assert_eq!(written, "main 2\nmain;a 2\nmain;a;b 1\n");
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
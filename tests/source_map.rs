@@ -0,0 +1,166 @@
+use cohost::debugger::SourceMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+const MAP: &str = "
+# written by hand
+source 0 main.co
+routine 0000 0009 main
+routine 0009 000d helper routine
+routine 000d 0010 imported
+line 0000 0 3
+line 0004 0 6
+line 0002 0 4
+line 0009 0 9
+";
+
+#[test]
+fn parses_records() {
+    let map: SourceMap = MAP.parse().unwrap();
+    assert_eq!(map.sources, [PathBuf::from("main.co")]);
+    assert_eq!(map.routines.len(), 3);
+    assert_eq!(map.routines[1].name, "helper routine");
+
+    // lines are sorted by address, and cover up to the next one
+    let lines: Vec<u32> = map.lines.iter().map(|entry| entry.line).collect();
+    assert_eq!(lines, [3, 4, 6, 9]);
+    assert_eq!(map.line_at(0x0003).map(|entry| entry.line), Some(4));
+    assert_eq!(map.line_at(0x000C).map(|entry| entry.line), Some(9));
+
+    // but not past their routine, or outside every routine
+    assert!(map.line_at(0x000D).is_none());
+    assert!(map.line_at(0x0010).is_none());
+    assert_eq!(
+        map.routine_at(0x0008).map(|r| r.name.as_str()),
+        Some("main")
+    );
+    assert!(map.routine_at(0x0010).is_none());
+
+    assert_eq!(
+        map.address_of_line(0, 5).map(|entry| entry.address),
+        Some(0x0004)
+    );
+    assert!(map.address_of_line(0, 10).is_none());
+
+    // and it writes what it reads
+    let written = map.to_string();
+    let reread: SourceMap = written.parse().unwrap();
+    assert_eq!(reread.to_string(), written);
+}
+
+#[test]
+fn routines_end_at_the_top_of_memory() {
+    let map: SourceMap = "routine ff00 10000 last".parse().unwrap();
+    assert_eq!(
+        map.routine_at(0xFFFF).map(|r| r.name.as_str()),
+        Some("last")
+    );
+    assert_eq!(map.to_string(), "routine ff00 10000 last\n");
+}
+
+#[test]
+fn rejects_bad_records() {
+    for text in [
+        "source 1 main.co",
+        "routine 0000 main",
+        "routine 0000 0001",
+        "routine 0000 10001 main",
+        "line 0000 0 1",
+        "source 0 main.co\nline 0000 0 x",
+        "symbol 0000 main",
+    ] {
+        assert!(text.parse::<SourceMap>().is_err(), "parsed {:?}", text);
+    }
+}
+
+// a scratch directory for one test's files
+fn scratch(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("cohost-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn assembler_writes_map() {
+    let directory = scratch("source-map");
+    let source = directory.join("main.co");
+    std::fs::write(
+        &source,
+        "% two [ x ] LIT8 2 ;
+: main
+    LIT8 1
+    ~two
+        'a
+    >helper ;
+: helper
+    ( nothing )
+    LIT8 3 ADD8
+;
+",
+    )
+    .unwrap();
+    let rom = directory.join("main.rom");
+    let status = Command::new(env!("CARGO_BIN_EXE_assemble"))
+        .arg("--source")
+        .arg(&source)
+        .arg("--output")
+        .arg(&rom)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let map = SourceMap::load(&rom.with_extension("map")).unwrap();
+    assert_eq!(map.source_index(&source), Some(0));
+    let routines: Vec<(&str, u16, u32)> = map
+        .routines
+        .iter()
+        .map(|r| (r.name.as_str(), r.start, r.end))
+        .collect();
+    assert_eq!(
+        routines,
+        [("main", 0x0000, 0x0009), ("helper", 0x0009, 0x000D)]
+    );
+
+    // lines without code, like a routine's name, give way to the next
+    let lines: Vec<(u16, u32)> = map.lines.iter().map(|e| (e.address, e.line)).collect();
+    assert_eq!(
+        lines,
+        [
+            (0x0000, 3),
+            (0x0002, 4),
+            (0x0004, 6),
+            (0x0009, 9),
+            (0x000C, 10)
+        ]
+    );
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn assembler_maps_a_routine_ending_at_the_top_of_memory() {
+    let directory = scratch("source-map-top");
+    let source = directory.join("main.co");
+    // 0xFFFF bytes of main, then a one byte routine at 0xFFFF
+    let body = "LIT8 1\n".repeat(0x7FFF);
+    std::fs::write(&source, format!(": main\n{};\n: last ;\n", body)).unwrap();
+    let rom = directory.join("main.rom");
+    let status = Command::new(env!("CARGO_BIN_EXE_assemble"))
+        .arg("--source")
+        .arg(&source)
+        .arg("--output")
+        .arg(&rom)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(std::fs::read(&rom).unwrap().len(), 0x1_0000);
+
+    let map = SourceMap::load(&rom.with_extension("map")).unwrap();
+    let last = map.routine_at(0xFFFF).unwrap();
+    assert_eq!(
+        (last.name.as_str(), last.start, last.end),
+        ("last", 0xFFFF, 0x1_0000)
+    );
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,78 @@
+use cohost::assembler::{
+    diagnostic::Diagnostics,
+    parsing::parse_module,
+    representation::{Context, Library, Resolver},
+    stdlib,
+};
+use cohost::core::{Fault, CPU};
+
+fn assemble(source: &str) -> Vec<u8> {
+    let mut diagnostics = Diagnostics::default();
+    let module = parse_module(source, &mut diagnostics).expect("source has errors");
+    let module = Resolver::new(Vec::new()).resolve(module).unwrap();
+    let library = Library::new();
+    Context::new(&library, module).unwrap().export().unwrap()
+}
+
+// runs until the first routine returns with nothing left to return to
+fn run(source: &str) -> CPU {
+    let mut cpu = CPU::new();
+    cpu.load_rom(assemble(source));
+    for _ in 0..10_000 {
+        match cpu.execute() {
+            Ok(()) => continue,
+            Err(Fault::StackUnderflow) if cpu.return_st.is_empty() => return cpu,
+            Err(fault) => panic!("{} at {:#06X}", fault, cpu.program_counter),
+        }
+    }
+    panic!("still running at {:#06X}", cpu.program_counter);
+}
+
+#[test]
+fn every_module_assembles() {
+    for module in stdlib::modules() {
+        let source = format!("+ {}\n;\n: main ;\n", module);
+        assert_eq!(assemble(&source), vec![0x07], "{}", module);
+    }
+}
+
+#[test]
+fn stack_shuffles() {
+    let cpu = run("+ .co.stack\n\t%exchange8 %over8 %nip8 %exchange16\n;\n\
+        : main LIT8 1 LIT8 2 ~exchange8 ~over8 ~nip8 LIT16 0x0304 LIT16 0x0506 ~exchange16 ;");
+    assert_eq!(cpu.data_st.as_slice(), [2, 2, 6, 5, 4, 3]);
+}
+
+#[test]
+fn arithmetic_helpers() {
+    let cpu = run("+ .co.math\n\t%minus8 %decrement8 %negate16 %increment16\n;\n\
+        : main LIT8 5 LIT8 3 ~minus8 LIT8 0 ~decrement8 LIT16 2 ~negate16 LIT16 0xFFFF ~increment16 ;");
+    assert_eq!(cpu.data_st.as_slice(), [2, 0xFF, 0xFE, 0xFF, 0, 0]);
+}
+
+#[test]
+fn conditionals() {
+    let cpu = run("+ .co.cond\n\t%if %end-if %else %end-else\n;\n: main\n\
+        \tLIT8 1 ~if 'a LIT8 10 ~end-if 'a\n\
+        \tLIT8 0 ~if 'b LIT8 20 ~end-if 'b\n\
+        \tLIT8 0 ~if 'c LIT8 30 ~else 'c LIT8 31 ~end-else 'c\n\
+        \tLIT8 1 ~if 'd LIT8 40 ~else 'd LIT8 41 ~end-else 'd\n;");
+    assert_eq!(cpu.data_st.as_slice(), [10, 31, 40]);
+}
+
+#[test]
+fn counted_loops() {
+    let cpu = run(
+        "+ .co.loop\n\t%times %end-times\n;\n+ .co.math\n\t%increment8\n;\n\
+        : main LIT8 0 LIT8 4 ~times 'count ~increment8 ~end-times 'count ;",
+    );
+    assert_eq!(cpu.data_st.as_slice(), [4]);
+    assert!(cpu.return_st.is_empty());
+}
+
+#[test]
+fn reverse_example() {
+    let source = include_str!("../example/reverse.co");
+    let cpu = run(source);
+    assert_eq!(cpu.data_st.as_slice(), [3, 2, 1]);
+}
@@ -0,0 +1,123 @@
+use cohost::core::device::{self, Device, DeviceMap, Devices};
+use cohost::core::CPU;
+use cohost::system::{Machine, System};
+use std::collections::VecDeque;
+
+// writes 0x2A to device slot 0 with SEND|DONE, then spins
+const SENDER: [u8; 12] = [
+    0xB0, 0x2A, 0xB0, 0x00, 0xB0, 0xC0, 0xB0, 0x00, 0x94, 0xB0, 0x09, 0x08,
+];
+
+// spins at 0; the handler at 0x10 copies the first byte in to 0x100, then
+// turns interrupts back on
+fn receiver() -> Vec<u8> {
+    let mut rom = vec![0xB0, 0x00, 0x08];
+    rom.resize(0x10, 0);
+    rom.extend([
+        0xB0, 0x00, 0xB0, 0x00, 0x90, 0xB1, 0x00, 0x01, 0xB5, 0xB8, 0xFC, 0x22, 0x07,
+    ]);
+    rom
+}
+
+fn mailbox_system() -> System {
+    let mut system = System::new(System::DEFAULT_QUANTUM);
+    let a = system.add(Machine::new(SENDER.to_vec()));
+    let b = system.add(Machine::new(receiver()));
+    let (slot_a, slot_b) = system.connect(a, b, 7).unwrap();
+    assert_eq!(slot_a, 0);
+    system.machines[b].cpu.devices[slot_b].vector = 0x10;
+    system
+}
+
+#[test]
+fn mailbox_delivers_between_cpus() {
+    let mut system = mailbox_system();
+    assert_eq!(system.run(8), 8);
+    for machine in &system.machines {
+        assert!(machine.is_running());
+    }
+    assert_eq!(system.machines[1].cpu.memory[0x100], 0x2A);
+}
+
+#[test]
+fn connect_rejects_bad_machines() {
+    let mut system = mailbox_system();
+    assert!(system.connect(0, 0, 1).is_err());
+    assert!(system.connect(0, 2, 1).is_err());
+    assert!(system.connect(0, 1, 7).is_err());
+}
+
+#[test]
+fn failed_connect_leaves_the_first_machine_alone() {
+    let mut system = mailbox_system();
+    let c = system.add(Machine::new(SENDER.to_vec()));
+
+    // `c` takes the mailbox, then machine 1 can't, already having one
+    assert!(system.connect(c, 1, 7).is_err());
+    assert!(system.machines[c].devices.is_empty());
+    assert_eq!(system.machines[c].cpu.slot_mask, 0);
+    assert!(system.machines[c].cpu.devices[0] == system.machines[c].cpu.devices[1]);
+
+    // so it can still be wired up to someone else
+    assert_eq!(system.connect(c, 0, 3), Ok((0, 1)));
+}
+
+#[test]
+fn runs_are_deterministic() {
+    let (mut first, mut second) = (mailbox_system(), mailbox_system());
+    first.run(100);
+    second.run(100);
+    for (a, b) in first.machines.iter().zip(&second.machines) {
+        assert_eq!(a.cpu.program_counter, b.cpu.program_counter);
+        assert_eq!(a.cpu.memory, b.cpu.memory);
+        assert_eq!(a.cpu.data_st.as_slice(), b.cpu.data_st.as_slice());
+        assert_eq!(a.cpu.return_st.as_slice(), b.cpu.return_st.as_slice());
+        assert!(a.cpu.devices == b.cpu.devices);
+    }
+}
+
+// a device with buffers already waiting to come in
+struct Queue(VecDeque<[u8; 64]>);
+impl Device for Queue {
+    fn poll(&mut self) -> Option<[u8; 64]> {
+        self.0.pop_front()
+    }
+    fn recv(&mut self, _: &[u8; 64]) {}
+}
+
+#[test]
+fn queued_buffers_wait_for_the_handler() {
+    // spins at 0; the handler at 0x10 turns interrupts back on and returns
+    let mut rom = vec![0xB0, 0x00, 0x08];
+    rom.resize(0x10, 0);
+    rom.extend([0x22, 0x07]);
+    let mut cpu = CPU::new();
+    cpu.load_rom(rom);
+    let id = Devices::Other([1; 32]);
+    let slot = cpu.connect_device(id.clone().into()).unwrap();
+    cpu.devices[slot].vector = 0x10;
+    let mut devices: DeviceMap = DeviceMap::new();
+    devices.insert(id, Box::new(Queue(VecDeque::from([[1; 64], [2; 64]]))));
+
+    // the first buffer interrupts, even from supervisor mode, and the second
+    // waits until the handler turns interrupts back on
+    device::service(&mut cpu, &mut devices).unwrap();
+    assert_eq!(
+        (cpu.program_counter, cpu.devices[slot].in_buffer),
+        (0x10, [1; 64])
+    );
+    assert!(!cpu.interrupts_enabled());
+    device::service(&mut cpu, &mut devices).unwrap();
+    assert_eq!(
+        (cpu.program_counter, cpu.devices[slot].in_buffer),
+        (0x10, [1; 64])
+    );
+
+    cpu.execute().unwrap();
+    device::service(&mut cpu, &mut devices).unwrap();
+    assert_eq!(
+        (cpu.program_counter, cpu.devices[slot].in_buffer),
+        (0x10, [2; 64])
+    );
+    assert_eq!(cpu.return_depth(), 4);
+}